@@ -0,0 +1,77 @@
+//! The backend's `x-api-token` signing, shared by the merchant emulator and
+//! the mock backend so both sign and verify bodies the same way.
+
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Header carrying the HMAC-SHA256 signature, both on requests and responses.
+pub const SIGNATURE_HEADER: &str = "x-api-token";
+
+/// Serializes a JSON value the way the backend does before signing:
+/// top-level keys sorted, nested values left in their original order, and
+/// numbers printed as JavaScript's `JSON.stringify` prints them. Key order
+/// cannot be left to `serde_json::Value`, which keeps insertion order when
+/// `preserve_order` is on.
+pub fn canonical_json(value: &Value) -> String {
+    match js_numbers(value) {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            
+            let sorted: serde_json::Map<String, Value> = entries.into_iter().collect();
+            Value::Object(sorted).to_string()
+        }
+        other => other.to_string(),
+    }
+}
+
+/// JavaScript has no separate integer type, so whole floats print without a
+/// fraction: `5000.0` becomes `5000` and `-0.0` becomes `0`. Amounts are f64
+/// here, and signing them as serde prints them would never match the backend.
+fn js_numbers(value: &Value) -> Value {
+    const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+    
+    match value {
+        Value::Number(number) => match number.as_f64() {
+            Some(f) if number.is_f64() && f.fract() == 0.0 && f.abs() <= MAX_SAFE_INTEGER => Value::from(f as i64),
+            _ => value.clone(),
+        },
+        Value::Array(items) => Value::Array(items.iter().map(js_numbers).collect()),
+        Value::Object(map) => Value::Object(map.iter().map(|(k, v)| (k.clone(), js_numbers(v))).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Signs a body exactly as it goes over the wire; pass it through
+/// `canonical_json` first.
+pub fn sign(private_key: &str, payload: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(private_key.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(payload.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+pub fn sign_value(private_key: &str, value: &Value) -> String {
+    sign(private_key, &canonical_json(value))
+}
+
+/// Checks a signature over a raw JSON body. Bodies that are not valid JSON
+/// are signed as-is, matching what the backend does for plain strings.
+pub fn verify(private_key: &str, body: &str, signature: &str) -> bool {
+    let payload = match serde_json::from_str::<Value>(body) {
+        Ok(value) => canonical_json(&value),
+        Err(_) => body.to_string(),
+    };
+    
+    let Ok(expected) = hex::decode(signature) else {
+        return false;
+    };
+    
+    let mut mac = HmacSha256::new_from_slice(private_key.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(payload.as_bytes());
+    mac.verify_slice(&expected).is_ok()
+}
@@ -1,55 +1,68 @@
-use crate::models::CallbackRequest;
+use crate::models::{CallbackBehavior, CallbackOutcome, CallbackRequest, CallbackSignature, ReceivedCallback};
+use crate::services::StorageService;
 use axum::{
-    extract::State,
-    http::StatusCode,
-    response::Json,
+    body::Bytes,
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
     routing::post,
     Router,
 };
+use chrono::Utc;
+use std::collections::BTreeMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
 use tower_http::cors::CorsLayer;
-use tracing::{error, info};
+use tracing::{error, info, warn};
+use uuid::Uuid;
 
 #[derive(Clone)]
 pub struct CallbackState {
-    pub sender: mpsc::UnboundedSender<CallbackRequest>,
+    pub sender: mpsc::UnboundedSender<ReceivedCallback>,
+    pub storage: Arc<StorageService>,
 }
 
 pub struct CallbackServer {
     port: u16,
-    receiver: mpsc::UnboundedReceiver<CallbackRequest>,
-    sender: mpsc::UnboundedSender<CallbackRequest>,
+    storage: Arc<StorageService>,
+    receiver: Option<mpsc::UnboundedReceiver<ReceivedCallback>>,
+    sender: mpsc::UnboundedSender<ReceivedCallback>,
 }
 
 impl CallbackServer {
-    pub fn new(port: u16) -> Self {
+    pub fn new(port: u16, storage: Arc<StorageService>) -> Self {
         let (sender, receiver) = mpsc::unbounded_channel();
         Self {
             port,
-            receiver,
+            storage,
+            receiver: Some(receiver),
             sender,
         }
     }
     
-    pub fn get_sender(&self) -> mpsc::UnboundedSender<CallbackRequest> {
+    pub fn get_sender(&self) -> mpsc::UnboundedSender<ReceivedCallback> {
         self.sender.clone()
     }
     
-    pub async fn try_recv(&mut self) -> Option<CallbackRequest> {
-        self.receiver.try_recv().ok()
+    /// Hands the receiving end of the callback channel to a consumer.
+    /// Must be called before `start`, which consumes the server.
+    pub fn take_receiver(&mut self) -> Option<mpsc::UnboundedReceiver<ReceivedCallback>> {
+        self.receiver.take()
     }
     
     pub async fn start(self) -> Result<(), Box<dyn std::error::Error>> {
         let state = CallbackState {
             sender: self.sender.clone(),
+            storage: self.storage.clone(),
         };
         
         let app = Router::new()
             .route("/callback", post(handle_callback))
+            .route("/callback/{merchant_id}", post(handle_merchant_callback))
             .layer(CorsLayer::permissive())
             .with_state(Arc::new(state));
-            
+        
         let addr = format!("0.0.0.0:{}", self.port);
         let listener = tokio::net::TcpListener::bind(&addr).await?;
         
@@ -63,14 +76,118 @@ impl CallbackServer {
 
 async fn handle_callback(
     State(state): State<Arc<CallbackState>>,
-    Json(payload): Json<CallbackRequest>,
-) -> StatusCode {
-    info!("Received callback: {:?}", payload);
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    info!("Received callback: {}", String::from_utf8_lossy(&body));
     
-    if let Err(e) = state.sender.send(payload) {
-        error!("Failed to send callback to channel: {}", e);
-        return StatusCode::INTERNAL_SERVER_ERROR;
+    respond(&state, None, "/callback".to_string(), &headers, &body, &CallbackBehavior::default()).await
+}
+
+async fn handle_merchant_callback(
+    State(state): State<Arc<CallbackState>>,
+    Path(merchant_id): Path<Uuid>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    info!("Received callback for merchant {}: {}", merchant_id, String::from_utf8_lossy(&body));
+    
+    let path = format!("/callback/{}", merchant_id);
+    
+    let Some(merchant) = state.storage.get_merchant(&merchant_id) else {
+        warn!("Callback for unknown merchant {}", merchant_id);
+        forward(&state, capture(Some(merchant_id), path, &headers, &body, CallbackOutcome::Respond(404)));
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    
+    respond(&state, Some(merchant_id), path, &headers, &body, &merchant.callback_behavior).await
+}
+
+/// Records the callback, then answers the way the merchant is configured to.
+async fn respond(
+    state: &CallbackState,
+    merchant_id: Option<Uuid>,
+    path: String,
+    headers: &HeaderMap,
+    body: &Bytes,
+    behavior: &CallbackBehavior,
+) -> Response {
+    let outcome = behavior.outcome(&mut rand::thread_rng());
+    let mut received = capture(merchant_id, path, headers, body, outcome);
+    
+    if received.callback.is_none() {
+        let error = received.parse_error.clone().unwrap_or_default();
+        received.outcome = CallbackOutcome::Respond(400);
+        forward(state, received);
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": error }))).into_response();
+    }
+    
+    if !forward(state, received) {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+    
+    if behavior.latency_ms > 0 {
+        tokio::time::sleep(Duration::from_millis(behavior.latency_ms)).await;
     }
     
-    StatusCode::OK
-}
\ No newline at end of file
+    match outcome {
+        CallbackOutcome::Respond(code) => status_code(code).into_response(),
+        CallbackOutcome::Fail(code) => {
+            (status_code(code), Json(serde_json::json!({ "error": "Simulated failure" }))).into_response()
+        }
+        CallbackOutcome::Timeout => {
+            tokio::time::sleep(Duration::from_millis(behavior.timeout_ms)).await;
+            StatusCode::GATEWAY_TIMEOUT.into_response()
+        }
+        CallbackOutcome::Malformed => {
+            (StatusCode::OK, [(header::CONTENT_TYPE, "application/json")], "{\"status\": ok").into_response()
+        }
+    }
+}
+
+/// Keeps the raw headers and body alongside the parsed payload.
+fn capture(
+    merchant_id: Option<Uuid>,
+    path: String,
+    headers: &HeaderMap,
+    body: &Bytes,
+    outcome: CallbackOutcome,
+) -> ReceivedCallback {
+    let headers: BTreeMap<String, String> = headers
+        .iter()
+        .map(|(name, value)| (name.to_string(), String::from_utf8_lossy(value.as_bytes()).into_owned()))
+        .collect();
+    
+    let (callback, parse_error) = match CallbackRequest::from_body(body) {
+        Ok(callback) => (Some(callback), None),
+        Err(e) => {
+            warn!("Failed to parse callback body: {}", e);
+            (None, Some(e.to_string()))
+        }
+    };
+    ReceivedCallback {
+        id: Uuid::new_v4(),
+        merchant_id,
+        path,
+        headers,
+        raw_body: String::from_utf8_lossy(body).into_owned(),
+        callback,
+        parse_error,
+        matched_order_id: None,
+        signature: CallbackSignature::NotChecked,
+        received_at: Utc::now(),
+        outcome,
+    }
+}
+
+fn forward(state: &CallbackState, received: ReceivedCallback) -> bool {
+    if let Err(e) = state.sender.send(received) {
+        error!("Failed to send callback to channel: {}", e);
+        return false;
+    }
+    true
+}
+
+fn status_code(code: u16) -> StatusCode {
+    StatusCode::from_u16(code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+}
@@ -1,58 +1,108 @@
 use crate::models::*;
-use anyhow::{anyhow, Result};
-use reqwest::{Client, StatusCode};
+use crate::api::signature::{self, SIGNATURE_HEADER};
+use crate::api::error::{ApiError, ApiResult};
+use crate::services::receipt_generator::SyntheticReceipt;
+use reqwest::multipart::{Form, Part};
+use crate::services::StatisticsService;
+use chrono::{DateTime, Utc};
+use reqwest::header::RETRY_AFTER;
+use rand::rngs::StdRng;
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use std::cell::RefCell;
+use std::future::Future;
+use std::sync::Arc;
 use std::time::Duration;
-use tracing::{debug, info, error};
+use tokio::time::sleep;
+use tracing::{debug, info, error, warn};
 
 pub struct ApiClient {
-    client: Client,
-    base_url: String,
+    pub(super) client: Client,
+    pub(super) base_url: String,
+    retry: RetryConfig,
+    stats_service: Arc<StatisticsService>,
 }
 
 impl ApiClient {
-    pub fn new(base_url: String) -> Result<Self> {
+    pub fn new(base_url: String, retry: RetryConfig, stats_service: Arc<StatisticsService>) -> anyhow::Result<Self> {
         let client = Client::builder()
             .timeout(Duration::from_secs(30))
             .build()?;
             
-        Ok(Self { client, base_url })
+        Ok(Self { client, base_url, retry, stats_service })
     }
     
-    pub async fn connect(&self, api_key: &str) -> Result<MerchantInfo> {
+    /// Sends the request produced by `build`, retrying per the operation's policy.
+    /// The last response is returned as-is, so callers still see the final
+    /// status and body once retries run out.
+    pub(super) async fn send<F>(&self, credentials: &MerchantCredentials, operation: ApiOperation, build: F) -> ApiResult<Response>
+    where
+        F: Fn() -> ApiResult<RequestBuilder>,
+    {
+        let policy = self.retry.policy(operation);
+        let mut retries = 0;
+        
+        loop {
+            let result = build()?.send().await;
+            
+            let retry = match &result {
+                Ok(response) => response_retry(policy, response, retries),
+                Err(e) => transport_retry(policy, e, retries),
+            };
+            
+            let Some((delay, reason)) = retry.filter(|_| retries < policy.max_retries) else {
+                if retries > 0 && result.as_ref().is_ok_and(|r| r.status().is_success()) {
+                    self.stats_service.record_retry_recovered(credentials.merchant_id);
+                }
+                return Ok(result?);
+            };
+            
+            retries += 1;
+            warn!("{:?} failed ({}), retry {}/{} in {:?}", operation, reason, retries, policy.max_retries, delay);
+            
+            self.stats_service.record_retry(credentials.merchant_id, operation, reason);
+            sleep(delay).await;
+        }
+    }
+    
+    pub async fn connect(&self, credentials: &MerchantCredentials) -> ApiResult<MerchantInfo> {
         let url = format!("{}/api/merchant/connect", self.base_url);
         
-        let response = self.client
-            .get(&url)
-            .header("x-merchant-api-key", api_key)
-            .send()
-            .await?;
+        let response = self.send(credentials, ApiOperation::Connect, || {
+            Ok(self.client
+                .get(&url)
+                .header("x-merchant-api-key", &credentials.api_key))
+        }).await?;
             
         let status = response.status();
+        let signature = response_signature(&response);
         let body = response.text().await?;
         
+        verify_response(credentials, status, signature.as_deref(), &body, Signing::Optional)?;
+        
         debug!("Connect response: status={}, body={}", status, body);
         
         if status.is_success() {
             Ok(serde_json::from_str(&body)?)
         } else {
-            let error: ErrorResponse = serde_json::from_str(&body)
-                .unwrap_or_else(|_| ErrorResponse { error: body });
-            Err(anyhow!("API error ({}): {}", status, error.error))
+            Err(ApiError::from_response(status, &body))
         }
     }
     
-    pub async fn get_balance(&self, api_key: &str) -> Result<f64> {
+    pub async fn get_balance(&self, credentials: &MerchantCredentials) -> ApiResult<f64> {
         let url = format!("{}/api/merchant/balance", self.base_url);
         
-        let response = self.client
-            .get(&url)
-            .header("x-merchant-api-key", api_key)
-            .send()
-            .await?;
+        let response = self.send(credentials, ApiOperation::Balance, || {
+            Ok(self.client
+                .get(&url)
+                .header("x-merchant-api-key", &credentials.api_key))
+        }).await?;
             
         let status = response.status();
+        let signature = response_signature(&response);
         let body = response.text().await?;
         
+        verify_response(credentials, status, signature.as_deref(), &body, Signing::Optional)?;
+        
         debug!("Balance API response: status={}, body={}", status, body);
         
         if status.is_success() {
@@ -60,120 +110,599 @@ impl ApiClient {
                 Ok(balance) => Ok(balance.balance),
                 Err(e) => {
                     error!("Failed to parse balance response: {}, body: {}", e, body);
-                    Err(anyhow!("Failed to parse balance response: {}", e))
+                    Err(e.into())
                 }
             }
         } else {
-            let error: ErrorResponse = serde_json::from_str(&body)
-                .unwrap_or_else(|_| ErrorResponse { error: body });
-            Err(anyhow!("API error ({}): {}", status, error.error))
+            Err(ApiError::from_response(status, &body))
         }
     }
     
     pub async fn create_transaction(
         &self,
-        api_key: &str,
+        credentials: &MerchantCredentials,
         request: TransactionRequest,
-    ) -> Result<TransactionResponse> {
+    ) -> ApiResult<TransactionResponse> {
         let url = format!("{}/api/merchant/transactions/create", self.base_url);
         
         info!("Creating transaction: {:?}", request);
         
-        let response = self.client
-            .post(&url)
-            .header("x-merchant-api-key", api_key)
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .await?;
+        let response = self.send(credentials, ApiOperation::CreateTransaction, || {
+            let builder = self.client
+                .post(&url)
+                .header("x-merchant-api-key", &credentials.api_key)
+                .header("Content-Type", "application/json");
+            sign_request(builder, credentials, &request)
+        }).await?;
             
         let status = response.status();
+        let signature = response_signature(&response);
         let body = response.text().await?;
         
+        verify_response(credentials, status, signature.as_deref(), &body, Signing::Optional)?;
+        
         debug!("Transaction response: status={}, body={}", status, body);
         
         if status == StatusCode::CREATED || status.is_success() {
             Ok(serde_json::from_str(&body)?)
         } else {
-            let error: ErrorResponse = serde_json::from_str(&body)
-                .unwrap_or_else(|_| ErrorResponse { error: body });
-            Err(anyhow!("API error ({}): {}", status, error.error))
+            Err(ApiError::from_response(status, &body))
+        }
+    }
+    
+    pub async fn cancel_transaction(&self, credentials: &MerchantCredentials, order_id: &str) -> ApiResult<CancelTransactionResponse> {
+        let url = format!(
+            "{}/api/merchant/transactions/by-order-id/{}/cancel",
+            self.base_url, order_id
+        );
+        
+        info!("Cancelling transaction by order id: {}", order_id);
+        
+        let response = self.send(credentials, ApiOperation::CancelTransaction, || {
+            Ok(self.client
+                .patch(&url)
+                .header("x-merchant-api-key", &credentials.api_key))
+        }).await?;
+            
+        let status = response.status();
+        let signature = response_signature(&response);
+        let body = response.text().await?;
+        
+        verify_response(credentials, status, signature.as_deref(), &body, Signing::Optional)?;
+        
+        debug!("Cancel response: status={}, body={}", status, body);
+        
+        if status.is_success() {
+            Ok(serde_json::from_str(&body)?)
+        } else {
+            Err(ApiError::from_response(status, &body))
+        }
+    }
+    
+    pub async fn upload_receipt(
+        &self,
+        credentials: &MerchantCredentials,
+        transaction_id: &str,
+        request: &ReceiptUploadRequest,
+    ) -> ApiResult<Receipt> {
+        let url = format!("{}/api/merchant/transactions/{}/receipt", self.base_url, transaction_id);
+        
+        info!("Uploading receipt {} for transaction {}", request.file_name, transaction_id);
+        
+        let response = self.send(credentials, ApiOperation::UploadReceipt, || {
+            let builder = self.client
+                .post(&url)
+                .header("x-merchant-api-key", &credentials.api_key);
+            sign_request(builder, credentials, request)
+        }).await?;
+            
+        let status = response.status();
+        let signature = response_signature(&response);
+        let body = response.text().await?;
+        
+        verify_response(credentials, status, signature.as_deref(), &body, Signing::Optional)?;
+        
+        debug!("Receipt upload response: status={}, body={}", status, body);
+        
+        if status.is_success() {
+            Ok(serde_json::from_str(&body)?)
+        } else {
+            Err(ApiError::from_response(status, &body))
+        }
+    }
+    
+    pub async fn get_receipts(&self, credentials: &MerchantCredentials, transaction_id: &str) -> ApiResult<Vec<Receipt>> {
+        let url = format!("{}/api/merchant/transactions/{}/receipts", self.base_url, transaction_id);
+        
+        let response = self.send(credentials, ApiOperation::ListReceipts, || {
+            Ok(self.client
+                .get(&url)
+                .header("x-merchant-api-key", &credentials.api_key))
+        }).await?;
+            
+        let status = response.status();
+        let signature = response_signature(&response);
+        let body = response.text().await?;
+        
+        verify_response(credentials, status, signature.as_deref(), &body, Signing::Optional)?;
+        
+        debug!("Receipts response: status={}, body={}", status, body);
+        
+        if status.is_success() {
+            Ok(serde_json::from_str(&body)?)
+        } else {
+            Err(ApiError::from_response(status, &body))
+        }
+    }
+    
+    pub async fn open_dispute(
+        &self,
+        credentials: &MerchantCredentials,
+        kind: DisputeKind,
+        target_id: &str,
+        message: &str,
+        files: &[SyntheticReceipt],
+    ) -> ApiResult<Dispute> {
+        let url = match kind {
+            DisputeKind::Deal => format!("{}/api/merchant/deal-disputes/deal/{}", self.base_url, target_id),
+            DisputeKind::Payout => format!("{}/api/merchant/payout-disputes/payout/{}", self.base_url, target_id),
+        };
+        
+        info!("Opening {:?} dispute for {}", kind, target_id);
+        
+        let response = self.send(credentials, ApiOperation::OpenDispute, || {
+            Ok(self.client
+                .post(&url)
+                .header("x-merchant-api-key", &credentials.api_key)
+                .multipart(dispute_form(message, files)?))
+        }).await?;
+            
+        let status = response.status();
+        let signature = response_signature(&response);
+        let body = response.text().await?;
+        
+        verify_response(credentials, status, signature.as_deref(), &body, Signing::Optional)?;
+        
+        debug!("Open dispute response: status={}, body={}", status, body);
+        
+        if status.is_success() {
+            let created: CreateDisputeResponse = serde_json::from_str(&body)?;
+            Ok(created.dispute)
+        } else {
+            Err(ApiError::from_response(status, &body))
+        }
+    }
+    
+    pub async fn get_disputes(&self, credentials: &MerchantCredentials, kind: DisputeKind) -> ApiResult<Vec<Dispute>> {
+        let url = match kind {
+            DisputeKind::Deal => format!("{}/api/merchant/deal-disputes/", self.base_url),
+            DisputeKind::Payout => format!("{}/api/merchant/payout-disputes/payouts", self.base_url),
+        };
+        
+        let response = self.send(credentials, ApiOperation::ListDisputes, || {
+            Ok(self.client
+                .get(&url)
+                .header("x-merchant-api-key", &credentials.api_key))
+        }).await?;
+            
+        let status = response.status();
+        let signature = response_signature(&response);
+        let body = response.text().await?;
+        
+        verify_response(credentials, status, signature.as_deref(), &body, Signing::Optional)?;
+        
+        debug!("Disputes response: status={}, body={}", status, body);
+        
+        if status.is_success() {
+            let list: DisputeListResponse = serde_json::from_str(&body)?;
+            Ok(list.data)
+        } else {
+            Err(ApiError::from_response(status, &body))
+        }
+    }
+    
+    pub async fn get_dispute(
+        &self,
+        credentials: &MerchantCredentials,
+        kind: DisputeKind,
+        dispute_id: &str,
+    ) -> ApiResult<Dispute> {
+        let url = match kind {
+            DisputeKind::Deal => format!("{}/api/merchant/deal-disputes/{}", self.base_url, dispute_id),
+            DisputeKind::Payout => format!("{}/api/merchant/payout-disputes/dispute/{}", self.base_url, dispute_id),
+        };
+        
+        let response = self.send(credentials, ApiOperation::GetDispute, || {
+            Ok(self.client
+                .get(&url)
+                .header("x-merchant-api-key", &credentials.api_key))
+        }).await?;
+            
+        let status = response.status();
+        let signature = response_signature(&response);
+        let body = response.text().await?;
+        
+        verify_response(credentials, status, signature.as_deref(), &body, Signing::Optional)?;
+        
+        debug!("Dispute response: status={}, body={}", status, body);
+        
+        if status.is_success() {
+            let details: DisputeResponse = serde_json::from_str(&body)?;
+            Ok(details.data)
+        } else {
+            Err(ApiError::from_response(status, &body))
+        }
+    }
+    
+    pub async fn send_dispute_message(
+        &self,
+        credentials: &MerchantCredentials,
+        kind: DisputeKind,
+        dispute_id: &str,
+        message: &str,
+        files: &[SyntheticReceipt],
+    ) -> ApiResult<DisputeMessage> {
+        let url = match kind {
+            DisputeKind::Deal => format!("{}/api/merchant/deal-disputes/{}/messages", self.base_url, dispute_id),
+            DisputeKind::Payout => format!("{}/api/merchant/payout-disputes/dispute/{}/messages", self.base_url, dispute_id),
+        };
+        
+        let response = self.send(credentials, ApiOperation::SendDisputeMessage, || {
+            Ok(self.client
+                .post(&url)
+                .header("x-merchant-api-key", &credentials.api_key)
+                .multipart(dispute_form(message, files)?))
+        }).await?;
+            
+        let status = response.status();
+        let signature = response_signature(&response);
+        let body = response.text().await?;
+        
+        verify_response(credentials, status, signature.as_deref(), &body, Signing::Optional)?;
+        
+        debug!("Dispute message response: status={}, body={}", status, body);
+        
+        if status.is_success() {
+            let sent: DisputeMessageResponse = serde_json::from_str(&body)?;
+            Ok(sent.message)
+        } else {
+            Err(ApiError::from_response(status, &body))
+        }
+    }
+    
+    pub async fn get_transaction_status(
+        &self,
+        credentials: &MerchantCredentials,
+        transaction_id: &str,
+    ) -> ApiResult<TransactionStatusResponse> {
+        let url = format!("{}/api/merchant/transactions/status/{}", self.base_url, transaction_id);
+        
+        let response = self.send(credentials, ApiOperation::TransactionStatus, || {
+            Ok(self.client
+                .get(&url)
+                .header("x-merchant-api-key", &credentials.api_key))
+        }).await?;
+            
+        let status = response.status();
+        let signature = response_signature(&response);
+        let body = response.text().await?;
+        
+        verify_response(credentials, status, signature.as_deref(), &body, Signing::Optional)?;
+        
+        debug!("Transaction status response: status={}, body={}", status, body);
+        
+        if status.is_success() {
+            Ok(serde_json::from_str(&body)?)
+        } else {
+            Err(ApiError::from_response(status, &body))
         }
     }
     
-    pub async fn get_transaction(&self, api_key: &str, order_id: &str) -> Result<TransactionListItem> {
+    pub async fn get_transaction(&self, credentials: &MerchantCredentials, order_id: &str) -> ApiResult<TransactionListItem> {
         let url = format!("{}/api/merchant/transactions?orderId={}", self.base_url, order_id);
         
-        let response = self.client
-            .get(&url)
-            .header("x-merchant-api-key", api_key)
-            .send()
-            .await?;
+        let response = self.send(credentials, ApiOperation::GetTransaction, || {
+            Ok(self.client
+                .get(&url)
+                .header("x-merchant-api-key", &credentials.api_key))
+        }).await?;
             
         let status = response.status();
+        let signature = response_signature(&response);
         let body = response.text().await?;
         
+        verify_response(credentials, status, signature.as_deref(), &body, Signing::Optional)?;
+        
         if status.is_success() {
             let list: TransactionListResponse = serde_json::from_str(&body)?;
             list.data.into_iter()
                 .next()
-                .ok_or_else(|| anyhow!("Transaction not found"))
+                .ok_or_else(|| ApiError::Rejected {
+                    status: StatusCode::NOT_FOUND,
+                    code: None,
+                    message: "Transaction not found".to_string(),
+                })
         } else {
-            let error: ErrorResponse = serde_json::from_str(&body)
-                .unwrap_or_else(|_| ErrorResponse { error: body });
-            Err(anyhow!("API error ({}): {}", status, error.error))
+            Err(ApiError::from_response(status, &body))
         }
     }
     
     pub async fn get_transactions(
         &self,
-        api_key: &str,
+        credentials: &MerchantCredentials,
         page: u64,
         limit: u64,
-    ) -> Result<TransactionListResponse> {
+    ) -> ApiResult<TransactionListResponse> {
         let url = format!(
             "{}/api/merchant/transactions?page={}&limit={}",
             self.base_url, page, limit
         );
         
-        let response = self.client
-            .get(&url)
-            .header("x-merchant-api-key", api_key)
-            .send()
-            .await?;
+        let response = self.send(credentials, ApiOperation::ListTransactions, || {
+            Ok(self.client
+                .get(&url)
+                .header("x-merchant-api-key", &credentials.api_key))
+        }).await?;
             
         let status = response.status();
+        let signature = response_signature(&response);
         let body = response.text().await?;
         
+        verify_response(credentials, status, signature.as_deref(), &body, Signing::Optional)?;
+        
         if status.is_success() {
             Ok(serde_json::from_str(&body)?)
         } else {
-            let error: ErrorResponse = serde_json::from_str(&body)
-                .unwrap_or_else(|_| ErrorResponse { error: body });
-            Err(anyhow!("API error ({}): {}", status, error.error))
+            Err(ApiError::from_response(status, &body))
         }
     }
     
-    pub async fn get_methods(&self, api_key: &str) -> Result<Vec<Method>> {
+    pub async fn create_payout(&self, credentials: &MerchantCredentials, request: PayoutRequest) -> ApiResult<Payout> {
+        let url = format!("{}/api/merchant/payouts", self.base_url);
+        
+        info!("Creating payout: {:?}", request);
+        
+        let response = self.send(credentials, ApiOperation::CreatePayout, || {
+            let builder = self.client
+                .post(&url)
+                .header("x-api-key", &credentials.api_key)
+                .header("Content-Type", "application/json");
+            sign_request(builder, credentials, &request)
+        }).await?;
+            
+        let status = response.status();
+        let signature = response_signature(&response);
+        let body = response.text().await?;
+        
+        verify_response(credentials, status, signature.as_deref(), &body, Signing::Optional)?;
+        
+        debug!("Payout response: status={}, body={}", status, body);
+        
+        if status.is_success() {
+            let response: PayoutResponse = serde_json::from_str(&body)?;
+            Ok(response.payout)
+        } else {
+            Err(ApiError::from_response(status, &body))
+        }
+    }
+    
+    pub async fn get_payout(&self, credentials: &MerchantCredentials, payout_id: &str) -> ApiResult<Payout> {
+        let url = format!("{}/api/merchant/payouts/{}", self.base_url, payout_id);
+        
+        let response = self.send(credentials, ApiOperation::GetPayout, || {
+            Ok(self.client
+                .get(&url)
+                .header("x-api-key", &credentials.api_key))
+        }).await?;
+            
+        let status = response.status();
+        let signature = response_signature(&response);
+        let body = response.text().await?;
+        
+        verify_response(credentials, status, signature.as_deref(), &body, Signing::Optional)?;
+        
+        debug!("Payout status response: status={}, body={}", status, body);
+        
+        if status.is_success() {
+            let response: PayoutResponse = serde_json::from_str(&body)?;
+            Ok(response.payout)
+        } else {
+            Err(ApiError::from_response(status, &body))
+        }
+    }
+    
+    pub async fn get_enums(&self, credentials: &MerchantCredentials) -> ApiResult<EnumCatalog> {
+        let url = format!("{}/api/merchant/enums", self.base_url);
+        
+        let response = self.send(credentials, ApiOperation::Enums, || {
+            Ok(self.client
+                .get(&url)
+                .header("x-merchant-api-key", &credentials.api_key))
+        }).await?;
+            
+        let status = response.status();
+        let signature = response_signature(&response);
+        let body = response.text().await?;
+        
+        verify_response(credentials, status, signature.as_deref(), &body, Signing::Optional)?;
+        
+        debug!("Enums API response: status={}, body={}", status, body);
+        
+        if status.is_success() {
+            Ok(serde_json::from_str(&body)?)
+        } else {
+            Err(ApiError::from_response(status, &body))
+        }
+    }
+    
+    pub async fn get_methods(&self, credentials: &MerchantCredentials) -> ApiResult<Vec<Method>> {
         let url = format!("{}/api/merchant/methods", self.base_url);
         
-        let response = self.client
-            .get(&url)
-            .header("x-merchant-api-key", api_key)
-            .send()
-            .await?;
+        let response = self.send(credentials, ApiOperation::Methods, || {
+            Ok(self.client
+                .get(&url)
+                .header("x-merchant-api-key", &credentials.api_key))
+        }).await?;
             
         let status = response.status();
+        let signature = response_signature(&response);
         let body = response.text().await?;
         
+        verify_response(credentials, status, signature.as_deref(), &body, Signing::Optional)?;
+        
         debug!("Methods API response: status={}, body={}", status, body);
         
         if status.is_success() {
             Ok(serde_json::from_str(&body)?)
         } else {
-            let error: ErrorResponse = serde_json::from_str(&body)
-                .unwrap_or_else(|_| ErrorResponse { error: body });
-            Err(anyhow!("API error ({}): {}", status, error.error))
+            Err(ApiError::from_response(status, &body))
+        }
+    }
+}
+
+/// Signs a JSON request body with the merchant's private key, if one is set.
+fn sign_request<T: serde::Serialize>(
+    builder: RequestBuilder,
+    credentials: &MerchantCredentials,
+    body: &T,
+) -> ApiResult<RequestBuilder> {
+    let builder = builder.json(body);
+    
+    match &credentials.private_key {
+        Some(private_key) => {
+            let value = serde_json::to_value(body)?;
+            Ok(builder.header(SIGNATURE_HEADER, signature::sign_value(private_key, &value)))
+        }
+        None => Ok(builder),
+    }
+}
+
+/// Dispute endpoints take multipart forms: a `message` field and optional `files`.
+/// Multipart bodies are not signed.
+fn dispute_form(message: &str, files: &[SyntheticReceipt]) -> ApiResult<Form> {
+    let mut form = Form::new().text("message", message.to_string());
+    
+    for file in files {
+        let part = Part::bytes(file.bytes.clone())
+            .file_name(file.file_name.clone())
+            .mime_str(file.format.mime_type())?;
+        form = form.part("files", part);
+    }
+    
+    Ok(form)
+}
+
+tokio::task_local! {
+    static RETRY_RNG: RefCell<StdRng>;
+}
+
+/// Runs `future` with its retry jitter drawn from `rng`, so a seeded traffic
+/// run backs off on the same schedule every time. Requests sent outside it
+/// use thread-local randomness.
+pub async fn with_retry_rng<F: Future>(rng: StdRng, future: F) -> F::Output {
+    RETRY_RNG.scope(RefCell::new(rng), future).await
+}
+
+fn backoff(policy: &RetryPolicy, retries: u32) -> Duration {
+    RETRY_RNG
+        .try_with(|rng| policy.backoff(retries, &mut *rng.borrow_mut()))
+        .unwrap_or_else(|_| policy.backoff(retries, &mut rand::thread_rng()))
+}
+
+/// Retry decision for a response: 429 and 503 mean the backend did not process
+/// the request, so they are always retried, honouring `Retry-After`. Other
+/// gateway and server errors are retried only when the policy allows it.
+fn response_retry(policy: &RetryPolicy, response: &Response, retries: u32) -> Option<(Duration, &'static str)> {
+    let status = response.status();
+    let reason = ApiError::from_response(status, "").category();
+    
+    match status {
+        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE => match retry_after(response) {
+            Some(wait) if wait > Duration::from_millis(policy.max_retry_after_ms) => None,
+            Some(wait) => Some((wait, reason)),
+            None => Some((backoff(policy, retries), reason)),
+        },
+        StatusCode::REQUEST_TIMEOUT
+        | StatusCode::INTERNAL_SERVER_ERROR
+        | StatusCode::BAD_GATEWAY
+        | StatusCode::GATEWAY_TIMEOUT if policy.retry_ambiguous => {
+            Some((backoff(policy, retries), reason))
+        }
+        _ => None,
+    }
+}
+
+/// A failed connect never reached the backend and is always safe to retry.
+/// Timeouts and dropped connections might have been processed.
+fn transport_retry(policy: &RetryPolicy, error: &reqwest::Error, retries: u32) -> Option<(Duration, &'static str)> {
+    let reason = if error.is_connect() {
+        "Transport"
+    } else if policy.retry_ambiguous && error.is_timeout() {
+        "Timeout"
+    } else if policy.retry_ambiguous && (error.is_request() || error.is_body()) {
+        "Transport"
+    } else {
+        return None;
+    };
+    
+    Some((backoff(policy, retries), reason))
+}
+
+/// `Retry-After` as either delay-seconds or an HTTP date
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+    
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some((date.with_timezone(&Utc) - Utc::now()).to_std().unwrap_or(Duration::ZERO))
+}
+
+pub(super) fn response_signature(response: &Response) -> Option<String> {
+    response
+        .headers()
+        .get(SIGNATURE_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(String::from)
+}
+
+/// Whether the backend signs an endpoint's responses.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum Signing {
+    /// Native routes are signed only when they answer in the Wellbit format
+    Optional,
+    /// Wellbit routes sign every response they build
+    Required,
+}
+
+/// Verifies the backend's x-api-token signature. Merchants without a private
+/// key accept everything. With one, a signature that is present must match,
+/// and a successful response from an endpoint that always signs must carry
+/// one; unsigned error responses are let through to be reported as what they
+/// are, since the backend cannot sign a rejection before it knows the merchant.
+pub(super) fn verify_response(
+    credentials: &MerchantCredentials,
+    status: StatusCode,
+    signature: Option<&str>,
+    body: &str,
+    signing: Signing,
+) -> ApiResult<()> {
+    let Some(private_key) = &credentials.private_key else {
+        return Ok(());
+    };
+    
+    match signature {
+        Some(signature) if signature::verify(private_key, body, signature) => Ok(()),
+        Some(signature) => {
+            error!("Response signature mismatch: signature={}, body={}", signature, body);
+            Err(ApiError::SignatureMismatch)
+        }
+        None if signing == Signing::Required && status.is_success() => {
+            error!("Response signature missing: status={}, body={}", status, body);
+            Err(ApiError::SignatureMismatch)
         }
+        None => Ok(()),
     }
 }
\ No newline at end of file
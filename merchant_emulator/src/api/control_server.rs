@@ -0,0 +1,380 @@
+use crate::device::DeviceManager;
+use crate::models::{Merchant, MerchantSummary, Statistics, TrafficConfig};
+use crate::services::{MerchantService, RateProgress, StatisticsService, TrafficGenerator};
+use axum::{
+    body::Bytes,
+    extract::{Path, Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Json, Response},
+    routing::{get, post},
+    Router,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+use tracing::info;
+use uuid::Uuid;
+
+/// What the control API drives. Traffic it starts runs in quiet mode.
+#[derive(Clone)]
+pub struct ControlState {
+    pub merchant_service: Arc<MerchantService>,
+    pub traffic_generator: Arc<TrafficGenerator>,
+    pub stats_service: Arc<StatisticsService>,
+    pub device_manager: Arc<DeviceManager>,
+    pub token: Option<String>, // Required as `Authorization: Bearer <token>` when set
+}
+
+/// REST API for running the emulator headless: merchants, traffic, live
+/// traffic settings, devices and statistics.
+pub struct ControlServer {
+    addr: String,
+    state: ControlState,
+    allow_unauthenticated: bool,
+}
+
+impl ControlServer {
+    pub fn new(addr: String, state: ControlState) -> Self {
+        Self { addr, state, allow_unauthenticated: false }
+    }
+    
+    /// Serves without a token on a non-loopback address instead of refusing to start.
+    pub fn allow_unauthenticated(mut self, allow: bool) -> Self {
+        self.allow_unauthenticated = allow;
+        self
+    }
+    
+    pub fn router(state: ControlState) -> Router {
+        let state = Arc::new(state);
+        
+        Router::new()
+            .route("/health", get(health))
+            .route("/merchants", get(list_merchants).post(create_merchant))
+            .route("/merchants/{merchant_id}", get(get_merchant))
+            .route("/merchants/{merchant_id}/traffic/start", post(start_traffic))
+            .route("/merchants/{merchant_id}/traffic/stop", post(stop_traffic))
+            .route(
+                "/merchants/{merchant_id}/traffic/config",
+                get(get_traffic_config).put(replace_traffic_config).patch(patch_traffic_config),
+            )
+            .route("/merchants/{merchant_id}/statistics", get(get_statistics))
+            .route("/statistics", get(list_statistics))
+            .route("/devices", get(list_devices))
+            .layer(middleware::from_fn_with_state(state.clone(), authorize))
+            .with_state(state)
+    }
+    
+    pub async fn start(self) -> Result<(), Box<dyn std::error::Error>> {
+        let listener = tokio::net::TcpListener::bind(&self.addr).await?;
+        
+        // Checked on the bound address, so hostnames and 0.0.0.0 are judged by what they resolve to
+        let loopback = listener.local_addr()?.ip().is_loopback();
+        if self.state.token.is_none() && !loopback && !self.allow_unauthenticated {
+            return Err(format!(
+                "refusing to serve the control API on {} without a token; set one or allow unauthenticated access explicitly",
+                self.addr
+            ).into());
+        }
+        
+        let app = Self::router(self.state);
+        
+        info!("Control API listening on {}", self.addr);
+        
+        axum::serve(listener, app).await?;
+        
+        Ok(())
+    }
+}
+
+/// An error answered as `{"error": message}`.
+#[derive(Debug)]
+pub struct ControlError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ControlError {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self { status, message: message.into() }
+    }
+}
+
+impl From<anyhow::Error> for ControlError {
+    fn from(e: anyhow::Error) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e))
+    }
+}
+
+impl IntoResponse for ControlError {
+    fn into_response(self) -> Response {
+        (self.status, Json(json!({ "error": self.message }))).into_response()
+    }
+}
+
+type ControlResult<T> = Result<Json<T>, ControlError>;
+
+/// A merchant with its traffic state, as returned by the merchant endpoints.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MerchantStatus {
+    #[serde(flatten)]
+    pub merchant: MerchantSummary,
+    pub running: bool,
+    pub method_id: Option<String>,
+    pub rate: Option<RateProgress>,
+    pub traffic_config: TrafficConfig,
+    pub statistics: Option<Statistics>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateMerchant {
+    name: String,
+    api_key: String,
+    private_key: Option<String>,
+}
+
+/// Optional body of `traffic/start`; set fields are saved to the merchant's traffic settings.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct StartTraffic {
+    method: Option<String>, // First available method when unset
+    max_transactions: Option<u64>,
+    seed: Option<u64>,
+}
+
+/// Answer of the traffic settings updates.
+#[derive(Debug, Serialize)]
+struct ConfigUpdate {
+    traffic_config: TrafficConfig,
+    restarted: bool, // Running traffic was restarted with the new settings
+}
+
+async fn authorize(State(state): State<Arc<ControlState>>, request: Request, next: Next) -> Response {
+    if let Some(token) = &state.token {
+        let provided = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        // Constant time, so response timing does not reveal how much of a guess matched
+        let matches = provided.is_some_and(|provided| bool::from(provided.as_bytes().ct_eq(token.as_bytes())));
+        if !matches {
+            return ControlError::new(StatusCode::UNAUTHORIZED, "Missing or wrong bearer token").into_response();
+        }
+    }
+    
+    next.run(request).await
+}
+
+async fn health(State(state): State<Arc<ControlState>>) -> ControlResult<Value> {
+    let merchants = state.merchant_service.get_all_merchants();
+    let mut running = 0;
+    for merchant in &merchants {
+        if state.traffic_generator.is_running(&merchant.id).await {
+            running += 1;
+        }
+    }
+    
+    Ok(Json(json!({
+        "status": "ok",
+        "version": env!("CARGO_PKG_VERSION"),
+        "merchants": merchants.len(),
+        "running": running,
+        "devices_connected": state.device_manager.get_all_devices().iter().filter(|d| d.is_connected).count(),
+    })))
+}
+
+async fn list_merchants(State(state): State<Arc<ControlState>>) -> ControlResult<Vec<MerchantStatus>> {
+    let mut merchants = state.merchant_service.get_all_merchants();
+    merchants.sort_by_key(|m| m.created_at);
+    
+    let mut statuses = Vec::new();
+    for merchant in &merchants {
+        statuses.push(status(&state, merchant.id).await?);
+    }
+    Ok(Json(statuses))
+}
+
+async fn create_merchant(
+    State(state): State<Arc<ControlState>>,
+    Json(request): Json<CreateMerchant>,
+) -> Result<(StatusCode, Json<MerchantStatus>), ControlError> {
+    let merchant = state.merchant_service
+        .create_merchant(request.name, request.api_key, request.private_key)
+        .await
+        .map_err(|e| ControlError::new(StatusCode::BAD_GATEWAY, format!("Failed to create merchant: {:#}", e)))?;
+    
+    Ok((StatusCode::CREATED, Json(status(&state, merchant.id).await?)))
+}
+
+async fn get_merchant(State(state): State<Arc<ControlState>>, Path(merchant_id): Path<Uuid>) -> ControlResult<MerchantStatus> {
+    Ok(Json(status(&state, merchant_id).await?))
+}
+
+async fn start_traffic(
+    State(state): State<Arc<ControlState>>,
+    Path(merchant_id): Path<Uuid>,
+    body: Bytes,
+) -> ControlResult<MerchantStatus> {
+    let request: StartTraffic = parse_optional(&body)?;
+    let mut merchant = find_merchant(&state, merchant_id)?;
+    if state.traffic_generator.is_running(&merchant_id).await {
+        return Err(ControlError::new(StatusCode::CONFLICT, format!("Traffic is already running for {}", merchant.name)));
+    }
+    
+    if request.max_transactions.is_some() {
+        merchant.traffic_config.max_transactions = request.max_transactions;
+    }
+    if request.seed.is_some() {
+        merchant.traffic_config.seed = request.seed;
+    }
+    merchant.traffic_config
+        .validate()
+        .map_err(|e| ControlError::new(StatusCode::BAD_REQUEST, e))?;
+    
+    let method_id = match request.method {
+        Some(method) => method,
+        None => state.merchant_service
+            .get_available_methods(&merchant)
+            .await
+            .map_err(|e| ControlError::new(StatusCode::BAD_GATEWAY, format!("Failed to get payment methods: {:#}", e)))?
+            .first()
+            .map(|m| m.id.clone())
+            .ok_or_else(|| ControlError::new(StatusCode::CONFLICT, format!("No payment methods available for {}", merchant.name)))?,
+    };
+    
+    merchant.traffic_config.enabled = true;
+    state.merchant_service.update_merchant(merchant.clone()).await?;
+    state.traffic_generator.start_traffic(merchant, method_id, true).await?;
+    
+    Ok(Json(status(&state, merchant_id).await?))
+}
+
+async fn stop_traffic(State(state): State<Arc<ControlState>>, Path(merchant_id): Path<Uuid>) -> ControlResult<MerchantStatus> {
+    let mut merchant = find_merchant(&state, merchant_id)?;
+    if !state.traffic_generator.is_running(&merchant_id).await {
+        return Err(ControlError::new(StatusCode::CONFLICT, format!("Traffic is not running for {}", merchant.name)));
+    }
+    
+    state.traffic_generator.stop_traffic(&merchant_id).await?;
+    merchant.traffic_config.enabled = false;
+    state.merchant_service.update_merchant(merchant).await?;
+    
+    Ok(Json(status(&state, merchant_id).await?))
+}
+
+async fn get_traffic_config(State(state): State<Arc<ControlState>>, Path(merchant_id): Path<Uuid>) -> ControlResult<TrafficConfig> {
+    Ok(Json(find_merchant(&state, merchant_id)?.traffic_config))
+}
+
+/// Replaces the traffic settings; fields left out take their defaults.
+async fn replace_traffic_config(
+    State(state): State<Arc<ControlState>>,
+    Path(merchant_id): Path<Uuid>,
+    Json(config): Json<TrafficConfig>,
+) -> ControlResult<ConfigUpdate> {
+    let merchant = find_merchant(&state, merchant_id)?;
+    apply_config(&state, merchant, config).await
+}
+
+/// Changes only the fields in the body, as a JSON merge patch: objects are
+/// merged, and `null` resets a field to its default.
+async fn patch_traffic_config(
+    State(state): State<Arc<ControlState>>,
+    Path(merchant_id): Path<Uuid>,
+    Json(patch): Json<Value>,
+) -> ControlResult<ConfigUpdate> {
+    let merchant = find_merchant(&state, merchant_id)?;
+    let mut current = serde_json::to_value(&merchant.traffic_config).map_err(anyhow::Error::from)?;
+    merge(&mut current, patch);
+    let config = serde_json::from_value(current)
+        .map_err(|e| ControlError::new(StatusCode::BAD_REQUEST, format!("Invalid traffic settings: {}", e)))?;
+    
+    apply_config(&state, merchant, config).await
+}
+
+async fn get_statistics(State(state): State<Arc<ControlState>>, Path(merchant_id): Path<Uuid>) -> ControlResult<Statistics> {
+    let merchant = find_merchant(&state, merchant_id)?;
+    state.stats_service
+        .get_statistics(&merchant_id)
+        .map(Json)
+        .ok_or_else(|| ControlError::new(StatusCode::NOT_FOUND, format!("No statistics for {}", merchant.name)))
+}
+
+async fn list_statistics(State(state): State<Arc<ControlState>>) -> ControlResult<HashMap<Uuid, Statistics>> {
+    Ok(Json(state.stats_service.get_all_statistics()))
+}
+
+async fn list_devices(State(state): State<Arc<ControlState>>) -> ControlResult<Value> {
+    let mut devices = state.device_manager.get_all_devices();
+    devices.sort_by_key(|d| d.created_at);
+    Ok(Json(serde_json::to_value(devices).map_err(anyhow::Error::from)?))
+}
+
+/// Saves validated settings, and restarts running traffic so they take effect now.
+async fn apply_config(
+    state: &ControlState,
+    mut merchant: Merchant,
+    mut config: TrafficConfig,
+) -> ControlResult<ConfigUpdate> {
+    config.validate().map_err(|e| ControlError::new(StatusCode::BAD_REQUEST, e))?;
+    config.enabled = merchant.traffic_config.enabled;
+    config.created_count = merchant.traffic_config.created_count;
+    
+    merchant.traffic_config = config;
+    state.merchant_service.update_merchant(merchant.clone()).await?;
+    let restarted = state.traffic_generator.restart_traffic(merchant.clone()).await?;
+    
+    Ok(Json(ConfigUpdate { traffic_config: merchant.traffic_config, restarted }))
+}
+
+async fn status(state: &ControlState, merchant_id: Uuid) -> Result<MerchantStatus, ControlError> {
+    let merchant = find_merchant(state, merchant_id)?;
+    let method_id = state.traffic_generator.running_method(&merchant_id).await;
+    
+    Ok(MerchantStatus {
+        merchant: MerchantSummary::from(&merchant),
+        running: method_id.is_some(),
+        method_id,
+        rate: state.traffic_generator.rate_progress(&merchant_id).await,
+        traffic_config: merchant.traffic_config,
+        statistics: state.stats_service.get_statistics(&merchant_id),
+    })
+}
+
+fn find_merchant(state: &ControlState, merchant_id: Uuid) -> Result<Merchant, ControlError> {
+    state.merchant_service
+        .get_merchant(&merchant_id)
+        .ok_or_else(|| ControlError::new(StatusCode::NOT_FOUND, format!("Merchant {} not found", merchant_id)))
+}
+
+/// Parses a JSON body that may be left out entirely.
+fn parse_optional<T: DeserializeOwned + Default>(body: &Bytes) -> Result<T, ControlError> {
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return Ok(T::default());
+    }
+    serde_json::from_slice(body).map_err(|e| ControlError::new(StatusCode::BAD_REQUEST, format!("Invalid body: {}", e)))
+}
+
+/// JSON merge patch (RFC 7396).
+fn merge(target: &mut Value, patch: Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch;
+        return;
+    };
+    if !target.is_object() {
+        *target = json!({});
+    }
+    
+    if let Value::Object(fields) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                fields.remove(&key);
+            } else {
+                merge(fields.entry(key).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,199 @@
+use crate::models::ErrorResponse;
+use reqwest::StatusCode;
+use thiserror::Error;
+
+pub type ApiResult<T> = std::result::Result<T, ApiError>;
+
+/// A failed backend call. HTTP failures keep the status and the backend's
+/// error code so statistics can group them by cause rather than by message.
+#[derive(Debug, Error)]
+pub enum ApiError {
+    #[error("Transport error: {0}")]
+    Transport(#[source] reqwest::Error),
+    
+    #[error("Request timed out{}", status.map(|s| format!(" ({})", s)).unwrap_or_default())]
+    Timeout { status: Option<StatusCode> },
+    
+    #[error("Auth error ({status}): {message}")]
+    Auth { status: StatusCode, code: Option<String>, message: String },
+    
+    #[error("Validation error ({status}): {message}")]
+    Validation { status: StatusCode, code: Option<String>, message: String },
+    
+    #[error("No requisites available ({status}): {message}")]
+    NoRequisites { status: StatusCode, code: Option<String>, message: String },
+    
+    #[error("Insufficient balance ({status}): {message}")]
+    InsufficientBalance { status: StatusCode, code: Option<String>, message: String },
+    
+    #[error("Rate limited ({status}): {message}")]
+    RateLimited { status: StatusCode, code: Option<String>, message: String },
+    
+    #[error("Server error ({status}): {message}")]
+    Server { status: StatusCode, code: Option<String>, message: String },
+    
+    /// Any other non-success response, e.g. 404 or 409
+    #[error("API error ({status}): {message}")]
+    Rejected { status: StatusCode, code: Option<String>, message: String },
+    
+    #[error("Signature mismatch: response x-api-token is missing or does not match body")]
+    SignatureMismatch,
+    
+    #[error("Invalid response body: {0}")]
+    Decode(#[from] serde_json::Error),
+    
+    /// The request could not be expressed in the merchant's protocol and was not sent
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+}
+
+impl ApiError {
+    /// Classifies a non-success response from its status and `{ error, code }` body.
+    pub fn from_response(status: StatusCode, body: &str) -> Self {
+        let (message, code) = match serde_json::from_str::<ErrorResponse>(body) {
+            Ok(response) => (response.error, response.code),
+            Err(_) => (body.to_string(), None),
+        };
+        
+        // Some endpoints send the code itself as the error text, e.g. `{ "error": "NO_REQUISITE" }`
+        let code = code.or_else(|| is_error_code(&message).then(|| message.clone()));
+        
+        match code.as_deref() {
+            Some("NO_REQUISITE") => return ApiError::NoRequisites { status, code, message },
+            Some("INSUFFICIENT_BALANCE" | "INSUFFICIENT_FUNDS") => {
+                return ApiError::InsufficientBalance { status, code, message }
+            }
+            _ => {}
+        }
+        
+        if message.contains("Insufficient") || message.contains("Недостаточно") {
+            return ApiError::InsufficientBalance { status, code, message };
+        }
+        
+        match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ApiError::Auth { status, code, message },
+            StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => ApiError::Validation { status, code, message },
+            StatusCode::REQUEST_TIMEOUT | StatusCode::GATEWAY_TIMEOUT => ApiError::Timeout { status: Some(status) },
+            StatusCode::TOO_MANY_REQUESTS => ApiError::RateLimited { status, code, message },
+            s if s.is_server_error() => ApiError::Server { status, code, message },
+            _ => ApiError::Rejected { status, code, message },
+        }
+    }
+    
+    /// Stable label used to group errors in statistics
+    pub fn category(&self) -> &'static str {
+        match self {
+            ApiError::Transport(_) => "Transport",
+            ApiError::Timeout { .. } => "Timeout",
+            ApiError::Auth { .. } => "Auth",
+            ApiError::Validation { .. } => "Validation",
+            ApiError::NoRequisites { .. } => "No Requisites",
+            ApiError::InsufficientBalance { .. } => "Insufficient Balance",
+            ApiError::RateLimited { .. } => "Rate Limited",
+            ApiError::Server { .. } => "Server Error",
+            ApiError::Rejected { .. } => "Rejected",
+            ApiError::SignatureMismatch => "Signature Mismatch",
+            ApiError::Decode(_) => "Invalid Response",
+            ApiError::InvalidRequest(_) => "Invalid Request",
+        }
+    }
+    
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            ApiError::Timeout { status } => *status,
+            ApiError::Auth { status, .. }
+            | ApiError::Validation { status, .. }
+            | ApiError::NoRequisites { status, .. }
+            | ApiError::InsufficientBalance { status, .. }
+            | ApiError::RateLimited { status, .. }
+            | ApiError::Server { status, .. }
+            | ApiError::Rejected { status, .. } => Some(*status),
+            ApiError::Transport(e) => e.status(),
+            ApiError::SignatureMismatch | ApiError::Decode(_) | ApiError::InvalidRequest(_) => None,
+        }
+    }
+    
+    /// Backend error code, when the response carried one
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            ApiError::Auth { code, .. }
+            | ApiError::Validation { code, .. }
+            | ApiError::NoRequisites { code, .. }
+            | ApiError::InsufficientBalance { code, .. }
+            | ApiError::RateLimited { code, .. }
+            | ApiError::Server { code, .. }
+            | ApiError::Rejected { code, .. } => code.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            ApiError::Timeout { status: None }
+        } else {
+            ApiError::Transport(e)
+        }
+    }
+}
+
+fn is_error_code(message: &str) -> bool {
+    !message.is_empty() && message.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn responses_are_classified_by_code_message_and_status() {
+        let cases = [
+            (401, r#"{"error":"Invalid API key"}"#, "Auth", None),
+            (403, r#"{"error":"Merchant disabled","code":"FORBIDDEN"}"#, "Auth", Some("FORBIDDEN")),
+            (400, r#"{"error":"amount is required"}"#, "Validation", None),
+            (422, "not json", "Validation", None),
+            (400, r#"{"error":"No requisites","code":"NO_REQUISITE"}"#, "No Requisites", Some("NO_REQUISITE")),
+            (409, r#"{"error":"NO_REQUISITE"}"#, "No Requisites", Some("NO_REQUISITE")),
+            (400, r#"{"error":"Low balance","code":"INSUFFICIENT_FUNDS"}"#, "Insufficient Balance", Some("INSUFFICIENT_FUNDS")),
+            (400, r#"{"error":"Insufficient balance"}"#, "Insufficient Balance", None),
+            (400, r#"{"error":"Недостаточно средств"}"#, "Insufficient Balance", None),
+            (429, r#"{"error":"Too many requests"}"#, "Rate Limited", None),
+            (500, r#"{"error":"Internal error"}"#, "Server Error", None),
+            (503, "Service Unavailable", "Server Error", None),
+            (504, "", "Timeout", None),
+            (404, r#"{"error":"Not found"}"#, "Rejected", None),
+        ];
+        
+        for (status, body, category, code) in cases {
+            let status = StatusCode::from_u16(status).unwrap();
+            let error = ApiError::from_response(status, body);
+            assert_eq!(error.category(), category, "{} {}", status, body);
+            assert_eq!(error.code(), code, "{} {}", status, body);
+            assert_eq!(error.status(), Some(status), "{} {}", status, body);
+        }
+    }
+    
+    #[test]
+    fn unparsed_bodies_keep_their_text() {
+        let error = ApiError::from_response(StatusCode::BAD_GATEWAY, "upstream down");
+        assert!(matches!(error, ApiError::Server { ref message, .. } if message == "upstream down"));
+    }
+    
+    #[test]
+    fn local_failures_have_their_own_categories() {
+        let decode = ApiError::from(serde_json::from_str::<serde_json::Value>("{").unwrap_err());
+        let cases = [
+            (decode, "Invalid Response"),
+            (ApiError::SignatureMismatch, "Signature Mismatch"),
+            (ApiError::InvalidRequest("no method".to_string()), "Invalid Request"),
+            (ApiError::Timeout { status: None }, "Timeout"),
+        ];
+        
+        for (error, category) in cases {
+            assert_eq!(error.category(), category, "{:?}", error);
+            assert_eq!(error.status(), None);
+            assert_eq!(error.code(), None);
+        }
+    }
+}
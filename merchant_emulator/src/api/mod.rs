@@ -1,5 +1,11 @@
 pub mod client;
 pub mod callback_server;
+pub mod control_server;
+pub mod signature;
+pub mod error;
+pub mod wellbit;
 
 pub use client::ApiClient;
-pub use callback_server::CallbackServer;
\ No newline at end of file
+pub use callback_server::CallbackServer;
+pub use control_server::{ControlServer, ControlState};
+pub use error::ApiError;
\ No newline at end of file
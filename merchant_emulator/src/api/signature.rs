@@ -0,0 +1,3 @@
+//! Request and response signing, shared with the mock backend.
+
+pub use api_signature::{canonical_json, sign, sign_value, verify, SIGNATURE_HEADER};
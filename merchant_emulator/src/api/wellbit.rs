@@ -0,0 +1,85 @@
+use crate::models::*;
+use crate::api::client::{response_signature, verify_response, ApiClient, Signing};
+use crate::api::error::{ApiError, ApiResult};
+use crate::api::signature::{self, SIGNATURE_HEADER};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tracing::{debug, info};
+
+/// Wellbit payment endpoints. They authenticate with the merchant's public key
+/// in `x-api-key` and always require a signed body.
+impl ApiClient {
+    pub async fn wellbit_create_payment(
+        &self,
+        credentials: &MerchantCredentials,
+        request: &WellbitPaymentRequest,
+    ) -> ApiResult<WellbitPayment> {
+        info!("Creating Wellbit payment: {:?}", request);
+        
+        self.wellbit_post(credentials, ApiOperation::CreateTransaction, "create", request).await
+    }
+    
+    pub async fn wellbit_get_payment(&self, credentials: &MerchantCredentials, payment_id: u64) -> ApiResult<WellbitPayment> {
+        let request = WellbitPaymentQuery { payment_id };
+        
+        self.wellbit_post(credentials, ApiOperation::TransactionStatus, "get", &request).await
+    }
+    
+    pub async fn wellbit_update_status(
+        &self,
+        credentials: &MerchantCredentials,
+        payment_id: u64,
+        payment_status: &str,
+    ) -> ApiResult<WellbitPayment> {
+        let request = WellbitStatusUpdate {
+            payment_id,
+            payment_status: payment_status.to_string(),
+        };
+        
+        info!("Setting Wellbit payment {} status to {}", payment_id, payment_status);
+        
+        self.wellbit_post(credentials, ApiOperation::CancelTransaction, "status", &request).await
+    }
+    
+    async fn wellbit_post<B, T>(
+        &self,
+        credentials: &MerchantCredentials,
+        operation: ApiOperation,
+        action: &str,
+        request: &B,
+    ) -> ApiResult<T>
+    where
+        B: Serialize,
+        T: DeserializeOwned,
+    {
+        let url = format!("{}/api/wellbit/payment/{}", self.base_url, action);
+        
+        // The backend signs with an empty key when the merchant has none
+        let token = signature::sign_value(
+            credentials.private_key.as_deref().unwrap_or_default(),
+            &serde_json::to_value(request)?,
+        );
+        
+        let response = self.send(credentials, operation, || {
+            Ok(self.client
+                .post(&url)
+                .header("x-api-key", &credentials.wellbit_key)
+                .header(SIGNATURE_HEADER, &token)
+                .json(request))
+        }).await?;
+            
+        let status = response.status();
+        let signature = response_signature(&response);
+        let body = response.text().await?;
+        
+        verify_response(credentials, status, signature.as_deref(), &body, Signing::Required)?;
+        
+        debug!("Wellbit {} response: status={}, body={}", action, status, body);
+        
+        if status.is_success() {
+            Ok(serde_json::from_str(&body)?)
+        } else {
+            Err(ApiError::from_response(status, &body))
+        }
+    }
+}
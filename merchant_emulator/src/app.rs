@@ -0,0 +1,167 @@
+use crate::api::{ApiClient, CallbackServer};
+use crate::device::{DeviceApiClient, DeviceManager, DevicePingService};
+use crate::models::Config;
+use crate::services::{
+    CatalogService, DeviceNotificationService, MerchantService, Protocols, ReconciliationService, ScenarioRunner, StatisticsService,
+    StatusTracker, StorageService, TrafficGenerator,
+};
+use anyhow::Result;
+use std::sync::Arc;
+use tracing::{error, info};
+
+/// The emulator's services, wired together once for the interactive menu and the CLI.
+pub struct App {
+    pub config: Config,
+    pub storage: Arc<StorageService>,
+    pub stats_service: Arc<StatisticsService>,
+    pub api_client: Arc<ApiClient>,
+    pub catalog: Arc<CatalogService>,
+    pub status_tracker: Arc<StatusTracker>,
+    pub merchant_service: Arc<MerchantService>,
+    pub device_manager: Arc<DeviceManager>,
+    pub device_api_client: Arc<DeviceApiClient>,
+    pub device_notifications: Arc<DeviceNotificationService>,
+    pub traffic_generator: Arc<TrafficGenerator>,
+    pub reconciliation_service: ReconciliationService,
+    pub scenario_runner: ScenarioRunner,
+    pub ping_service: Arc<DevicePingService>,
+}
+
+impl App {
+    /// Creates the data directories and services, and restores saved state.
+    pub async fn new(config: Config) -> Result<Self> {
+        tokio::fs::create_dir_all(&config.data_dir).await?;
+        tokio::fs::create_dir_all(&config.export_dir).await?;
+        
+        let storage = Arc::new(StorageService::new(config.data_dir.clone()).await?);
+        let stats_service = Arc::new(StatisticsService::new());
+        let api_client = Arc::new(ApiClient::new(
+            config.api_base_url.clone(),
+            config.retry.clone(),
+            stats_service.clone(),
+        )?);
+        
+        // Restore statistics so counters survive restarts
+        stats_service.load(storage.get_all_statistics());
+        for merchant in storage.get_all_merchants() {
+            stats_service.ensure_merchant(merchant.id);
+        }
+        
+        let catalog = Arc::new(CatalogService::new(
+            api_client.clone(),
+            stats_service.clone(),
+            std::time::Duration::from_secs(config.catalog_ttl_secs),
+        ));
+        
+        let protocols = Arc::new(Protocols::new(api_client.clone()));
+        
+        let status_tracker = Arc::new(StatusTracker::new(
+            protocols.clone(),
+            storage.clone(),
+            stats_service.clone(),
+            catalog.clone(),
+        ));
+        
+        let merchant_service = Arc::new(MerchantService::new(
+            api_client.clone(),
+            storage.clone(),
+            stats_service.clone(),
+            status_tracker.clone(),
+            catalog.clone(),
+            protocols.clone(),
+        ));
+        
+        // Create global device manager and load saved devices
+        let device_manager = Arc::new(DeviceManager::new(config.data_dir.clone()));
+        device_manager.load_devices().await?;
+        
+        let device_api_client = Arc::new(DeviceApiClient::new(config.api_base_url.clone()));
+        let device_notifications = Arc::new(DeviceNotificationService::new(
+            device_manager.clone(),
+            device_api_client.clone(),
+        ));
+        
+        let traffic_generator = Arc::new(TrafficGenerator::new(merchant_service.clone(), device_notifications.clone()));
+        let reconciliation_service = ReconciliationService::new(storage.clone());
+        let scenario_runner = ScenarioRunner::new(
+            merchant_service.clone(),
+            traffic_generator.clone(),
+            storage.clone(),
+            device_manager.clone(),
+            device_api_client.clone(),
+        );
+        
+        let ping_service = Arc::new(DevicePingService::new(
+            device_manager.clone(),
+            device_api_client.clone()
+        ));
+        
+        Ok(Self {
+            config,
+            storage,
+            stats_service,
+            api_client,
+            catalog,
+            status_tracker,
+            merchant_service,
+            device_manager,
+            device_api_client,
+            device_notifications,
+            traffic_generator,
+            reconciliation_service,
+            scenario_runner,
+            ping_service,
+        })
+    }
+    
+    /// Picks up where the previous session stopped: polls its open
+    /// transactions and pings its connected devices.
+    pub async fn resume(&self) {
+        let resumed = self.status_tracker.resume(&self.storage.get_all_merchants());
+        if resumed > 0 {
+            info!("Resumed status tracking for {} open transactions", resumed);
+        }
+        
+        for device in self.device_manager.get_all_devices() {
+            if device.is_connected && device.token.is_some() {
+                if let Err(e) = self.ping_service.start_ping_for_device(device.id.clone()).await {
+                    error!("Failed to restart ping for device {}: {}", device.id, e);
+                }
+            }
+        }
+    }
+    
+    /// Starts the callback server and routes what it receives to the merchant that created the order.
+    pub fn start_callback_server(&self) {
+        let mut callback_server = CallbackServer::new(self.config.callback_server_port, self.storage.clone());
+        
+        if let Some(mut callback_rx) = callback_server.take_receiver() {
+            let merchant_service = self.merchant_service.clone();
+            tokio::spawn(async move {
+                while let Some(callback) = callback_rx.recv().await {
+                    if let Err(e) = merchant_service.route_callback(callback).await {
+                        error!("Failed to handle callback: {}", e);
+                    }
+                }
+            });
+        }
+        
+        tokio::spawn(async move {
+            if let Err(e) = callback_server.start().await {
+                error!("Callback server error: {}", e);
+            }
+        });
+    }
+    
+    /// Stops all traffic and writes everything to disk.
+    pub async fn shutdown(&self) -> Result<()> {
+        self.traffic_generator.stop_all_traffic().await?;
+        self.storage.save_merchants().await?;
+        self.storage.save_transactions().await?;
+        self.storage.save_payouts().await?;
+        self.storage.save_callbacks().await?;
+        self.merchant_service.persist_statistics().await?;
+        self.device_manager.save_devices().await?;
+        Ok(())
+    }
+}
\ No newline at end of file
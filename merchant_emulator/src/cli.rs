@@ -0,0 +1,498 @@
+use crate::api::{ControlServer, ControlState};
+use crate::app::App;
+use crate::models::{Config, Device, Merchant, MerchantSummary, Statistics};
+use crate::services::{RateProgress, ScenarioRunner};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use clap::{Parser, Subcommand};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio::fs;
+use tracing::info;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+const STATUS_INTERVAL: Duration = Duration::from_secs(1);
+const STALE_AFTER_SECS: i64 = 5; // A run whose status file is older than this has died
+const STOP_TIMEOUT: Duration = Duration::from_secs(15);
+const TOKEN_ENV: &str = "MERCHANT_EMULATOR_TOKEN";
+
+/// Without a subcommand the interactive menu starts. Subcommands print JSON to
+/// stdout and exit with status 1 on failure, with `{"error": ...}` as output.
+#[derive(Debug, Parser)]
+#[command(name = "merchant-emulator", version, about = "Merchant traffic emulator")]
+pub struct Cli {
+    /// Backend URL, instead of the configured one
+    #[arg(long, global = true)]
+    pub api_url: Option<String>,
+    
+    #[arg(long, global = true)]
+    pub data_dir: Option<PathBuf>,
+    
+    #[arg(long, global = true)]
+    pub export_dir: Option<PathBuf>,
+    
+    /// Port of the callback server started by `traffic start` and `run-scenario`
+    #[arg(long, global = true)]
+    pub callback_port: Option<u16>,
+    
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Create and list merchants
+    #[command(subcommand)]
+    Merchant(MerchantCommand),
+    
+    /// Run, stop and follow traffic
+    #[command(subcommand)]
+    Traffic(TrafficCommand),
+    
+    /// Manage emulated devices
+    #[command(subcommand)]
+    Device(DeviceCommand),
+    
+    /// Export a merchant's history, statistics, callback log and reconciliation report
+    Export {
+        /// Merchant id or name
+        merchant: String,
+    },
+    
+    /// Run a YAML scenario file and write its results bundle
+    RunScenario {
+        file: PathBuf,
+    },
+    
+    /// Run headless with the callback server, device pings and the control API
+    /// until interrupted or terminated
+    Daemon {
+        /// Address of the control API
+        #[arg(long, default_value = "127.0.0.1:8090")]
+        listen: String,
+        /// Bearer token the control API requires; also read from MERCHANT_EMULATOR_TOKEN
+        #[arg(long)]
+        token: Option<String>,
+        /// Serve the control API without a token on a non-loopback address
+        #[arg(long)]
+        allow_unauthenticated: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum MerchantCommand {
+    /// Create a merchant after verifying its API key with the backend
+    Create {
+        #[arg(long)]
+        name: String,
+        #[arg(long)]
+        api_key: String,
+        /// Private key for callback signature checks
+        #[arg(long)]
+        private_key: Option<String>,
+    },
+    List,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TrafficCommand {
+    /// Run traffic in the foreground until it stops by itself, `--duration`
+    /// passes, `traffic stop` is called or the process is interrupted
+    Start {
+        /// Merchant id or name
+        merchant: String,
+        /// Payment method id; the first available method when unset
+        #[arg(long)]
+        method: Option<String>,
+        #[arg(long)]
+        max_transactions: Option<u64>,
+        /// Milliseconds between orders, without variance
+        #[arg(long)]
+        interval_ms: Option<u64>,
+        /// Seconds to run for
+        #[arg(long)]
+        duration: Option<u64>,
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Ask a running `traffic start` to stop, and wait for it
+    Stop {
+        merchant: String,
+    },
+    /// Show running traffic, or one merchant's run and statistics
+    Status {
+        merchant: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DeviceCommand {
+    Create {
+        #[arg(long)]
+        name: String,
+    },
+    List,
+    /// Connect a device with the code shown in the trader's account
+    Connect {
+        /// Device id or name
+        device: String,
+        #[arg(long)]
+        code: String,
+        /// Trader whose orders the device receives payment notifications for
+        #[arg(long)]
+        trader_id: Option<String>,
+    },
+    /// Send a bank notification from a connected device
+    Notify {
+        device: String,
+        #[arg(long)]
+        amount: f64,
+        #[arg(long, default_value = "Sberbank")]
+        bank: String,
+    },
+}
+
+impl Cli {
+    /// Log level when RUST_LOG is unset: one-shot commands only report problems.
+    pub fn default_log_level(&self) -> &'static str {
+        match self.command {
+            None | Some(Command::Daemon { .. }) => "info",
+            Some(_) => "warn",
+        }
+    }
+    
+    pub fn apply(&self, config: &mut Config) {
+        if let Some(url) = &self.api_url {
+            config.api_base_url = url.trim_end_matches('/').to_string();
+        }
+        if let Some(dir) = &self.data_dir {
+            config.data_dir = dir.clone();
+        }
+        if let Some(dir) = &self.export_dir {
+            config.export_dir = dir.clone();
+        }
+        if let Some(port) = self.callback_port {
+            config.callback_server_port = port;
+        }
+    }
+}
+
+/// Written by a running `traffic start` so other invocations can follow and stop it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TrafficRun {
+    merchant_id: uuid::Uuid,
+    merchant: String,
+    method_id: String,
+    pid: u32,
+    started_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    statistics: Option<Statistics>,
+    rate: Option<RateProgress>,
+}
+
+impl TrafficRun {
+    fn is_alive(&self) -> bool {
+        (Utc::now() - self.updated_at).num_seconds() < STALE_AFTER_SECS
+    }
+}
+
+/// Prints the JSON result of a subcommand and returns the exit code.
+pub fn print_result(result: Result<Value>) -> i32 {
+    let (value, code) = match result {
+        Ok(value) => (value, 0),
+        Err(e) => (json!({ "error": format!("{:#}", e) }), 1),
+    };
+    println!("{}", serde_json::to_string_pretty(&value).unwrap_or_else(|_| value.to_string()));
+    code
+}
+
+pub async fn run(app: &App, command: Command) -> Result<Value> {
+    match command {
+        Command::Merchant(MerchantCommand::Create { name, api_key, private_key }) => {
+            let merchant = app.merchant_service.create_merchant(name, api_key, private_key).await?;
+            Ok(serde_json::to_value(MerchantSummary::from(&merchant))?)
+        }
+        
+        Command::Merchant(MerchantCommand::List) => {
+            let mut merchants = app.merchant_service.get_all_merchants();
+            merchants.sort_by_key(|m| m.created_at);
+            let summaries: Vec<_> = merchants.iter().map(MerchantSummary::from).collect();
+            Ok(serde_json::to_value(summaries)?)
+        }
+        
+        Command::Traffic(TrafficCommand::Start { merchant, method, max_transactions, interval_ms, duration, seed }) => {
+            let mut merchant = find_merchant(app, &merchant)?;
+            if max_transactions.is_some() {
+                merchant.traffic_config.max_transactions = max_transactions;
+            }
+            if let Some(interval_ms) = interval_ms {
+                merchant.traffic_config.interval_ms = interval_ms;
+                merchant.traffic_config.interval_variance = 0;
+            }
+            if seed.is_some() {
+                merchant.traffic_config.seed = seed;
+            }
+            run_traffic(app, merchant, method, duration.map(Duration::from_secs)).await
+        }
+        
+        Command::Traffic(TrafficCommand::Stop { merchant }) => {
+            let merchant = find_merchant(app, &merchant)?;
+            let run = read_run(app, &merchant.id)
+                .await
+                .filter(TrafficRun::is_alive)
+                .ok_or_else(|| anyhow!("Traffic is not running for {}", merchant.name))?;
+            
+            fs::write(stop_path(app, &merchant.id), b"").await?;
+            let started = Instant::now();
+            while read_run(app, &merchant.id).await.is_some_and(|run| run.is_alive()) {
+                if started.elapsed() > STOP_TIMEOUT {
+                    return Err(anyhow!("Traffic for {} did not stop within {:?}", merchant.name, STOP_TIMEOUT));
+                }
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+            
+            Ok(json!({ "merchant_id": merchant.id, "stopped": true, "pid": run.pid }))
+        }
+        
+        Command::Traffic(TrafficCommand::Status { merchant: Some(merchant) }) => {
+            let merchant = find_merchant(app, &merchant)?;
+            let run = read_run(app, &merchant.id).await.filter(TrafficRun::is_alive);
+            Ok(json!({
+                "merchant_id": merchant.id,
+                "merchant": merchant.name,
+                "running": run.is_some(),
+                "run": run,
+                "statistics": app.stats_service.get_statistics(&merchant.id),
+            }))
+        }
+        
+        Command::Traffic(TrafficCommand::Status { merchant: None }) => {
+            let mut runs = Vec::new();
+            for merchant in app.merchant_service.get_all_merchants() {
+                if let Some(run) = read_run(app, &merchant.id).await.filter(TrafficRun::is_alive) {
+                    runs.push(run);
+                }
+            }
+            Ok(serde_json::to_value(runs)?)
+        }
+        
+        Command::Device(DeviceCommand::Create { name }) => {
+            let device = app.device_manager.create_device(name).await?;
+            Ok(serde_json::to_value(device)?)
+        }
+        
+        Command::Device(DeviceCommand::List) => {
+            let mut devices = app.device_manager.get_all_devices();
+            devices.sort_by_key(|d| d.created_at);
+            Ok(serde_json::to_value(devices)?)
+        }
+        
+        Command::Device(DeviceCommand::Connect { device, code, trader_id }) => {
+            let device = find_device(app, &device)?;
+            let mut connected = app.device_manager.connect_with_code(&app.device_api_client, &device.id, code).await?;
+            if let Some(trader_id) = trader_id {
+                app.device_manager.link_device_to_trader(&device.id, &trader_id).await?;
+                connected.trader_id = Some(trader_id);
+            }
+            Ok(serde_json::to_value(connected)?)
+        }
+        
+        Command::Device(DeviceCommand::Notify { device, amount, bank }) => {
+            let device = find_device(app, &device)?;
+            app.device_notifications.send_test_notification(&device.id, &bank, amount).await?;
+            Ok(json!({ "device_id": device.id, "bank": bank, "amount": amount, "sent": true }))
+        }
+        
+        Command::Export { merchant } => {
+            let merchant = find_merchant(app, &merchant)?;
+            let export_dir = &app.config.export_dir;
+            let (history, statistics) = app.merchant_service.export_merchant_data(&merchant.id, export_dir).await?;
+            let callbacks = app.merchant_service.export_callbacks(&merchant.id, export_dir).await?;
+            let reconciliation = app.reconciliation_service.export(&merchant.id, export_dir).await?;
+            Ok(json!({
+                "merchant_id": merchant.id,
+                "history": history,
+                "statistics": statistics,
+                "callbacks": callbacks,
+                "reconciliation": reconciliation,
+            }))
+        }
+        
+        Command::RunScenario { file } => {
+            let scenario = ScenarioRunner::load(&file).await?;
+            app.start_callback_server();
+            let result = app.scenario_runner.run(&scenario, &app.config.export_dir).await?;
+            Ok(serde_json::to_value(result)?)
+        }
+        
+        Command::Daemon { listen, token, allow_unauthenticated } => {
+            app.resume().await;
+            app.start_callback_server();
+            
+            let server = ControlServer::new(listen.clone(), ControlState {
+                merchant_service: app.merchant_service.clone(),
+                traffic_generator: app.traffic_generator.clone(),
+                stats_service: app.stats_service.clone(),
+                device_manager: app.device_manager.clone(),
+                token: token.or_else(|| std::env::var(TOKEN_ENV).ok()).filter(|t| !t.is_empty()),
+            })
+            .allow_unauthenticated(allow_unauthenticated);
+            
+            let started_at = Utc::now();
+            tokio::select! {
+                result = server.start() => result.map_err(|e| anyhow!("Control API on {} failed: {}", listen, e))?,
+                signal = shutdown_signal() => info!("Received {}, shutting down", signal),
+            }
+            app.shutdown().await?;
+            
+            Ok(json!({ "listen": listen, "started_at": started_at, "stopped_at": Utc::now() }))
+        }
+    }
+}
+
+/// Runs one merchant's traffic in the foreground, keeping its status file current.
+async fn run_traffic(app: &App, mut merchant: Merchant, method: Option<String>, duration: Option<Duration>) -> Result<Value> {
+    if read_run(app, &merchant.id).await.is_some_and(|run| run.is_alive()) {
+        return Err(anyhow!("Traffic is already running for {}", merchant.name));
+    }
+    
+    let method_id = match method {
+        Some(method) => method,
+        None => app.merchant_service
+            .get_available_methods(&merchant)
+            .await?
+            .first()
+            .map(|m| m.id.clone())
+            .ok_or_else(|| anyhow!("No payment methods available for {}", merchant.name))?,
+    };
+    
+    merchant.traffic_config.enabled = true;
+    app.merchant_service.update_merchant(merchant.clone()).await?;
+    app.start_callback_server();
+    app.traffic_generator.start_traffic(merchant.clone(), method_id.clone(), true).await?;
+    
+    let runs_dir = app.config.data_dir.join("traffic_runs");
+    fs::create_dir_all(&runs_dir).await?;
+    let _ = fs::remove_file(stop_path(app, &merchant.id)).await;
+    
+    let mut run = TrafficRun {
+        merchant_id: merchant.id,
+        merchant: merchant.name.clone(),
+        method_id,
+        pid: std::process::id(),
+        started_at: Utc::now(),
+        updated_at: Utc::now(),
+        statistics: None,
+        rate: None,
+    };
+    
+    let started = Instant::now();
+    let mut last_status: Option<Instant> = None;
+    let interrupt = shutdown_signal();
+    tokio::pin!(interrupt);
+    
+    let stopped_by = loop {
+        if !app.traffic_generator.is_running(&merchant.id).await {
+            break "finished";
+        }
+        if duration.is_some_and(|duration| started.elapsed() >= duration) {
+            break "duration";
+        }
+        if fs::try_exists(stop_path(app, &merchant.id)).await.unwrap_or(false) {
+            break "stop_request";
+        }
+        
+        if last_status.is_none_or(|at| at.elapsed() >= STATUS_INTERVAL) {
+            run.updated_at = Utc::now();
+            run.statistics = app.stats_service.get_statistics(&merchant.id);
+            run.rate = app.traffic_generator.rate_progress(&merchant.id).await;
+            fs::write(run_path(app, &merchant.id), serde_json::to_string_pretty(&run)?).await?;
+            last_status = Some(Instant::now());
+        }
+        
+        tokio::select! {
+            _ = &mut interrupt => break "interrupted",
+            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+        }
+    };
+    
+    if stopped_by != "finished" {
+        app.traffic_generator.stop_traffic(&merchant.id).await?;
+    }
+    merchant.traffic_config.enabled = false;
+    app.merchant_service.update_merchant(merchant.clone()).await?;
+    app.shutdown().await?;
+    
+    let _ = fs::remove_file(run_path(app, &merchant.id)).await;
+    let _ = fs::remove_file(stop_path(app, &merchant.id)).await;
+    
+    Ok(json!({
+        "merchant_id": merchant.id,
+        "method_id": run.method_id,
+        "stopped_by": stopped_by,
+        "elapsed_secs": started.elapsed().as_secs_f64(),
+        "statistics": app.stats_service.get_statistics(&merchant.id),
+    }))
+}
+
+/// Waits for Ctrl-C, or SIGTERM where there is one, and names the signal.
+async fn shutdown_signal() -> &'static str {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        if let Ok(mut terminate) = signal(SignalKind::terminate()) {
+            return tokio::select! {
+                _ = tokio::signal::ctrl_c() => "interrupt",
+                _ = terminate.recv() => "terminate",
+            };
+        }
+    }
+    
+    let _ = tokio::signal::ctrl_c().await;
+    "interrupt"
+}
+
+fn run_path(app: &App, merchant_id: &uuid::Uuid) -> PathBuf {
+    app.config.data_dir.join("traffic_runs").join(format!("{}.json", merchant_id))
+}
+
+fn stop_path(app: &App, merchant_id: &uuid::Uuid) -> PathBuf {
+    app.config.data_dir.join("traffic_runs").join(format!("{}.stop", merchant_id))
+}
+
+async fn read_run(app: &App, merchant_id: &uuid::Uuid) -> Option<TrafficRun> {
+    let data = fs::read_to_string(run_path(app, merchant_id)).await.ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+/// Looks a merchant up by id, then by name.
+fn find_merchant(app: &App, key: &str) -> Result<Merchant> {
+    let merchants = app.merchant_service.get_all_merchants();
+    if let Some(merchant) = merchants.iter().find(|m| m.id.to_string() == key) {
+        return Ok(merchant.clone());
+    }
+    
+    let mut named = merchants.into_iter().filter(|m| m.name == key);
+    match (named.next(), named.next()) {
+        (Some(merchant), None) => Ok(merchant),
+        (Some(_), Some(_)) => Err(anyhow!("Several merchants are named {}; use the id", key)),
+        (None, _) => Err(anyhow!("Merchant {} not found", key)),
+    }
+}
+
+/// Looks a device up by id, then by name.
+fn find_device(app: &App, key: &str) -> Result<Device> {
+    let devices = app.device_manager.get_all_devices();
+    if let Some(device) = devices.iter().find(|d| d.id == key) {
+        return Ok(device.clone());
+    }
+    
+    let mut named = devices.into_iter().filter(|d| d.name == key);
+    match (named.next(), named.next()) {
+        (Some(device), None) => Ok(device),
+        (Some(_), Some(_)) => Err(anyhow!("Several devices are named {}; use the id", key)),
+        (None, _) => Err(anyhow!("Device {} not found", key)),
+    }
+}
\ No newline at end of file
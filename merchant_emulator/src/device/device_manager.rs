@@ -1,3 +1,4 @@
+use crate::device::{ConnectDeviceRequest, DeviceApiClient};
 use crate::models::Device;
 use anyhow::Result;
 use std::collections::HashMap;
@@ -89,6 +90,27 @@ impl DeviceManager {
         Ok(())
     }
     
+    /// Connects the device to the backend with a device code and stores the token it returns.
+    pub async fn connect_with_code(&self, api_client: &DeviceApiClient, id: &str, device_code: String) -> Result<Device> {
+        let device = self.get_device(id).ok_or_else(|| anyhow::anyhow!("Device not found"))?;
+        
+        let request = ConnectDeviceRequest {
+            device_code: device_code.clone(),
+            battery_level: device.battery_level,
+            network_info: device.network_info.clone(),
+            device_model: device.device_model.clone(),
+            android_version: device.android_version.clone(),
+            app_version: device.app_version.clone(),
+        };
+        let response = api_client.connect_device(request).await?;
+        let token = response
+            .token
+            .ok_or_else(|| anyhow::anyhow!("Connection failed: {}", response.message))?;
+        
+        self.connect_device(id, device_code, token).await?;
+        self.get_device(id).ok_or_else(|| anyhow::anyhow!("Device not found"))
+    }
+    
     pub async fn disconnect_device(&self, id: &str) -> Result<()> {
         {
             let mut devices = self.devices.write();
@@ -9,6 +9,12 @@ pub struct NotificationEmulator {
     receiver: mpsc::UnboundedReceiver<DeviceNotification>,
 }
 
+impl Default for NotificationEmulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl NotificationEmulator {
     pub fn new() -> Self {
         let (sender, receiver) = mpsc::unbounded_channel();
@@ -68,7 +68,7 @@ impl DevicePingService {
                     }
                     Err(_) => {
                         // Try simple ping as fallback
-                        if let Err(_) = api_client.ping(Some(&token)).await {
+                        if api_client.ping(Some(&token)).await.is_err() {
                             // Disconnect device after failed ping
                             let _ = device_manager.disconnect_device(&device_id_clone).await;
                             break;
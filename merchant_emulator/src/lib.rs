@@ -0,0 +1,7 @@
+pub mod api;
+pub mod app;
+pub mod cli;
+pub mod device;
+pub mod models;
+pub mod services;
+pub mod ui;
@@ -1,84 +1,65 @@
-mod api;
-mod device;
-mod models;
-mod services;
-mod ui;
-
 use anyhow::Result;
-use api::{ApiClient, CallbackServer};
-use device::{DeviceManager, NotificationEmulator, DeviceApiClient};
+use clap::Parser;
+use merchant_emulator::{app::App, cli, device, models, services, ui};
+use cli::Cli;
+use device::NotificationEmulator;
 use models::Config;
-use services::{MerchantService, StorageService, StatisticsService, TrafficGenerator};
+use services::{LogCaptureLayer, ScenarioRunner};
 use std::sync::Arc;
-use tokio::sync::{RwLock, mpsc};
-use tracing::{error, info};
-use tracing_subscriber::{fmt, prelude::*, EnvFilter};
-use ui::{MainMenu, MenuItem, MerchantMenu, MerchantMenuItem, TrafficMenu, LogViewer};
+use tokio::sync::RwLock;
+use tracing::info;
+use tracing_subscriber::{filter::filter_fn, fmt, prelude::*, EnvFilter};
+use ui::{CallbackLogAction, Dashboard, MainMenu, MenuItem, MerchantMenu, MerchantMenuItem, TrafficMenu, LogViewer};
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize logging
+    let cli = Cli::parse();
+    
+    // Initialize logging. Logs go to stderr so subcommands keep stdout for JSON,
+    // except while a full-screen view captures them
+    let (log_capture_layer, log_capture) = LogCaptureLayer::new();
+    let capturing = log_capture.clone();
     tracing_subscriber::registry()
-        .with(fmt::layer())
+        .with(fmt::layer().with_writer(std::io::stderr).with_filter(filter_fn(move |_| !capturing.is_capturing())))
+        .with(log_capture_layer)
         .with(
             EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| EnvFilter::new("info"))
+                .unwrap_or_else(|_| EnvFilter::new(cli.default_log_level()))
         )
         .init();
 
-    info!("Starting Merchant Emulator");
-
     // Load configuration
-    let config = Config::default();
-    
-    // Create directories
-    tokio::fs::create_dir_all(&config.data_dir).await?;
-    tokio::fs::create_dir_all(&config.export_dir).await?;
-
-    // Initialize services
-    let api_client = Arc::new(ApiClient::new(config.api_base_url.clone())?);
-    let storage = Arc::new(StorageService::new(config.data_dir.clone()).await?);
-    let stats_service = Arc::new(StatisticsService::new());
-    
-    let merchant_service = Arc::new(MerchantService::new(
-        api_client.clone(),
-        storage.clone(),
-        stats_service.clone(),
-    ));
+    let mut config = Config::default();
+    cli.apply(&mut config);
     
-    let traffic_generator = Arc::new(TrafficGenerator::new(merchant_service.clone()));
+    let app = App::new(config).await?;
     
-    // Create global device manager and ping service
-    let device_manager = Arc::new(DeviceManager::new(config.data_dir.clone()));
-    // Load saved devices
-    device_manager.load_devices().await?;
+    if let Some(command) = cli.command {
+        let code = cli::print_result(cli::run(&app, command).await);
+        std::process::exit(code);
+    }
     
-    let device_api_client = Arc::new(DeviceApiClient::new(config.api_base_url.clone()));
-    let ping_service = Arc::new(device::DevicePingService::new(
-        device_manager.clone(),
-        device_api_client.clone()
-    ));
+    info!("Starting Merchant Emulator");
     
-    // Restart ping services for connected devices
-    for device in device_manager.get_all_devices() {
-        if device.is_connected && device.token.is_some() {
-            if let Err(e) = ping_service.start_ping_for_device(device.id.clone()).await {
-                error!("Failed to restart ping for device {}: {}", device.id, e);
-            }
-        }
-    }
+    app.resume().await;
+    app.start_callback_server();
     
     let _notification_emulator = Arc::new(RwLock::new(NotificationEmulator::new()));
     
-    // Start callback server
-    let callback_server = CallbackServer::new(config.callback_server_port);
-    let _callback_sender = callback_server.get_sender();
-    
-    tokio::spawn(async move {
-        if let Err(e) = callback_server.start().await {
-            error!("Callback server error: {}", e);
-        }
-    });
+    let App {
+        config,
+        storage,
+        stats_service,
+        status_tracker,
+        merchant_service,
+        device_manager,
+        device_api_client,
+        traffic_generator,
+        reconciliation_service,
+        scenario_runner,
+        ping_service,
+        ..
+    } = &app;
 
     // Main application loop
     loop {
@@ -86,9 +67,9 @@ async fn main() -> Result<()> {
         
         match MainMenu::show()? {
             MenuItem::CreateMerchant => {
-                let (name, api_key) = MainMenu::get_merchant_details()?;
+                let (name, api_key, private_key) = MainMenu::get_merchant_details()?;
                 
-                match merchant_service.create_merchant(name, api_key).await {
+                match merchant_service.create_merchant(name, api_key, private_key).await {
                     Ok(merchant) => {
                         MainMenu::show_success(&format!("Created merchant: {}", merchant.name));
                     }
@@ -190,6 +171,84 @@ async fn main() -> Result<()> {
                             }
                         }
                         
+                        MerchantMenuItem::CancelTransaction => {
+                            let transactions = storage.get_merchant_transactions(&merchant.id);
+                            let order_id = MerchantMenu::select_order_to_cancel(&transactions)?;
+                            
+                            match merchant_service.cancel_transaction(&merchant, &order_id).await {
+                                Ok(canceled) => MainMenu::show_success(&format!(
+                                    "Order {} cancelled (status: {:?})", order_id, canceled.status
+                                )),
+                                Err(e) => MainMenu::show_error(&format!("Failed to cancel order: {}", e)),
+                            }
+                        }
+                        
+                        MerchantMenuItem::Receipts => {
+                            let transactions = storage.get_merchant_transactions(&merchant.id);
+                            
+                            if transactions.iter().all(|tx| tx.error.is_some()) {
+                                MainMenu::show_info("No created transactions");
+                                continue;
+                            }
+                            
+                            let Some(history) = MerchantMenu::select_transaction_for_receipts(&transactions)? else {
+                                continue;
+                            };
+                            
+                            let receipts = match merchant_service.list_receipts(&merchant, &history.transaction.id).await {
+                                Ok(receipts) => receipts,
+                                Err(e) => {
+                                    MainMenu::show_error(&format!("Failed to load receipts: {}", e));
+                                    continue;
+                                }
+                            };
+                            
+                            if let Some(format) = MerchantMenu::select_receipt_upload(&receipts)? {
+                                match merchant_service.attach_receipt(&merchant, &history.transaction, format).await {
+                                    Ok(receipt) => MainMenu::show_success(&format!("Receipt {} uploaded", receipt.file_name)),
+                                    Err(e) => MainMenu::show_error(&format!("Receipt upload failed: {}", e)),
+                                }
+                            }
+                        }
+                        
+                        MerchantMenuItem::Disputes => {
+                            let dispute_menu = ui::DisputeMenu::new(merchant_service.clone(), storage.clone());
+                            if let Err(e) = dispute_menu.run(&merchant).await {
+                                MainMenu::show_error(&format!("Dispute menu error: {}", e));
+                            }
+                        }
+                        
+                        MerchantMenuItem::ViewCallbackLog => loop {
+                            let callbacks = storage.get_merchant_callbacks(&merchant.id);
+                            
+                            if callbacks.is_empty() {
+                                MainMenu::show_info("No callbacks received");
+                                break;
+                            }
+                            
+                            match MerchantMenu::browse_callback_log(&callbacks)? {
+                                CallbackLogAction::View(index) => MerchantMenu::show_callback_details(&callbacks[index]),
+                                CallbackLogAction::Export => {
+                                    match merchant_service.export_callbacks(&merchant.id, &config.export_dir).await {
+                                        Ok(path) => MainMenu::show_success(&format!("Callback log exported: {:?}", path)),
+                                        Err(e) => MainMenu::show_error(&format!("Export failed: {}", e)),
+                                    }
+                                }
+                                CallbackLogAction::Back => break,
+                            }
+                        },
+                        
+                        MerchantMenuItem::Reconciliation => {
+                            let report = reconciliation_service.report(&merchant.id);
+                            
+                            if MerchantMenu::show_reconciliation_report(&report)? {
+                                match reconciliation_service.export(&merchant.id, &config.export_dir).await {
+                                    Ok(path) => MainMenu::show_success(&format!("Report exported: {:?}", path)),
+                                    Err(e) => MainMenu::show_error(&format!("Export failed: {}", e)),
+                                }
+                            }
+                        }
+                        
                         MerchantMenuItem::ViewStatistics => {
                             if let Some(stats) = stats_service.get_statistics(&merchant.id) {
                                 println!("\nStatistics for {}:", merchant.name);
@@ -205,6 +264,29 @@ async fn main() -> Result<()> {
                                 println!("  Callbacks received: {}", stats.callbacks_received);
                                 println!("  Liquid transactions: {}", stats.liquid_transactions);
                                 println!("  Non-liquid transactions: {}", stats.non_liquid_transactions);
+                                println!("  Cancelled transactions: {}", stats.cancelled_transactions);
+                                println!("  Failed cancellations: {}", stats.failed_cancellations);
+                                println!("  Payouts created: {}", stats.payouts_created);
+                                println!("  Payouts failed: {}", stats.payouts_failed);
+                                println!("  Receipts uploaded: {}", stats.receipts_uploaded);
+                                println!("  Receipt uploads failed: {}", stats.receipts_failed);
+                                println!("  Disputes opened: {}", stats.disputes_opened);
+                                println!("  Dispute requests failed: {}", stats.disputes_failed);
+                                println!("  Dispute messages sent: {}", stats.dispute_messages_sent);
+                                println!("  Total payout amount: {} RUB", stats.total_payout_amount);
+                                println!("  Retry attempts: {} ({} calls recovered)", stats.retry_attempts, stats.retries_recovered);
+                                
+                                if let Some(progress) = traffic_generator.rate_progress(&merchant.id).await {
+                                    println!("\n  Rate run in progress:");
+                                    println!("    Target: {:.1} TPS, achieved: {:.1} TPS", progress.target_tps, progress.achieved_tps);
+                                    println!("    Dispatched: {} ({} created, {} in flight)", progress.dispatched, progress.created, progress.in_flight);
+                                    println!("    Behind schedule: {} dispatches, lag now {} ms, max {} ms{}",
+                                        progress.late, progress.lag_ms, progress.max_lag_ms,
+                                        if progress.is_behind() { " - FALLING BEHIND" } else { "" });
+                                    if let Some(time) = progress.simulated_time {
+                                        println!("    Simulated time: {} (load x{:.2})", time.format("%a %H:%M"), progress.load_multiplier);
+                                    }
+                                }
                                 
                                 if !stats.error_breakdown.is_empty() {
                                     println!("\n  Error breakdown:");
@@ -213,6 +295,20 @@ async fn main() -> Result<()> {
                                     }
                                 }
                                 
+                                if !stats.retry_breakdown.is_empty() {
+                                    println!("\n  Retries:");
+                                    for (reason, count) in &stats.retry_breakdown {
+                                        println!("    {}: {}", reason, count);
+                                    }
+                                }
+                                
+                                if !stats.unknown_values.is_empty() {
+                                    println!("\n  Unrecognised values:");
+                                    for (value, count) in &stats.unknown_values {
+                                        println!("    {}: {}", value, count);
+                                    }
+                                }
+                                
                                 if !stats.status_breakdown.is_empty() {
                                     println!("\n  Status breakdown:");
                                     for (status, count) in &stats.status_breakdown {
@@ -220,6 +316,32 @@ async fn main() -> Result<()> {
                                     }
                                 }
                                 
+                                if !stats.payout_status_breakdown.is_empty() {
+                                    println!("\n  Payout status breakdown:");
+                                    for (status, count) in &stats.payout_status_breakdown {
+                                        println!("    {}: {}", status, count);
+                                    }
+                                }
+                                
+                                if !stats.conversion_breakdown.is_empty() {
+                                    println!("\n  Buyer conversion by amount:");
+                                    let mut bands: Vec<_> = stats.conversion_breakdown.iter().collect();
+                                    bands.sort_by_key(|(band, _)| {
+                                        band.split('-').next().and_then(|min| min.parse::<u64>().ok()).unwrap_or(u64::MAX)
+                                    });
+                                    for (band, conversion) in bands {
+                                        println!("    {} RUB: {} orders, {} paid ({:.1}%), {} notified, {} cancelled, {} abandoned",
+                                            band,
+                                            conversion.orders,
+                                            conversion.paid,
+                                            conversion.paid as f64 / conversion.orders.max(1) as f64 * 100.0,
+                                            conversion.notified,
+                                            conversion.cancelled,
+                                            conversion.abandoned,
+                                        );
+                                    }
+                                }
+                                
                                 println!("\nPress Enter to continue...");
                                 let _ = std::io::stdin().read_line(&mut String::new());
                             } else {
@@ -240,11 +362,39 @@ async fn main() -> Result<()> {
                         }
                         
                         MerchantMenuItem::ConfigureCallback => {
-                            merchant.callback_url = MerchantMenu::get_callback_url()?;
+                            let default_url = format!(
+                                "http://localhost:{}/callback/{}",
+                                config.callback_server_port, merchant.id
+                            );
+                            merchant.callback_url = MerchantMenu::get_callback_url(&default_url)?;
                             merchant_service.update_merchant(merchant.clone()).await?;
                             MainMenu::show_success("Callback URL updated");
                         }
                         
+                        MerchantMenuItem::ConfigureCallbackBehavior => {
+                            merchant.callback_behavior = MerchantMenu::configure_callback_behavior(&merchant.callback_behavior)?;
+                            merchant_service.update_merchant(merchant.clone()).await?;
+                            MainMenu::show_success("Callback responses updated");
+                        }
+                        
+                        MerchantMenuItem::ConfigurePrivateKey => {
+                            merchant.private_key = MerchantMenu::get_private_key()?;
+                            merchant_service.update_merchant(merchant.clone()).await?;
+                            MainMenu::show_success(if merchant.private_key.is_some() {
+                                "Signature verification enabled"
+                            } else {
+                                "Signature verification disabled"
+                            });
+                        }
+                        
+                        MerchantMenuItem::ConfigureProtocol => {
+                            let (protocol, wellbit) = MerchantMenu::configure_protocol(&merchant)?;
+                            merchant.protocol = protocol;
+                            merchant.wellbit = wellbit;
+                            merchant_service.update_merchant(merchant.clone()).await?;
+                            MainMenu::show_success(&format!("Merchant protocol set to {}", merchant.protocol));
+                        }
+                        
                         MerchantMenuItem::TogglePaymentType => {
                             merchant.payment_type = match merchant.payment_type {
                                 models::PaymentType::Rub => {
@@ -333,10 +483,11 @@ async fn main() -> Result<()> {
                     let mut total_successful = 0u64;
                     let mut total_failed = 0u64;
                     let mut total_amount = 0.0;
+                    let mut by_protocol: std::collections::BTreeMap<String, (u64, u64, f64)> = std::collections::BTreeMap::new();
                     
                     for (merchant_id, stats) in &all_stats {
                         if let Some(merchant) = storage.get_merchant(merchant_id) {
-                            println!("\n  {}:", merchant.name);
+                            println!("\n  {} ({}):", merchant.name, merchant.protocol);
                             println!("    Requests: {} (Success: {}, Failed: {})",
                                 stats.total_requests, stats.successful_requests, stats.failed_requests);
                             println!("    Amount: {} RUB", stats.total_amount);
@@ -345,6 +496,22 @@ async fn main() -> Result<()> {
                             total_successful += stats.successful_requests;
                             total_failed += stats.failed_requests;
                             total_amount += stats.total_amount;
+                            
+                            let protocol = by_protocol.entry(merchant.protocol.to_string()).or_default();
+                            protocol.0 += stats.total_requests;
+                            protocol.1 += stats.successful_requests;
+                            protocol.2 += stats.total_amount;
+                        }
+                    }
+                    
+                    // Side by side view when the same scenario runs against both integrations
+                    if by_protocol.len() > 1 {
+                        println!("\n  By protocol:");
+                        for (protocol, (requests, successful, amount)) in &by_protocol {
+                            println!("    {}: {} requests, {:.2}% success, {} RUB",
+                                protocol, requests,
+                                if *requests > 0 { (*successful as f64 / *requests as f64) * 100.0 } else { 0.0 },
+                                amount);
                         }
                     }
                     
@@ -357,6 +524,7 @@ async fn main() -> Result<()> {
                         } else { 0.0 }
                     );
                     println!("    Total amount: {} RUB", total_amount);
+                    println!("    Open transactions being tracked: {}", status_tracker.tracked_count());
                     
                     println!("\nPress Enter to continue...");
                     let _ = std::io::stdin().read_line(&mut String::new());
@@ -370,7 +538,6 @@ async fn main() -> Result<()> {
             MenuItem::DeviceEmulator => {
                 if config.device_emulator_enabled {
                     let device_menu = ui::DeviceMenu::new(
-                        Arc::new(RwLock::new(config.clone())),
                         device_manager.clone(),
                         device_api_client.clone(),
                         ping_service.clone()
@@ -383,6 +550,62 @@ async fn main() -> Result<()> {
                 }
             }
             
+            MenuItem::RunScenario => {
+                let path = std::path::PathBuf::from(MainMenu::get_scenario_path()?.trim());
+                let scenario = match ScenarioRunner::load(&path).await {
+                    Ok(scenario) => scenario,
+                    Err(e) => {
+                        MainMenu::show_error(&format!("{:#}", e));
+                        continue;
+                    }
+                };
+                
+                println!("\nScenario {}:", scenario.name);
+                if let Some(description) = &scenario.description {
+                    println!("  {}", description);
+                }
+                println!("  Devices: {}", scenario.devices.len());
+                for merchant in &scenario.merchants {
+                    println!("  Merchant {} ({}, {:?})", merchant.name, merchant.protocol, merchant.traffic.mode);
+                }
+                if let Some(duration) = scenario.duration_secs {
+                    println!("  Stops after {}s", duration);
+                }
+                
+                if !MainMenu::confirm_action("Run this scenario?")? {
+                    continue;
+                }
+                
+                MainMenu::show_info("Running scenario, this may take a while...");
+                match scenario_runner.run(&scenario, &config.export_dir).await {
+                    Ok(result) => {
+                        println!("\nScenario {} finished{}", result.scenario,
+                            if result.stopped_early { " (stopped at its duration)" } else { "" });
+                        for merchant in &result.merchants {
+                            println!("  {}: {} transactions, {} payouts, {} callbacks ({} orders without one), seed {}",
+                                merchant.name, merchant.transactions, merchant.payouts, merchant.callbacks,
+                                merchant.missing_callbacks, merchant.seed);
+                        }
+                        MainMenu::show_success(&format!("Results written to {:?}", result.bundle_dir));
+                    }
+                    Err(e) => MainMenu::show_error(&format!("Scenario failed: {:#}", e)),
+                }
+            }
+            
+            MenuItem::Dashboard => {
+                let mut dashboard = Dashboard::new(
+                    merchant_service.clone(),
+                    traffic_generator.clone(),
+                    stats_service.clone(),
+                    storage.clone(),
+                    device_manager.clone(),
+                );
+                
+                if let Err(e) = dashboard.run(&log_capture).await {
+                    MainMenu::show_error(&format!("Dashboard error: {}", e));
+                }
+            }
+            
             MenuItem::Settings => {
                 println!("\nCurrent Settings:");
                 println!("  API URL: {}", config.api_base_url);
@@ -398,11 +621,7 @@ async fn main() -> Result<()> {
             MenuItem::Exit => {
                 if MainMenu::confirm_action("Are you sure you want to exit?")? {
                     info!("Shutting down...");
-                    traffic_generator.stop_all_traffic().await?;
-                    storage.save_merchants().await?;
-                    storage.save_transactions().await?;
-                    storage.save_statistics().await?;
-                    device_manager.save_devices().await?;
+                    app.shutdown().await?;
                     break;
                 }
             }
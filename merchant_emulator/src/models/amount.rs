@@ -0,0 +1,257 @@
+use crate::models::AmountRange;
+use rand::Rng;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+
+/// How generated order amounts are drawn, and how they are rounded.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AmountDistribution {
+    #[serde(flatten)]
+    pub model: AmountModel,
+    #[serde(default)]
+    pub rounding: AmountRounding,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum AmountModel {
+    /// A range is picked by weight, then an amount uniformly inside it.
+    /// Weights are relative and need not sum to 100.
+    Weighted { ranges: Vec<WeightedRange> },
+    /// Amounts cluster around `median`; `sigma` is the spread of ln(amount).
+    /// Draws outside min..=max are clamped.
+    LogNormal { median: f64, sigma: f64, min: f64, max: f64 },
+    /// One of the listed amounts, each equally likely.
+    Fixed { amounts: Vec<f64> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WeightedRange {
+    pub min: u64,
+    pub max: u64,
+    pub weight: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AmountRounding {
+    #[default]
+    Kopecks,       // Two decimal places
+    Whole,         // Whole rubles
+    Multiple(u64), // Nearest multiple, e.g. 100 for 1500, 1600...
+}
+
+impl std::fmt::Display for AmountRounding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AmountRounding::Kopecks => write!(f, "kopecks"),
+            AmountRounding::Whole => write!(f, "whole rubles"),
+            AmountRounding::Multiple(step) => write!(f, "multiples of {}", step),
+        }
+    }
+}
+
+impl AmountDistribution {
+    pub fn weighted(ranges: Vec<WeightedRange>) -> Self {
+        Self {
+            model: AmountModel::Weighted { ranges },
+            rounding: AmountRounding::default(),
+        }
+    }
+    
+    /// Deposit amounts: mostly small orders with a long tail.
+    pub fn default_deposits() -> Self {
+        Self::weighted(vec![
+            WeightedRange { min: 1000, max: 3000, weight: 30.0 },
+            WeightedRange { min: 3000, max: 5000, weight: 25.0 },
+            WeightedRange { min: 5000, max: 10000, weight: 20.0 },
+            WeightedRange { min: 10000, max: 20000, weight: 13.0 },
+            WeightedRange { min: 20000, max: 50000, weight: 8.0 },
+            WeightedRange { min: 50000, max: 100000, weight: 4.0 },
+        ])
+    }
+    
+    pub fn default_payouts() -> Self {
+        Self::weighted(vec![
+            WeightedRange { min: 1000, max: 5000, weight: 40.0 },
+            WeightedRange { min: 5000, max: 20000, weight: 35.0 },
+            WeightedRange { min: 20000, max: 50000, weight: 20.0 },
+            WeightedRange { min: 50000, max: 150000, weight: 5.0 },
+        ])
+    }
+    
+    /// Converts the old `amount_probabilities` map, reading its values as weights.
+    pub fn from_legacy(probabilities: &HashMap<AmountRange, f64>) -> Self {
+        let mut ranges: Vec<WeightedRange> = probabilities
+            .iter()
+            .map(|(range, weight)| WeightedRange { min: range.min, max: range.max, weight: *weight })
+            .collect();
+        ranges.sort_by_key(|range| range.min);
+        
+        Self::weighted(ranges)
+    }
+    
+    /// Checks that every amount this can produce is positive and well defined.
+    pub fn validate(&self) -> Result<(), String> {
+        match &self.model {
+            AmountModel::Weighted { ranges } => {
+                if ranges.is_empty() {
+                    return Err("At least one amount range is required".to_string());
+                }
+                for range in ranges {
+                    if range.min == 0 || range.min > range.max {
+                        return Err(format!("Invalid range {}-{}: min must be positive and not above max", range.min, range.max));
+                    }
+                    if !range.weight.is_finite() || range.weight < 0.0 {
+                        return Err(format!("Invalid weight {} for range {}-{}", range.weight, range.min, range.max));
+                    }
+                }
+                if ranges.iter().map(|r| r.weight).sum::<f64>() <= 0.0 {
+                    return Err("At least one range needs a weight above zero".to_string());
+                }
+            }
+            AmountModel::LogNormal { median, sigma, min, max } => {
+                if !(*min > 0.0 && min <= max && max.is_finite()) {
+                    return Err(format!("Invalid bounds {}-{}: min must be positive and not above max", min, max));
+                }
+                if !(*min..=*max).contains(median) {
+                    return Err(format!("Median {} is outside {}-{}", median, min, max));
+                }
+                if !sigma.is_finite() || *sigma < 0.0 {
+                    return Err(format!("Invalid sigma {}", sigma));
+                }
+            }
+            AmountModel::Fixed { amounts } => {
+                if amounts.is_empty() {
+                    return Err("At least one amount is required".to_string());
+                }
+                if let Some(amount) = amounts.iter().find(|a| !a.is_finite() || **a <= 0.0) {
+                    return Err(format!("Invalid amount {}", amount));
+                }
+            }
+        }
+        
+        if self.rounding == AmountRounding::Multiple(0) {
+            return Err("Rounding step must be above zero".to_string());
+        }
+        
+        Ok(())
+    }
+    
+    pub fn sample(&self, rng: &mut impl Rng) -> f64 {
+        match &self.model {
+            AmountModel::Weighted { ranges } => {
+                let total: f64 = ranges.iter().map(|r| r.weight.max(0.0)).sum();
+                if total <= 0.0 {
+                    return 1000.0;
+                }
+                
+                let mut roll = rng.gen_range(0.0..total);
+                let mut chosen = None;
+                for range in ranges.iter().filter(|r| r.weight > 0.0) {
+                    // Float error can leave a sliver past the last range, which then keeps it
+                    chosen = Some(range);
+                    if roll < range.weight {
+                        break;
+                    }
+                    roll -= range.weight;
+                }
+                let range = chosen.expect("a positive weight exists");
+                
+                let amount = rng.gen_range(range.min as f64..=range.max as f64);
+                self.rounding.apply(amount, range.min as f64, range.max as f64)
+            }
+            AmountModel::LogNormal { median, sigma, min, max } => {
+                let amount = (median.ln() + sigma * standard_normal(rng)).exp().clamp(*min, *max);
+                self.rounding.apply(amount, *min, *max)
+            }
+            AmountModel::Fixed { amounts } => {
+                if amounts.is_empty() {
+                    return 1000.0;
+                }
+                let amount = amounts[rng.gen_range(0..amounts.len())];
+                self.rounding.apply(amount, amount, amount)
+            }
+        }
+    }
+    
+    /// Lines describing the distribution, for menus and logs.
+    pub fn describe(&self) -> Vec<String> {
+        let mut lines = match &self.model {
+            AmountModel::Weighted { ranges } => {
+                let total: f64 = ranges.iter().map(|r| r.weight.max(0.0)).sum();
+                ranges
+                    .iter()
+                    .map(|r| {
+                        let share = if total > 0.0 { r.weight.max(0.0) / total * 100.0 } else { 0.0 };
+                        format!("{}-{} RUB: weight {} ({:.1}%)", r.min, r.max, r.weight, share)
+                    })
+                    .collect()
+            }
+            AmountModel::LogNormal { median, sigma, min, max } => {
+                vec![format!("Log-normal: median {} RUB, sigma {}, clamped to {}-{}", median, sigma, min, max)]
+            }
+            AmountModel::Fixed { amounts } => {
+                let amounts: Vec<String> = amounts.iter().map(|a| a.to_string()).collect();
+                vec![format!("Fixed: {} RUB", amounts.join(", "))]
+            }
+        };
+        
+        lines.push(format!("Rounded to {}", self.rounding));
+        lines
+    }
+}
+
+impl AmountRounding {
+    /// Rounds `amount`, staying within min..=max when a rounded value fits there.
+    pub fn apply(&self, amount: f64, min: f64, max: f64) -> f64 {
+        let step = match self {
+            AmountRounding::Kopecks => return (amount * 100.0).round() / 100.0,
+            AmountRounding::Whole => 1.0,
+            AmountRounding::Multiple(step) => (*step).max(1) as f64,
+        };
+        
+        let lowest = (min / step).ceil() * step;
+        let highest = (max / step).floor() * step;
+        if lowest > highest {
+            // No multiple inside the range; keep the amount in range instead
+            return (amount * 100.0).round() / 100.0;
+        }
+        
+        ((amount / step).round() * step).clamp(lowest, highest)
+    }
+}
+
+/// Box-Muller transform; avoids pulling in rand_distr for one distribution.
+fn standard_normal(rng: &mut impl Rng) -> f64 {
+    let u1: f64 = rng.gen_range(f64::EPSILON..1.0);
+    let u2: f64 = rng.gen_range(0.0..1.0);
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+/// Reads a distribution, or the `"min-max": weight` map older versions saved.
+pub fn deserialize_compat<'de, D>(deserializer: D) -> Result<AmountDistribution, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Stored {
+        Current(AmountDistribution),
+        Legacy(HashMap<String, f64>),
+    }
+    
+    match Stored::deserialize(deserializer)? {
+        Stored::Current(distribution) => Ok(distribution),
+        Stored::Legacy(map) => {
+            let probabilities: HashMap<AmountRange, f64> = map
+                .into_iter()
+                .filter_map(|(key, weight)| {
+                    let (min, max) = key.split_once('-')?;
+                    Some((AmountRange { min: min.parse().ok()?, max: max.parse().ok()? }, weight))
+                })
+                .collect();
+            Ok(AmountDistribution::from_legacy(&probabilities))
+        }
+    }
+}
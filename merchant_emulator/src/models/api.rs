@@ -20,6 +20,21 @@ pub struct BalanceResponse {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ErrorResponse {
     pub error: String,
+    #[serde(default)]
+    pub code: Option<String>,
+}
+
+/// Enum values the backend accepts, from `GET /api/merchant/enums`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnumCatalog {
+    pub status: Vec<String>,
+    #[serde(rename = "transactionType")]
+    pub transaction_type: Vec<String>,
+    #[serde(rename = "methodType")]
+    pub method_type: Vec<String>,
+    pub currency: Vec<String>,
+    #[serde(rename = "bankType")]
+    pub bank_type: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,118 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Simulated buyer behind each deposit: whether they pay, and when.
+/// Paying for a liquid order means the trader's device gets the bank
+/// notification; unpaid orders are cancelled by the merchant or left to expire.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct BuyerBehavior {
+    pub enabled: bool,
+    pub bands: Vec<ConversionBand>,
+    pub other_pay_percentage: f64, // Amounts outside every band
+    pub pay_delay_min_ms: u64,
+    pub pay_delay_max_ms: u64,
+    pub abandon_cancel_percentage: f64, // 0-100% of unpaid orders the merchant cancels; the rest expire
+    pub abandon_cancel_delay_ms: u64,
+}
+
+/// Chance that a buyer pays an order of min..=max RUB.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConversionBand {
+    pub min: u64,
+    pub max: u64,
+    pub pay_percentage: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BuyerDecision {
+    Pay { after: Duration },
+    Cancel { after: Duration },
+    Abandon,
+}
+
+/// Per-band counters, so success rates can be compared by amount.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConversionStats {
+    pub orders: u64,
+    pub paid: u64,
+    pub notified: u64, // Paid orders whose bank notification reached a device
+    pub cancelled: u64,
+    pub abandoned: u64,
+}
+
+impl Default for BuyerBehavior {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bands: vec![
+                ConversionBand { min: 1000, max: 3000, pay_percentage: 64.0 },
+                ConversionBand { min: 3000, max: 5000, pay_percentage: 69.0 },
+                ConversionBand { min: 5000, max: 10000, pay_percentage: 73.0 },
+                ConversionBand { min: 10000, max: 20000, pay_percentage: 82.0 },
+                ConversionBand { min: 20000, max: 50000, pay_percentage: 88.0 },
+                ConversionBand { min: 50000, max: 100000, pay_percentage: 92.0 },
+            ],
+            other_pay_percentage: 70.0,
+            pay_delay_min_ms: 30_000,
+            pay_delay_max_ms: 180_000,
+            abandon_cancel_percentage: 30.0,
+            abandon_cancel_delay_ms: 60_000,
+        }
+    }
+}
+
+impl BuyerBehavior {
+    fn band(&self, amount: f64) -> Option<&ConversionBand> {
+        self.bands
+            .iter()
+            .find(|band| (band.min as f64..=band.max as f64).contains(&amount))
+    }
+    
+    /// Label of the band an amount falls in, used as the statistics key.
+    pub fn band_label(&self, amount: f64) -> String {
+        match self.band(amount) {
+            Some(band) => format!("{}-{}", band.min, band.max),
+            None => "other".to_string(),
+        }
+    }
+    
+    pub fn pay_percentage(&self, amount: f64) -> f64 {
+        self.band(amount)
+            .map(|band| band.pay_percentage)
+            .unwrap_or(self.other_pay_percentage)
+    }
+    
+    pub fn decide(&self, amount: f64, rng: &mut impl Rng) -> BuyerDecision {
+        if rng.gen_range(0.0..100.0) < self.pay_percentage(amount) {
+            let delay = rng.gen_range(self.pay_delay_min_ms..=self.pay_delay_max_ms.max(self.pay_delay_min_ms));
+            return BuyerDecision::Pay { after: Duration::from_millis(delay) };
+        }
+        
+        if rng.gen_range(0.0..100.0) < self.abandon_cancel_percentage {
+            BuyerDecision::Cancel { after: Duration::from_millis(self.abandon_cancel_delay_ms) }
+        } else {
+            BuyerDecision::Abandon
+        }
+    }
+    
+    pub fn validate(&self) -> Result<(), String> {
+        for band in &self.bands {
+            if band.min > band.max {
+                return Err(format!("Invalid band {}-{}: min is above max", band.min, band.max));
+            }
+            if !(0.0..=100.0).contains(&band.pay_percentage) {
+                return Err(format!("Pay chance for {}-{} must be 0-100%", band.min, band.max));
+            }
+        }
+        if !(0.0..=100.0).contains(&self.other_pay_percentage) || !(0.0..=100.0).contains(&self.abandon_cancel_percentage) {
+            return Err("Percentages must be 0-100%".to_string());
+        }
+        if self.pay_delay_min_ms > self.pay_delay_max_ms {
+            return Err("Minimum pay delay is above the maximum".to_string());
+        }
+        
+        Ok(())
+    }
+}
\ No newline at end of file
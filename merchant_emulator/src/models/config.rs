@@ -1,3 +1,4 @@
+use crate::models::RetryConfig;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -9,6 +10,15 @@ pub struct Config {
     pub export_dir: PathBuf,
     pub log_level: String,
     pub device_emulator_enabled: bool,
+    #[serde(default)]
+    pub retry: RetryConfig,
+    /// How long enum values and merchant methods are cached
+    #[serde(default = "default_catalog_ttl_secs")]
+    pub catalog_ttl_secs: u64,
+}
+
+fn default_catalog_ttl_secs() -> u64 {
+    300
 }
 
 impl Default for Config {
@@ -41,6 +51,8 @@ impl Default for Config {
             export_dir,
             log_level: "info".to_string(),
             device_emulator_enabled: true,
+            retry: RetryConfig::default(),
+            catalog_ttl_secs: default_catalog_ttl_secs(),
         }
     }
 }
\ No newline at end of file
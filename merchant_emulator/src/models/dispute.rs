@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+
+/// Deal disputes live under `/api/merchant/deal-disputes`, payout disputes
+/// under `/api/merchant/payout-disputes`. Both share the same shape.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum DisputeKind {
+    Deal,
+    Payout,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum DisputeStatus {
+    #[serde(rename = "OPEN")]
+    Open,
+    #[serde(rename = "IN_PROGRESS")]
+    InProgress,
+    #[serde(rename = "RESOLVED_SUCCESS")]
+    ResolvedSuccess,
+    #[serde(rename = "RESOLVED_FAIL")]
+    ResolvedFail,
+    #[serde(rename = "CANCELLED")]
+    Cancelled,
+}
+
+impl DisputeStatus {
+    /// Open disputes still accept messages
+    pub fn is_open(&self) -> bool {
+        matches!(self, DisputeStatus::Open | DisputeStatus::InProgress)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum DisputeSenderType {
+    #[serde(rename = "MERCHANT")]
+    Merchant,
+    #[serde(rename = "TRADER")]
+    Trader,
+    #[serde(rename = "ADMIN")]
+    Admin,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dispute {
+    pub id: String,
+    #[serde(rename = "dealId", default)]
+    pub deal_id: Option<String>,
+    #[serde(rename = "payoutId", default)]
+    pub payout_id: Option<String>,
+    #[serde(rename = "merchantId")]
+    pub merchant_id: String,
+    #[serde(rename = "traderId")]
+    pub trader_id: String,
+    pub status: DisputeStatus,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    #[serde(rename = "updatedAt", default)]
+    pub updated_at: Option<String>,
+    #[serde(rename = "resolvedAt", default)]
+    pub resolved_at: Option<String>,
+    #[serde(default)]
+    pub resolution: Option<String>,
+    #[serde(default)]
+    pub messages: Vec<DisputeMessage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisputeMessage {
+    pub id: String,
+    #[serde(rename = "disputeId")]
+    pub dispute_id: String,
+    #[serde(rename = "senderId")]
+    pub sender_id: String,
+    #[serde(rename = "senderType")]
+    pub sender_type: DisputeSenderType,
+    pub message: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    #[serde(default)]
+    pub attachments: Vec<DisputeAttachment>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisputeAttachment {
+    pub id: String,
+    pub filename: String,
+    pub url: String,
+    pub size: u64,
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateDisputeResponse {
+    pub success: bool,
+    pub dispute: Dispute,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisputeResponse {
+    pub success: bool,
+    pub data: Dispute,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisputeListResponse {
+    pub success: bool,
+    pub data: Vec<Dispute>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisputeMessageResponse {
+    pub success: bool,
+    pub message: DisputeMessage,
+}
@@ -1,14 +1,16 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
-use std::collections::HashMap;
 use rand;
+use crate::models::{amount, AmountDistribution, BuyerBehavior, ProtocolKind, TrafficPacing, TrafficProfile, WellbitSettings};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Merchant {
     pub id: Uuid,
     pub name: String,
     pub api_key: String,
+    #[serde(default)]
+    pub private_key: Option<String>, // Used to verify x-api-token signatures
     pub callback_url: Option<String>,
     pub created_at: DateTime<Utc>,
     pub traffic_config: TrafficConfig,
@@ -17,17 +19,132 @@ pub struct Merchant {
     pub rate: Option<f64>, // For USDT only
     pub is_active: bool,
     pub balance_usdt: f64,
+    #[serde(default)]
+    pub callback_behavior: CallbackBehavior,
+    #[serde(default)]
+    pub protocol: ProtocolKind,
+    #[serde(default)]
+    pub wellbit: WellbitSettings,
 }
 
+/// How the callback server answers callbacks addressed to this merchant.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CallbackBehavior {
+    pub status_code: u16, // Status returned for normal responses
+    pub latency_ms: u64, // Delay before every response
+    pub failure_rate: f64, // 0-100% of callbacks answered with failure_status_code
+    pub failure_status_code: u16,
+    pub timeout_rate: f64, // 0-100% of callbacks held open for timeout_ms
+    pub timeout_ms: u64,
+    pub malformed_rate: f64, // 0-100% of callbacks answered with an invalid JSON body
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum CallbackOutcome {
+    Respond(u16),
+    Fail(u16),
+    Timeout,
+    Malformed,
+}
+
+impl Default for CallbackBehavior {
+    fn default() -> Self {
+        Self {
+            status_code: 200,
+            latency_ms: 0,
+            failure_rate: 0.0,
+            failure_status_code: 500,
+            timeout_rate: 0.0,
+            timeout_ms: 30_000,
+            malformed_rate: 0.0,
+        }
+    }
+}
+
+impl CallbackBehavior {
+    /// Picks how to answer a single callback. Failures take precedence over
+    /// timeouts, which take precedence over malformed bodies.
+    pub fn outcome(&self, rng: &mut impl rand::Rng) -> CallbackOutcome {
+        let roll = rng.gen_range(0.0..100.0);
+        
+        if roll < self.failure_rate {
+            CallbackOutcome::Fail(self.failure_status_code)
+        } else if roll < self.failure_rate + self.timeout_rate {
+            CallbackOutcome::Timeout
+        } else if roll < self.failure_rate + self.timeout_rate + self.malformed_rate {
+            CallbackOutcome::Malformed
+        } else {
+            CallbackOutcome::Respond(self.status_code)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MerchantCredentials {
+    pub merchant_id: Uuid,
+    pub api_key: String,
+    pub private_key: Option<String>,
+    pub protocol: ProtocolKind,
+    pub wellbit_key: String, // Wellbit x-api-key, empty for native merchants
+}
+
+// Missing fields take their defaults, so scenario files only list what they change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct TrafficConfig {
     pub enabled: bool,
     pub interval_ms: u64, // Random interval between transactions
     pub interval_variance: u64, // +/- variance for randomness
     pub max_transactions: Option<u64>, // Limit on total transactions
     pub created_count: u64,
-    #[serde(with = "amount_range_map")]
-    pub amount_probabilities: HashMap<AmountRange, f64>,
+    #[serde(alias = "amount_probabilities", deserialize_with = "amount::deserialize_compat")]
+    pub amount_distribution: AmountDistribution,
+    #[serde(default)]
+    pub cancel_percentage: f64, // 0-100% of created orders cancelled by the merchant
+    #[serde(default = "default_cancel_delay_ms")]
+    pub cancel_delay_ms: u64, // Delay between creation and cancellation
+    #[serde(default)]
+    pub mode: TrafficMode,
+    #[serde(default)]
+    pub receipt_percentage: f64, // 0-100% of created orders that get a synthetic receipt
+    #[serde(default)]
+    pub dispute_percentage: f64, // 0-100% of completed deals the merchant disputes
+    #[serde(default = "default_dispute_messages")]
+    pub dispute_messages: Vec<String>, // Opening message followed by follow-ups
+    #[serde(
+        default = "AmountDistribution::default_payouts",
+        alias = "payout_amount_probabilities",
+        deserialize_with = "amount::deserialize_compat"
+    )]
+    pub payout_amount_distribution: AmountDistribution,
+    #[serde(default)]
+    pub buyer: BuyerBehavior, // Whether buyers pay deposits, by amount band
+    #[serde(default)]
+    pub seed: Option<u64>, // Replays the same run when set; a random seed is picked otherwise
+    #[serde(default)]
+    pub pacing: TrafficPacing,
+    #[serde(default)]
+    pub profile: TrafficProfile, // Time-of-day shape applied on top of the pacing
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum TrafficMode {
+    #[default]
+    Deposits, // IN transactions
+    Payouts,  // OUT payouts via /api/merchant/payouts
+}
+
+fn default_cancel_delay_ms() -> u64 {
+    30_000
+}
+
+fn default_dispute_messages() -> Vec<String> {
+    vec![
+        "Customer reports the payment was not credited. Receipt attached.".to_string(),
+        "Please check the attached receipt and confirm the transfer.".to_string(),
+        "Customer is still waiting. Please resolve this dispute.".to_string(),
+    ]
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -44,33 +161,96 @@ pub enum PaymentType {
 
 impl Default for TrafficConfig {
     fn default() -> Self {
-        let mut amount_probabilities = HashMap::new();
-        
-        // Default probabilities from requirements
-        amount_probabilities.insert(AmountRange { min: 1000, max: 3000 }, 64.0);
-        amount_probabilities.insert(AmountRange { min: 3000, max: 5000 }, 69.0);
-        amount_probabilities.insert(AmountRange { min: 5000, max: 10000 }, 73.0);
-        amount_probabilities.insert(AmountRange { min: 10000, max: 20000 }, 82.0);
-        amount_probabilities.insert(AmountRange { min: 20000, max: 50000 }, 88.0);
-        amount_probabilities.insert(AmountRange { min: 50000, max: 100000 }, 92.0);
-        
         Self {
             enabled: false,
             interval_ms: 5000,
             interval_variance: 2000,
             max_transactions: None,
             created_count: 0,
-            amount_probabilities,
+            amount_distribution: AmountDistribution::default_deposits(),
+            cancel_percentage: 0.0,
+            cancel_delay_ms: default_cancel_delay_ms(),
+            mode: TrafficMode::Deposits,
+            receipt_percentage: 0.0,
+            dispute_percentage: 0.0,
+            dispute_messages: default_dispute_messages(),
+            payout_amount_distribution: AmountDistribution::default_payouts(),
+            buyer: BuyerBehavior::default(),
+            seed: None,
+            pacing: TrafficPacing::Sequential,
+            profile: TrafficProfile::default(),
+        }
+    }
+}
+
+impl TrafficConfig {
+    /// Checks every part of the configuration, as the traffic menu does one part at a time.
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, percentage) in [
+            ("Cancel", self.cancel_percentage),
+            ("Receipt", self.receipt_percentage),
+            ("Dispute", self.dispute_percentage),
+        ] {
+            if !(0.0..=100.0).contains(&percentage) {
+                return Err(format!("{} percentage must be 0-100%", name));
+            }
+        }
+        self.amount_distribution.validate().map_err(|e| format!("Amounts: {}", e))?;
+        self.payout_amount_distribution.validate().map_err(|e| format!("Payout amounts: {}", e))?;
+        self.buyer.validate().map_err(|e| format!("Buyer behaviour: {}", e))?;
+        if let TrafficPacing::Rate(rate) = &self.pacing {
+            rate.validate().map_err(|e| format!("Pacing: {}", e))?;
+        }
+        if self.profile.enabled {
+            self.profile.validate().map_err(|e| format!("Profile: {}", e))?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Whether a run with this configuration stops by itself.
+    pub fn is_bounded(&self) -> bool {
+        self.max_transactions.is_some() || matches!(&self.pacing, TrafficPacing::Rate(rate) if !rate.stages.is_empty())
+    }
+}
+
+/// A merchant as shown outside the emulator, without its keys.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MerchantSummary {
+    pub id: Uuid,
+    pub name: String,
+    pub protocol: ProtocolKind,
+    pub payment_type: PaymentType,
+    pub liquidity_percentage: f64,
+    pub callback_url: Option<String>,
+    pub is_active: bool,
+    pub balance_usdt: f64,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&Merchant> for MerchantSummary {
+    fn from(merchant: &Merchant) -> Self {
+        Self {
+            id: merchant.id,
+            name: merchant.name.clone(),
+            protocol: merchant.protocol,
+            payment_type: merchant.payment_type,
+            liquidity_percentage: merchant.liquidity_percentage,
+            callback_url: merchant.callback_url.clone(),
+            is_active: merchant.is_active,
+            balance_usdt: merchant.balance_usdt,
+            created_at: merchant.created_at,
         }
     }
 }
 
 impl Merchant {
-    pub fn new(name: String, api_key: String) -> Self {
+    pub fn new(name: String, api_key: String, private_key: Option<String>) -> Self {
         Self {
             id: Uuid::new_v4(),
             name,
             api_key,
+            private_key,
             callback_url: None,
             created_at: Utc::now(),
             traffic_config: TrafficConfig::default(),
@@ -79,48 +259,40 @@ impl Merchant {
             rate: None,
             is_active: true,
             balance_usdt: 0.0,
+            callback_behavior: CallbackBehavior::default(),
+            protocol: ProtocolKind::Native,
+            wellbit: WellbitSettings::default(),
+        }
+    }
+    
+    pub fn credentials(&self) -> MerchantCredentials {
+        MerchantCredentials {
+            merchant_id: self.id,
+            api_key: self.api_key.clone(),
+            private_key: self.private_key.clone(),
+            protocol: self.protocol,
+            wellbit_key: self.wellbit.api_key_public.clone(),
         }
     }
     
-    pub fn is_liquid(&self) -> bool {
-        let random: f64 = rand::random::<f64>() * 100.0;
+    pub fn is_liquid(&self, rng: &mut impl rand::Rng) -> bool {
+        let random: f64 = rng.gen::<f64>() * 100.0;
         random <= self.liquidity_percentage
     }
-}
-
-// Custom serialization for HashMap<AmountRange, f64>
-mod amount_range_map {
-    use super::*;
-    use serde::{Deserialize, Deserializer, Serialize, Serializer};
-    use std::collections::HashMap;
     
-    pub fn serialize<S>(map: &HashMap<AmountRange, f64>, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        let string_map: HashMap<String, f64> = map
-            .iter()
-            .map(|(k, v)| (format!("{}-{}", k.min, k.max), *v))
-            .collect();
-        string_map.serialize(serializer)
+    pub fn should_cancel(&self, rng: &mut impl rand::Rng) -> bool {
+        let percentage = self.traffic_config.cancel_percentage;
+        percentage > 0.0 && rng.gen_range(0.0..100.0) < percentage
     }
     
-    pub fn deserialize<'de, D>(deserializer: D) -> Result<HashMap<AmountRange, f64>, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        let string_map: HashMap<String, f64> = HashMap::deserialize(deserializer)?;
-        let mut map = HashMap::new();
-        
-        for (k, v) in string_map {
-            let parts: Vec<&str> = k.split('-').collect();
-            if parts.len() == 2 {
-                if let (Ok(min), Ok(max)) = (parts[0].parse::<u64>(), parts[1].parse::<u64>()) {
-                    map.insert(AmountRange { min, max }, v);
-                }
-            }
-        }
-        
-        Ok(map)
+    // Receipts and disputes only exist in the native API
+    pub fn should_open_dispute(&self, rng: &mut impl rand::Rng) -> bool {
+        let percentage = self.traffic_config.dispute_percentage;
+        self.protocol == ProtocolKind::Native && percentage > 0.0 && rng.gen_range(0.0..100.0) < percentage
+    }
+    
+    pub fn should_attach_receipt(&self, rng: &mut impl rand::Rng) -> bool {
+        let percentage = self.traffic_config.receipt_percentage;
+        self.protocol == ProtocolKind::Native && percentage > 0.0 && rng.gen_range(0.0..100.0) < percentage
     }
 }
\ No newline at end of file
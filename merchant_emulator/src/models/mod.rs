@@ -1,11 +1,33 @@
 pub mod merchant;
+pub mod amount;
+pub mod buyer;
+pub mod pacing;
+pub mod profile;
+pub mod scenario;
 pub mod transaction;
+pub mod payout;
+pub mod reconciliation;
+pub mod receipt;
+pub mod dispute;
+pub mod retry;
+pub mod wellbit;
 pub mod api;
 pub mod device;
 pub mod config;
 
 pub use merchant::*;
+pub use amount::*;
+pub use buyer::*;
+pub use pacing::*;
+pub use profile::*;
+pub use scenario::*;
 pub use transaction::*;
+pub use payout::*;
+pub use reconciliation::*;
+pub use receipt::*;
+pub use dispute::*;
+pub use retry::*;
+pub use wellbit::*;
 pub use api::*;
 pub use device::*;
 pub use config::*;
\ No newline at end of file
@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How a traffic run spaces its requests.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TrafficPacing {
+    /// One request at a time, `interval_ms` (± variance) apart; throughput is
+    /// bounded by API latency.
+    #[default]
+    Sequential,
+    /// Open loop: requests go out on a fixed schedule whether or not earlier
+    /// ones have answered, up to `max_concurrency` at once.
+    Rate(RateConfig),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RateConfig {
+    pub target_tps: f64, // Steady rate when there are no stages
+    pub max_concurrency: usize,
+    #[serde(default)]
+    pub stages: Vec<RateStage>, // Run ends after the last stage
+}
+
+/// Moves the rate linearly from the previous stage's rate (0 for the first)
+/// to `target_tps` over `duration_secs`; equal rates make a plateau.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RateStage {
+    pub duration_secs: u64,
+    pub target_tps: f64,
+}
+
+impl Default for RateConfig {
+    fn default() -> Self {
+        Self {
+            target_tps: 10.0,
+            max_concurrency: 50,
+            stages: Vec::new(),
+        }
+    }
+}
+
+impl RateConfig {
+    /// Target rate `elapsed` into the run, or None once the stages are over.
+    pub fn rate_at(&self, elapsed: Duration) -> Option<f64> {
+        if self.stages.is_empty() {
+            return Some(self.target_tps);
+        }
+        
+        let mut elapsed = elapsed.as_secs_f64();
+        let mut from = 0.0;
+        for stage in &self.stages {
+            let duration = stage.duration_secs as f64;
+            if elapsed < duration {
+                return Some(from + (stage.target_tps - from) * elapsed / duration);
+            }
+            elapsed -= duration;
+            from = stage.target_tps;
+        }
+        None
+    }
+    
+    /// When the request after one sent at `from` is due, with the rate scaled
+    /// by `multiplier`. The rate is integrated in short steps, so a ramp from
+    /// zero sends its first request once a whole one has accrued rather than
+    /// waiting out the interval of its starting rate. None if the stages end
+    /// first.
+    pub fn next_due(&self, from: Duration, multiplier: f64) -> Option<Duration> {
+        const STEP: Duration = Duration::from_millis(10);
+        
+        let mut at = from;
+        let mut accrued = 0.0;
+        loop {
+            let rate = self.rate_at(at)? * multiplier;
+            if rate > 0.0 {
+                let step = rate * STEP.as_secs_f64();
+                if accrued + step >= 1.0 {
+                    return Some(at + Duration::from_secs_f64((1.0 - accrued) / rate));
+                }
+                accrued += step;
+            }
+            at += STEP;
+        }
+    }
+    
+    pub fn total_duration(&self) -> Option<Duration> {
+        if self.stages.is_empty() {
+            return None;
+        }
+        Some(Duration::from_secs(self.stages.iter().map(|s| s.duration_secs).sum()))
+    }
+    
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_concurrency == 0 {
+            return Err("Concurrency limit must be at least 1".to_string());
+        }
+        if self.stages.is_empty() && !(self.target_tps.is_finite() && self.target_tps > 0.0) {
+            return Err("Target rate must be above zero".to_string());
+        }
+        for stage in &self.stages {
+            if stage.duration_secs == 0 {
+                return Err("Stage durations must be at least one second".to_string());
+            }
+            if !stage.target_tps.is_finite() || stage.target_tps < 0.0 {
+                return Err(format!("Invalid stage rate {}", stage.target_tps));
+            }
+        }
+        if !self.stages.is_empty() && self.stages.iter().all(|s| s.target_tps == 0.0) {
+            return Err("At least one stage needs a rate above zero".to_string());
+        }
+        
+        Ok(())
+    }
+    
+    /// Lines describing the schedule, for menus and logs.
+    pub fn describe(&self) -> Vec<String> {
+        let mut lines = vec![format!("Up to {} requests in flight", self.max_concurrency)];
+        if self.stages.is_empty() {
+            lines.insert(0, format!("{} TPS until stopped", self.target_tps));
+        } else {
+            let mut from = 0.0;
+            for stage in &self.stages {
+                lines.push(format!("{}s: {} -> {} TPS", stage.duration_secs, from, stage.target_tps));
+                from = stage.target_tps;
+            }
+        }
+        lines
+    }
+}
\ No newline at end of file
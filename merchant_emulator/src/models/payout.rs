@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use crate::models::PaymentMethod;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Payout {
+    pub id: String,
+    #[serde(rename = "numericId")]
+    pub numeric_id: u64,
+    pub amount: f64,
+    #[serde(rename = "amountUsdt", default)]
+    pub amount_usdt: f64,
+    #[serde(default)]
+    pub total: f64,
+    #[serde(rename = "totalUsdt", default)]
+    pub total_usdt: f64,
+    pub rate: f64,
+    pub wallet: String,
+    pub bank: String,
+    #[serde(rename = "isCard")]
+    pub is_card: bool,
+    pub status: PayoutStatus,
+    pub method: Option<PaymentMethod>,
+    #[serde(rename = "expireAt")]
+    pub expire_at: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    #[serde(rename = "acceptedAt", default)]
+    pub accepted_at: Option<String>,
+    #[serde(rename = "confirmedAt", default)]
+    pub confirmed_at: Option<String>,
+    #[serde(rename = "cancelledAt", default)]
+    pub cancelled_at: Option<String>,
+    #[serde(rename = "cancelReason", default)]
+    pub cancel_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum PayoutStatus {
+    #[serde(rename = "CREATED")]
+    Created,
+    #[serde(rename = "AVAILABLE")]
+    Available,
+    #[serde(rename = "ACTIVE")]
+    Active,
+    #[serde(rename = "PROCESSING")]
+    Processing,
+    #[serde(rename = "CHECKING")]
+    Checking,
+    #[serde(rename = "SUCCESS")]
+    Success,
+    #[serde(rename = "COMPLETED")]
+    Completed,
+    #[serde(rename = "FAILED")]
+    Failed,
+    #[serde(rename = "EXPIRED")]
+    Expired,
+    #[serde(rename = "CANCELLED")]
+    Cancelled,
+    #[serde(rename = "DISPUTE")]
+    Dispute,
+    #[serde(rename = "DISPUTED")]
+    Disputed,
+}
+
+impl PayoutStatus {
+    /// Statuses after which the backend no longer changes the payout
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            PayoutStatus::Success
+                | PayoutStatus::Completed
+                | PayoutStatus::Failed
+                | PayoutStatus::Expired
+                | PayoutStatus::Cancelled
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayoutRequest {
+    #[serde(rename = "methodId")]
+    pub method_id: String,
+    pub amount: f64,
+    pub wallet: String,
+    pub bank: String,
+    #[serde(rename = "isCard")]
+    pub is_card: bool,
+    #[serde(rename = "merchantRate", skip_serializing_if = "Option::is_none")]
+    pub merchant_rate: Option<f64>,
+    #[serde(rename = "externalReference", skip_serializing_if = "Option::is_none")]
+    pub external_reference: Option<String>,
+    #[serde(rename = "webhookUrl", skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<PayoutMetadata>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayoutMetadata {
+    #[serde(rename = "isMock")]
+    pub is_mock: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayoutResponse {
+    pub success: bool,
+    pub payout: Payout,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayoutHistory {
+    pub merchant_id: Uuid,
+    pub payout: Option<Payout>,
+    pub request_time: DateTime<Utc>,
+    pub response_time: DateTime<Utc>,
+    pub request_body: PayoutRequest,
+    pub last_checked_at: Option<DateTime<Utc>>,
+    pub error: Option<String>,
+}
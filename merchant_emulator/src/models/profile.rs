@@ -0,0 +1,185 @@
+use chrono::{Datelike, Local, NaiveDateTime, NaiveTime, Timelike, Weekday};
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// Shapes traffic over a simulated day. The multiplier scales the base rate:
+/// intervals are divided by it, rate targets multiplied by it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct TrafficProfile {
+    pub enabled: bool,
+    pub weekday_hours: Vec<f64>, // 24 multipliers, one per hour; interpolated in between
+    pub weekend_hours: Vec<f64>,
+    #[serde(default)]
+    pub bursts: Vec<BurstWindow>,
+    pub clock_speed: f64, // Simulated seconds per real second; 1440 runs a day in a minute
+    #[serde(default)]
+    pub start_at: Option<NaiveDateTime>, // Simulated start; local time when unset
+}
+
+/// Extra load between `start` and `end` (wrapping past midnight when end is earlier).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BurstWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub multiplier: f64,
+    #[serde(default)]
+    pub days: DayFilter,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DayFilter {
+    #[default]
+    All,
+    Weekdays,
+    Weekends,
+}
+
+impl DayFilter {
+    fn matches(&self, weekday: Weekday) -> bool {
+        match self {
+            DayFilter::All => true,
+            DayFilter::Weekdays => !is_weekend(weekday),
+            DayFilter::Weekends => is_weekend(weekday),
+        }
+    }
+}
+
+impl std::fmt::Display for DayFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DayFilter::All => write!(f, "every day"),
+            DayFilter::Weekdays => write!(f, "weekdays"),
+            DayFilter::Weekends => write!(f, "weekends"),
+        }
+    }
+}
+
+fn is_weekend(weekday: Weekday) -> bool {
+    matches!(weekday, Weekday::Sat | Weekday::Sun)
+}
+
+impl Default for TrafficProfile {
+    fn default() -> Self {
+        // Quiet nights, a morning rise and an evening peak; weekends start later
+        Self {
+            enabled: false,
+            weekday_hours: vec![
+                0.3, 0.2, 0.15, 0.1, 0.1, 0.15, 0.3, 0.6, 0.9, 1.0, 1.1, 1.2,
+                1.2, 1.1, 1.1, 1.1, 1.2, 1.3, 1.5, 1.7, 1.8, 1.6, 1.1, 0.6,
+            ],
+            weekend_hours: vec![
+                0.5, 0.4, 0.3, 0.2, 0.15, 0.1, 0.15, 0.3, 0.5, 0.8, 1.0, 1.2,
+                1.3, 1.3, 1.3, 1.3, 1.4, 1.5, 1.6, 1.8, 1.9, 1.8, 1.4, 0.9,
+            ],
+            bursts: Vec::new(),
+            clock_speed: 1.0,
+            start_at: None,
+        }
+    }
+}
+
+impl TrafficProfile {
+    /// Load multiplier at a simulated moment.
+    pub fn multiplier_at(&self, at: NaiveDateTime) -> f64 {
+        let hours = if is_weekend(at.weekday()) { &self.weekend_hours } else { &self.weekday_hours };
+        if hours.len() != 24 {
+            return 1.0;
+        }
+        
+        let hour = at.hour() as usize;
+        let fraction = (at.minute() * 60 + at.second()) as f64 / 3600.0;
+        let next = if hour == 23 {
+            // The curve of the following day starts at midnight
+            let tomorrow = at.date().succ_opt().map(|d| d.weekday()).unwrap_or(at.weekday());
+            if is_weekend(tomorrow) { self.weekend_hours[0] } else { self.weekday_hours[0] }
+        } else {
+            hours[hour + 1]
+        };
+        let base = hours[hour] + (next - hours[hour]) * fraction;
+        
+        self.bursts
+            .iter()
+            .filter(|burst| burst.is_active(at))
+            .fold(base, |multiplier, burst| multiplier * burst.multiplier)
+    }
+    
+    pub fn clock(&self) -> SimClock {
+        SimClock {
+            real_start: Instant::now(),
+            sim_start: self.start_at.unwrap_or_else(|| Local::now().naive_local()),
+            speed: self.clock_speed,
+        }
+    }
+    
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, hours) in [("weekday", &self.weekday_hours), ("weekend", &self.weekend_hours)] {
+            if hours.len() != 24 {
+                return Err(format!("The {} curve needs 24 hourly values, got {}", name, hours.len()));
+            }
+            if let Some(value) = hours.iter().find(|v| !v.is_finite() || **v < 0.0) {
+                return Err(format!("Invalid {} multiplier {}", name, value));
+            }
+        }
+        if self.weekday_hours.iter().chain(&self.weekend_hours).all(|v| *v == 0.0) {
+            return Err("At least one hour needs a multiplier above zero".to_string());
+        }
+        if let Some(burst) = self.bursts.iter().find(|b| !b.multiplier.is_finite() || b.multiplier < 0.0) {
+            return Err(format!("Invalid burst multiplier {}", burst.multiplier));
+        }
+        if !self.clock_speed.is_finite() || self.clock_speed <= 0.0 {
+            return Err("Clock speed must be above zero".to_string());
+        }
+        
+        Ok(())
+    }
+    
+    /// Lines describing the profile, for menus and logs.
+    pub fn describe(&self) -> Vec<String> {
+        let curve = |hours: &[f64]| hours.iter().map(|h| h.to_string()).collect::<Vec<_>>().join(" ");
+        let mut lines = vec![
+            format!("Weekdays: {}", curve(&self.weekday_hours)),
+            format!("Weekends: {}", curve(&self.weekend_hours)),
+        ];
+        for burst in &self.bursts {
+            lines.push(format!("Burst {}-{} x{} ({})",
+                burst.start.format("%H:%M"), burst.end.format("%H:%M"), burst.multiplier, burst.days));
+        }
+        lines.push(match self.start_at {
+            Some(start) => format!("Clock: x{} from {}", self.clock_speed, start.format("%a %Y-%m-%d %H:%M")),
+            None => format!("Clock: x{} from the current time", self.clock_speed),
+        });
+        lines
+    }
+}
+
+impl BurstWindow {
+    pub fn is_active(&self, at: NaiveDateTime) -> bool {
+        let time = at.time();
+        if self.start <= self.end {
+            self.days.matches(at.weekday()) && self.start <= time && time < self.end
+        } else if time >= self.start {
+            self.days.matches(at.weekday())
+        } else {
+            // After midnight, the window belongs to the day it started on
+            let started = at.date().pred_opt().map(|d| d.weekday()).unwrap_or(at.weekday());
+            time < self.end && self.days.matches(started)
+        }
+    }
+}
+
+/// Simulated time of a run, advancing `speed` times faster than real time.
+#[derive(Debug, Clone)]
+pub struct SimClock {
+    real_start: Instant,
+    sim_start: NaiveDateTime,
+    speed: f64,
+}
+
+impl SimClock {
+    pub fn now(&self) -> NaiveDateTime {
+        let elapsed = self.real_start.elapsed().as_secs_f64() * self.speed;
+        self.sim_start + chrono::Duration::milliseconds((elapsed * 1000.0) as i64)
+    }
+}
\ No newline at end of file
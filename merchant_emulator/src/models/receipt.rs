@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+use crate::models::TransactionStatus;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Receipt {
+    pub id: String,
+    #[serde(rename = "fileName")]
+    pub file_name: String,
+    #[serde(rename = "isChecked")]
+    pub is_checked: bool,
+    #[serde(rename = "isFake")]
+    pub is_fake: bool,
+    #[serde(rename = "isAuto")]
+    pub is_auto: bool,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    #[serde(rename = "updatedAt", default)]
+    pub updated_at: Option<String>,
+}
+
+/// The backend takes receipts as base64 (optionally a data URI) inside a JSON body.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceiptUploadRequest {
+    #[serde(rename = "fileData")]
+    pub file_data: String,
+    #[serde(rename = "fileName")]
+    pub file_name: String,
+    #[serde(rename = "updateStatus", skip_serializing_if = "Option::is_none")]
+    pub update_status: Option<TransactionStatus>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ReceiptFormat {
+    Png,
+    Pdf,
+}
+
+impl ReceiptFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReceiptFormat::Png => "png",
+            ReceiptFormat::Pdf => "pdf",
+        }
+    }
+    
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ReceiptFormat::Png => "image/png",
+            ReceiptFormat::Pdf => "application/pdf",
+        }
+    }
+}
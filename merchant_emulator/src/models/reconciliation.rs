@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use crate::models::TransactionStatus;
+
+/// Result of joining a merchant's transactions with the callbacks received for them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconciliationReport {
+    pub merchant_id: Uuid,
+    pub generated_at: DateTime<Utc>,
+    pub total_transactions: usize, // Orders the backend accepted
+    pub total_callbacks: usize,
+    pub missing_callbacks: Vec<MissingCallback>, // Final or overdue orders that never got one
+    #[serde(default)]
+    pub pending_callbacks: Vec<MissingCallback>, // Orders still in flight without one
+    pub duplicate_callbacks: Vec<DuplicateCallback>,
+    pub unknown_order_callbacks: Vec<UnknownOrderCallback>,
+    #[serde(default)]
+    pub unattributed_callbacks: Vec<UnknownOrderCallback>, // Posted to /callback for no known order; may be any merchant's
+    pub out_of_order: Vec<OutOfOrderCallback>,
+    pub delay: Option<CallbackDelay>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissingCallback {
+    pub order_id: String,
+    pub status: TransactionStatus, // Last status known from polling or cancellation
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateCallback {
+    pub order_id: String,
+    pub status: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnknownOrderCallback {
+    pub callback_id: Uuid,
+    pub id: String, // Order or transaction id sent by the backend
+    pub status: String,
+    pub received_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutOfOrderCallback {
+    pub order_id: String,
+    pub previous_status: String,
+    pub status: String,
+    pub received_at: DateTime<Utc>,
+}
+
+/// Delay between the create response and each callback, in milliseconds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallbackDelay {
+    pub samples: usize,
+    pub min_ms: i64,
+    pub p50_ms: i64,
+    pub p90_ms: i64,
+    pub p95_ms: i64,
+    pub p99_ms: i64,
+    pub max_ms: i64,
+}
@@ -0,0 +1,177 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Merchant API calls, used to pick a retry policy and to label retries in statistics.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiOperation {
+    Connect,
+    Balance,
+    Enums,
+    Methods,
+    CreateTransaction,
+    CancelTransaction,
+    TransactionStatus,
+    GetTransaction,
+    ListTransactions,
+    CreatePayout,
+    GetPayout,
+    UploadReceipt,
+    ListReceipts,
+    OpenDispute,
+    ListDisputes,
+    GetDispute,
+    SendDisputeMessage,
+}
+
+impl ApiOperation {
+    /// Reads can be repeated freely. Everything else changes state on the
+    /// backend, so a second attempt after an unclear failure may duplicate it.
+    pub fn is_idempotent(&self) -> bool {
+        matches!(
+            self,
+            ApiOperation::Connect
+                | ApiOperation::Balance
+                | ApiOperation::Enums
+                | ApiOperation::Methods
+                | ApiOperation::TransactionStatus
+                | ApiOperation::GetTransaction
+                | ApiOperation::ListTransactions
+                | ApiOperation::GetPayout
+                | ApiOperation::ListReceipts
+                | ApiOperation::ListDisputes
+                | ApiOperation::GetDispute
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+    /// Random spread applied to each delay, as a fraction of it (0.2 = ±20%)
+    pub jitter: f64,
+    /// Retry failures where the request may already have been processed:
+    /// timeouts, dropped connections, 500/502/504
+    pub retry_ambiguous: bool,
+    /// Longest `Retry-After` that is honoured; longer waits give up instead
+    pub max_retry_after_ms: u64,
+}
+
+impl RetryPolicy {
+    pub fn idempotent() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay_ms: 200,
+            max_delay_ms: 5000,
+            jitter: 0.2,
+            retry_ambiguous: true,
+            max_retry_after_ms: 30000,
+        }
+    }
+    
+    /// Only retries failures the backend is known not to have processed
+    pub fn non_idempotent() -> Self {
+        Self {
+            max_retries: 2,
+            base_delay_ms: 500,
+            max_delay_ms: 5000,
+            jitter: 0.2,
+            retry_ambiguous: false,
+            max_retry_after_ms: 30000,
+        }
+    }
+    
+    /// Exponential backoff for the given retry (0-based), capped and jittered.
+    pub fn backoff(&self, retry: u32, rng: &mut impl Rng) -> Duration {
+        let exp = self.base_delay_ms.saturating_mul(1u64 << retry.min(32));
+        let capped = exp.min(self.max_delay_ms) as f64;
+        
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let factor = if jitter > 0.0 { rng.gen_range(1.0 - jitter..=1.0 + jitter) } else { 1.0 };
+        
+        Duration::from_millis((capped * factor) as u64)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryConfig {
+    pub idempotent: RetryPolicy,
+    pub non_idempotent: RetryPolicy,
+    /// Per-operation policies that replace the defaults above
+    #[serde(default)]
+    pub overrides: HashMap<ApiOperation, RetryPolicy>,
+}
+
+impl RetryConfig {
+    pub fn policy(&self, operation: ApiOperation) -> &RetryPolicy {
+        if let Some(policy) = self.overrides.get(&operation) {
+            return policy;
+        }
+        
+        if operation.is_idempotent() {
+            &self.idempotent
+        } else {
+            &self.non_idempotent
+        }
+    }
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            idempotent: RetryPolicy::idempotent(),
+            non_idempotent: RetryPolicy::non_idempotent(),
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    
+    fn policy(jitter: f64) -> RetryPolicy {
+        RetryPolicy { base_delay_ms: 100, max_delay_ms: 1000, jitter, ..RetryPolicy::idempotent() }
+    }
+    
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let policy = policy(0.0);
+        let mut rng = StdRng::seed_from_u64(1);
+        
+        let delays: Vec<u64> = (0..6).map(|retry| policy.backoff(retry, &mut rng).as_millis() as u64).collect();
+        assert_eq!(delays, [100, 200, 400, 800, 1000, 1000]);
+        // Shifts past 63 bits saturate rather than overflow
+        assert_eq!(policy.backoff(u32::MAX, &mut rng), Duration::from_millis(1000));
+    }
+    
+    #[test]
+    fn jitter_stays_within_its_spread() {
+        let policy = policy(0.2);
+        let mut rng = StdRng::seed_from_u64(2);
+        
+        for retry in 0..40 {
+            let capped = (100u64 << retry.min(32)).min(1000);
+            for _ in 0..50 {
+                let delay = policy.backoff(retry, &mut rng).as_millis() as u64;
+                assert!((capped * 8 / 10..=capped * 12 / 10).contains(&delay), "retry {}: {}ms", retry, delay);
+            }
+        }
+    }
+    
+    #[test]
+    fn jitter_is_clamped_to_the_whole_delay() {
+        let policy = policy(5.0);
+        let mut rng = StdRng::seed_from_u64(3);
+        
+        for _ in 0..200 {
+            assert!(policy.backoff(10, &mut rng) <= Duration::from_millis(2000));
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,177 @@
+use crate::models::{CallbackBehavior, ProtocolKind, Statistics, TrafficConfig, WellbitSettings};
+use chrono::{DateTime, Utc};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// A load test described in a YAML file: the devices to connect, the merchants
+/// to run and their traffic. Every merchant starts at once; the run ends when
+/// all of them stop or `duration_secs` passes. Unknown keys are rejected so a
+/// typo does not silently fall back to a default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub seed: Option<u64>, // Derives a seed for each merchant that does not set its own
+    #[serde(default)]
+    pub duration_secs: Option<u64>, // Stops merchants still running after this long
+    #[serde(default = "default_settle_secs")]
+    pub settle_secs: u64, // Wait for callbacks, cancellations and payments after traffic stops
+    #[serde(default)]
+    pub devices: Vec<ScenarioDevice>,
+    pub merchants: Vec<ScenarioMerchant>,
+}
+
+/// An emulated trader device, connected with its device code before traffic starts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioDevice {
+    pub name: String,
+    pub device_code: String,
+    #[serde(default)]
+    pub trader_id: Option<String>, // Payment notifications go to devices linked to the order's trader
+    #[serde(default = "default_battery_level")]
+    pub battery_level: u8,
+    #[serde(default = "default_network_info")]
+    pub network_info: String,
+}
+
+/// A merchant in a scenario. An existing merchant with the same name and API
+/// key is reused, otherwise one is created.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioMerchant {
+    pub name: String,
+    pub api_key: String,
+    #[serde(default)]
+    pub private_key: Option<String>,
+    #[serde(default)]
+    pub method: Option<String>, // First available method when unset
+    #[serde(default)]
+    pub protocol: ProtocolKind,
+    #[serde(default)]
+    pub wellbit: WellbitSettings,
+    #[serde(default = "default_liquidity_percentage")]
+    pub liquidity_percentage: f64,
+    #[serde(default)]
+    pub callback_url: Option<String>, // `{merchant_id}` is replaced with the merchant's id
+    #[serde(default)]
+    pub callback_behavior: CallbackBehavior,
+    #[serde(default, deserialize_with = "strict_traffic")]
+    pub traffic: TrafficConfig,
+}
+
+/// Reads a merchant's traffic block, rejecting keys `TrafficConfig` does not
+/// have. Stored merchants stay lenient so older config files still load.
+fn strict_traffic<'de, D: Deserializer<'de>>(deserializer: D) -> Result<TrafficConfig, D::Error> {
+    const ALIASES: [&str; 2] = ["amount_probabilities", "payout_amount_probabilities"];
+    
+    let value = serde_json::Value::deserialize(deserializer)?;
+    if let Some(keys) = value.as_object() {
+        // Every field serializes, so the default lists them all
+        let known = serde_json::to_value(TrafficConfig::default()).map_err(D::Error::custom)?;
+        if let Some(key) = keys.keys().find(|key| known.get(key.as_str()).is_none() && !ALIASES.contains(&key.as_str())) {
+            return Err(D::Error::custom(format!("unknown traffic field `{}`", key)));
+        }
+    }
+    serde_json::from_value(value).map_err(D::Error::custom)
+}
+
+fn default_settle_secs() -> u64 {
+    10
+}
+
+fn default_battery_level() -> u8 {
+    85
+}
+
+fn default_network_info() -> String {
+    "Wi-Fi".to_string()
+}
+
+fn default_liquidity_percentage() -> f64 {
+    80.0
+}
+
+impl Scenario {
+    pub fn from_yaml(yaml: &str) -> Result<Self, String> {
+        let scenario: Scenario = serde_yaml::from_str(yaml).map_err(|e| e.to_string())?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+    
+    pub fn to_yaml(&self) -> Result<String, String> {
+        serde_yaml::to_string(self).map_err(|e| e.to_string())
+    }
+    
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Scenario name is required".to_string());
+        }
+        if self.merchants.is_empty() {
+            return Err("At least one merchant is required".to_string());
+        }
+        
+        let mut names = HashSet::new();
+        for merchant in &self.merchants {
+            if !names.insert(merchant.name.as_str()) {
+                return Err(format!("Merchant {} is listed twice", merchant.name));
+            }
+            if merchant.api_key.trim().is_empty() {
+                return Err(format!("Merchant {} has no API key", merchant.name));
+            }
+            if !(0.0..=100.0).contains(&merchant.liquidity_percentage) {
+                return Err(format!("Merchant {}: liquidity must be 0-100%", merchant.name));
+            }
+            merchant.traffic.validate().map_err(|e| format!("Merchant {}: {}", merchant.name, e))?;
+            if self.duration_secs.is_none() && !merchant.traffic.is_bounded() {
+                return Err(format!(
+                    "Merchant {} never stops: set max_transactions, rate stages or the scenario's duration_secs",
+                    merchant.name
+                ));
+            }
+        }
+        
+        let mut names = HashSet::new();
+        for device in &self.devices {
+            if !names.insert(device.name.as_str()) {
+                return Err(format!("Device {} is listed twice", device.name));
+            }
+            if device.device_code.trim().is_empty() {
+                return Err(format!("Device {} has no device code", device.name));
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+/// Written as summary.json in the results bundle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioResult {
+    pub scenario: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub stopped_early: bool, // duration_secs ran out before every merchant finished
+    pub devices_connected: usize,
+    pub merchants: Vec<ScenarioMerchantResult>,
+    pub bundle_dir: PathBuf,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioMerchantResult {
+    pub name: String,
+    pub merchant_id: Uuid,
+    pub method_id: String,
+    pub seed: u64,
+    pub transactions: usize,
+    pub payouts: usize,
+    pub callbacks: usize,
+    pub missing_callbacks: usize, // Orders the reconciliation found without a callback
+    pub statistics: Statistics,
+}
\ No newline at end of file
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
+use std::collections::BTreeMap;
+use crate::models::{wellbit_status, CallbackOutcome, ProtocolKind, Receipt, WellbitCallback};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Transaction {
@@ -50,6 +52,104 @@ pub enum TransactionStatus {
     Paused,
     #[serde(rename = "FUNDS_RETURNED")]
     FundsReturned,
+    #[serde(rename = "MILK")]
+    Milk,
+    /// A status the emulator does not know yet, kept verbatim
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+impl TransactionStatus {
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "CREATED" => TransactionStatus::Created,
+            "IN_PROGRESS" => TransactionStatus::InProgress,
+            "READY" => TransactionStatus::Ready,
+            "CANCELED" => TransactionStatus::Canceled,
+            "EXPIRED" => TransactionStatus::Expired,
+            "DISPUTE" => TransactionStatus::Dispute,
+            "PAUSED" => TransactionStatus::Paused,
+            "FUNDS_RETURNED" => TransactionStatus::FundsReturned,
+            "MILK" => TransactionStatus::Milk,
+            other => TransactionStatus::Unknown(other.to_string()),
+        }
+    }
+    
+    /// Name of the status as the backend sends it
+    pub fn as_str(&self) -> &str {
+        match self {
+            TransactionStatus::Created => "CREATED",
+            TransactionStatus::InProgress => "IN_PROGRESS",
+            TransactionStatus::Ready => "READY",
+            TransactionStatus::Canceled => "CANCELED",
+            TransactionStatus::Expired => "EXPIRED",
+            TransactionStatus::Dispute => "DISPUTE",
+            TransactionStatus::Paused => "PAUSED",
+            TransactionStatus::FundsReturned => "FUNDS_RETURNED",
+            TransactionStatus::Milk => "MILK",
+            TransactionStatus::Unknown(value) => value,
+        }
+    }
+    
+    /// Statuses after which the backend no longer moves the transaction on its own
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            TransactionStatus::Ready
+                | TransactionStatus::Canceled
+                | TransactionStatus::Expired
+                | TransactionStatus::FundsReturned
+                | TransactionStatus::Milk
+        )
+    }
+    
+    /// Position in the transaction lifecycle. A status with a lower stage than
+    /// one already seen means updates arrived out of order.
+    pub fn lifecycle_stage(&self) -> u8 {
+        match self {
+            TransactionStatus::Created => 0,
+            // Unknown statuses are treated as intermediate so they never look out of order
+            TransactionStatus::InProgress | TransactionStatus::Paused | TransactionStatus::Unknown(_) => 1,
+            // Disputes can be opened on completed deals and resolved either way,
+            // so they share a stage with the final statuses
+            TransactionStatus::Dispute
+            | TransactionStatus::Ready
+            | TransactionStatus::Canceled
+            | TransactionStatus::Expired
+            | TransactionStatus::FundsReturned
+            | TransactionStatus::Milk => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum StatusSource {
+    Poll,
+    Callback,
+    Cancel,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusChange {
+    pub status: TransactionStatus,
+    pub observed_at: DateTime<Utc>,
+    pub source: StatusSource,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionStatusResponse {
+    pub id: String,
+    #[serde(rename = "orderId")]
+    pub order_id: String,
+    pub amount: f64,
+    pub status: TransactionStatus,
+    #[serde(rename = "type")]
+    pub transaction_type: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: String,
+    pub method: PaymentMethod,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -125,12 +225,82 @@ pub struct TransactionResponse {
     pub is_mock: bool,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CancelTransactionResponse {
+    pub success: bool,
+    pub transaction: CanceledTransaction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanceledTransaction {
+    pub id: String,
+    #[serde(rename = "numericId")]
+    pub numeric_id: u64,
+    #[serde(rename = "orderId")]
+    pub order_id: String,
+    pub amount: f64,
+    pub status: TransactionStatus,
+    #[serde(rename = "traderId")]
+    pub trader_id: Option<String>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CallbackRequest {
     pub id: String,
     pub status: String,
 }
 
+impl CallbackRequest {
+    /// Parses a native callback, or a Wellbit one with its status mapped to
+    /// the native name. Errors describe the native format.
+    pub fn from_body(body: &[u8]) -> Result<Self, serde_json::Error> {
+        let native_error = match serde_json::from_slice::<CallbackRequest>(body) {
+            Ok(callback) => return Ok(callback),
+            Err(e) => e,
+        };
+        
+        let Ok(wellbit) = serde_json::from_slice::<WellbitCallback>(body) else {
+            return Err(native_error);
+        };
+        
+        let id = match wellbit.payment_id {
+            serde_json::Value::String(id) => id,
+            other => other.to_string(),
+        };
+        
+        Ok(CallbackRequest {
+            id,
+            status: wellbit_status(&wellbit.payment_status).as_str().to_string(),
+        })
+    }
+}
+
+/// A callback as received by the callback server, kept verbatim in the callback log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceivedCallback {
+    pub id: Uuid,
+    pub merchant_id: Option<Uuid>, // From /callback/{merchant_id}, then the owner of the matched order
+    pub path: String,
+    pub headers: BTreeMap<String, String>,
+    pub raw_body: String,
+    pub callback: Option<CallbackRequest>, // None if the body did not parse
+    pub parse_error: Option<String>,
+    pub matched_order_id: Option<String>,
+    pub signature: CallbackSignature,
+    pub received_at: DateTime<Utc>,
+    pub outcome: CallbackOutcome,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum CallbackSignature {
+    NotChecked, // Merchant unknown or has no private key
+    Valid,
+    Invalid,
+    Missing, // Merchant has a private key but no x-api-token header was sent
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TransactionHistory {
     pub merchant_id: Uuid,
@@ -141,6 +311,33 @@ pub struct TransactionHistory {
     pub response_status: u16,
     pub response_body: Option<serde_json::Value>,
     pub error: Option<String>,
+    #[serde(default)]
+    pub canceled_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub status_changes: Vec<StatusChange>,
+    #[serde(default)]
+    pub receipts: Vec<Receipt>,
+    #[serde(default)]
+    pub dispute_id: Option<String>,
+    #[serde(default)]
+    pub protocol: ProtocolKind,
+}
+
+impl TransactionHistory {
+    /// Applies a status observed after creation. Returns false if nothing changed.
+    pub fn apply_status(&mut self, status: TransactionStatus, source: StatusSource) -> bool {
+        if self.transaction.status == status {
+            return false;
+        }
+        
+        self.transaction.status = status.clone();
+        self.status_changes.push(StatusChange {
+            status,
+            observed_at: Utc::now(),
+            source,
+        });
+        true
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -153,6 +350,42 @@ pub struct Statistics {
     pub callbacks_received: u64,
     pub liquid_transactions: u64,
     pub non_liquid_transactions: u64,
+    #[serde(default)]
+    pub cancelled_transactions: u64,
+    #[serde(default)]
+    pub failed_cancellations: u64,
+    #[serde(default)]
+    pub payouts_created: u64,
+    #[serde(default)]
+    pub payouts_failed: u64,
+    #[serde(default)]
+    pub receipts_uploaded: u64,
+    #[serde(default)]
+    pub receipts_failed: u64,
+    #[serde(default)]
+    pub disputes_opened: u64,
+    #[serde(default)]
+    pub disputes_failed: u64,
+    #[serde(default)]
+    pub dispute_messages_sent: u64,
+    /// Extra attempts made by the retry policy; not counted as requests or failures
+    #[serde(default)]
+    pub retry_attempts: u64,
+    /// Calls that failed at first but succeeded on a retry
+    #[serde(default)]
+    pub retries_recovered: u64,
+    #[serde(default)]
+    pub total_payout_amount: f64,
     pub error_breakdown: std::collections::HashMap<String, u64>,
     pub status_breakdown: std::collections::HashMap<String, u64>,
+    #[serde(default)]
+    pub payout_status_breakdown: std::collections::HashMap<String, u64>,
+    #[serde(default)]
+    pub retry_breakdown: std::collections::HashMap<String, u64>,
+    /// Statuses and bank types the emulator or the server catalog does not recognise
+    #[serde(default)]
+    pub unknown_values: std::collections::HashMap<String, u64>,
+    /// Simulated buyer outcomes by amount band
+    #[serde(default)]
+    pub conversion_breakdown: std::collections::HashMap<String, crate::models::ConversionStats>,
 }
\ No newline at end of file
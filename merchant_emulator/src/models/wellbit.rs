@@ -0,0 +1,114 @@
+use crate::models::TransactionStatus;
+use serde::{Deserialize, Serialize};
+
+/// Merchant API a merchant uses to create and follow deposits.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum ProtocolKind {
+    #[default]
+    Native,  // /api/merchant
+    Wellbit, // /api/wellbit/payment
+}
+
+impl std::fmt::Display for ProtocolKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProtocolKind::Native => write!(f, "Native"),
+            ProtocolKind::Wellbit => write!(f, "Wellbit"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WellbitPaymentType {
+    #[default]
+    Card,
+    Sbp,
+}
+
+/// Merchant settings used only by the Wellbit protocol.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WellbitSettings {
+    pub api_key_public: String, // Sent as x-api-key; requests are signed with the merchant's private key
+    pub payment_type: WellbitPaymentType,
+    pub bank: Option<String>, // Wellbit bank code, e.g. SBERRUB; None lets the backend pick any bank
+    pub course: f64, // RUB per USDT sent as payment_course
+    pub lifetime_secs: u64,
+}
+
+impl Default for WellbitSettings {
+    fn default() -> Self {
+        Self {
+            api_key_public: String::new(),
+            payment_type: WellbitPaymentType::Card,
+            bank: None,
+            course: 95.0,
+            lifetime_secs: 1800,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WellbitPaymentRequest {
+    pub payment_id: u64,
+    pub payment_amount: f64,
+    pub payment_amount_usdt: f64,
+    pub payment_amount_profit: f64,
+    pub payment_amount_profit_usdt: f64,
+    pub payment_fee_percent_profit: f64,
+    pub payment_type: WellbitPaymentType,
+    pub payment_bank: Option<String>,
+    pub payment_course: f64,
+    pub payment_lifetime: u64,
+    pub payment_status: String,
+}
+
+/// Response of `/payment/create`, `/payment/get` and `/payment/status`.
+/// Only create echoes the full payment back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WellbitPayment {
+    pub payment_id: u64,
+    pub payment_status: String,
+    #[serde(default)]
+    pub payment_amount: Option<f64>,
+    #[serde(default)]
+    pub payment_bank: Option<String>,
+    #[serde(default)]
+    pub payment_credential: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WellbitPaymentQuery {
+    pub payment_id: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WellbitStatusUpdate {
+    pub payment_id: u64,
+    pub payment_status: String,
+}
+
+/// Callback sent to Wellbit merchants, `{ callback: "payment", payment_id, payment_status }`.
+/// Status webhooks carry the same fields without `callback`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WellbitCallback {
+    #[serde(default)]
+    pub callback: Option<String>,
+    pub payment_id: serde_json::Value, // Number from /payment/status, order id string from notifications
+    pub payment_status: String,
+}
+
+/// Maps a Wellbit payment status to the transaction status the backend stores.
+/// `new` covers both CREATED and IN_PROGRESS; payments get requisites on
+/// creation, so it is read as IN_PROGRESS.
+pub fn wellbit_status(value: &str) -> TransactionStatus {
+    match value {
+        "new" => TransactionStatus::InProgress,
+        "complete" => TransactionStatus::Ready,
+        "cancel" => TransactionStatus::Canceled,
+        "chargeback" => TransactionStatus::Milk,
+        "appeal" => TransactionStatus::Dispute,
+        other => TransactionStatus::Unknown(other.to_string()),
+    }
+}
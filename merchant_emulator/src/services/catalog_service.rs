@@ -0,0 +1,113 @@
+use crate::api::ApiClient;
+use crate::models::{EnumCatalog, MerchantCredentials, Method, TransactionStatus};
+use crate::services::StatisticsService;
+use anyhow::Result;
+use parking_lot::RwLock;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+struct Cached<T> {
+    value: T,
+    fetched_at: Instant,
+}
+
+/// Caches the backend's enum values and each merchant's methods, and checks
+/// statuses and bank types coming back from the server against them.
+pub struct CatalogService {
+    api_client: Arc<ApiClient>,
+    stats_service: Arc<StatisticsService>,
+    ttl: Duration,
+    // Enum values are global, so one cache serves every merchant
+    enums: RwLock<Option<Cached<EnumCatalog>>>,
+    methods: RwLock<HashMap<Uuid, Cached<Vec<Method>>>>,
+    reported: RwLock<HashSet<String>>,
+}
+
+impl CatalogService {
+    pub fn new(api_client: Arc<ApiClient>, stats_service: Arc<StatisticsService>, ttl: Duration) -> Self {
+        Self {
+            api_client,
+            stats_service,
+            ttl,
+            enums: RwLock::new(None),
+            methods: RwLock::new(HashMap::new()),
+            reported: RwLock::new(HashSet::new()),
+        }
+    }
+    
+    pub async fn enums(&self, credentials: &MerchantCredentials) -> Result<EnumCatalog> {
+        if let Some(cached) = self.enums.read().as_ref().filter(|c| c.fetched_at.elapsed() < self.ttl) {
+            return Ok(cached.value.clone());
+        }
+        
+        let catalog = self.api_client.get_enums(credentials).await?;
+        *self.enums.write() = Some(Cached {
+            value: catalog.clone(),
+            fetched_at: Instant::now(),
+        });
+        
+        Ok(catalog)
+    }
+    
+    pub async fn methods(&self, credentials: &MerchantCredentials) -> Result<Vec<Method>> {
+        if let Some(cached) = self.methods.read().get(&credentials.merchant_id).filter(|c| c.fetched_at.elapsed() < self.ttl) {
+            return Ok(cached.value.clone());
+        }
+        
+        let methods = self.api_client.get_methods(credentials).await?;
+        self.methods.write().insert(credentials.merchant_id, Cached {
+            value: methods.clone(),
+            fetched_at: Instant::now(),
+        });
+        
+        Ok(methods)
+    }
+    
+    /// Reports statuses the emulator does not model, and modelled statuses the
+    /// server no longer lists.
+    pub async fn check_status(&self, credentials: &MerchantCredentials, status: &TransactionStatus) {
+        if let TransactionStatus::Unknown(value) = status {
+            self.report(credentials.merchant_id, "status", value, "not known to the emulator");
+            return;
+        }
+        
+        if let Some(catalog) = self.server_catalog(credentials).await {
+            if !catalog.status.iter().any(|s| s == status.as_str()) {
+                self.report(credentials.merchant_id, "status", status.as_str(), "not in the server catalog");
+            }
+        }
+    }
+    
+    pub async fn check_bank_type(&self, credentials: &MerchantCredentials, bank_type: &str) {
+        if let Some(catalog) = self.server_catalog(credentials).await {
+            if !catalog.bank_type.iter().any(|b| b == bank_type) {
+                self.report(credentials.merchant_id, "bank type", bank_type, "not in the server catalog");
+            }
+        }
+    }
+    
+    /// Validation is best effort: without a catalog only locally unknown values are reported
+    async fn server_catalog(&self, credentials: &MerchantCredentials) -> Option<EnumCatalog> {
+        match self.enums(credentials).await {
+            Ok(catalog) => Some(catalog),
+            Err(e) => {
+                debug!("Enum catalog unavailable, skipping validation: {}", e);
+                None
+            }
+        }
+    }
+    
+    /// Counts every occurrence, but only logs the first one of each value.
+    fn report(&self, merchant_id: Uuid, kind: &str, value: &str, reason: &str) {
+        let key = format!("{} {}", kind, value);
+        
+        if self.reported.write().insert(key.clone()) {
+            warn!("Unrecognised {} {}: {}", kind, value, reason);
+        }
+        
+        self.stats_service.record_unknown_value(merchant_id, &key);
+    }
+}
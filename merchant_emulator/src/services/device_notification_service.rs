@@ -1,6 +1,7 @@
 use crate::device::{DeviceManager, DeviceApiClient, NotificationRequest};
 use crate::models::{Merchant, Transaction};
 use anyhow::Result;
+use rand::Rng;
 use std::sync::Arc;
 use tracing::{info, error};
 use chrono::Utc;
@@ -22,19 +23,27 @@ impl DeviceNotificationService {
         &self,
         merchant: &Merchant,
         transaction: &Transaction,
+        rng: &mut impl Rng,
     ) -> Result<()> {
         // Check if merchant has liquid transactions enabled
-        if !merchant.is_liquid() {
+        if !merchant.is_liquid(rng) {
             info!("Transaction is not liquid, skipping device notification");
             return Ok(());
         }
 
+        self.send_payment_notification(transaction).await?;
+        Ok(())
+    }
+
+    /// Sends the bank's incoming-payment notification for `transaction` to the
+    /// connected devices of its trader. Returns how many devices accepted it.
+    pub async fn send_payment_notification(&self, transaction: &Transaction) -> Result<usize> {
         // Get trader ID from transaction
         let trader_id = match &transaction.trader_id {
             Some(id) => id,
             None => {
                 info!("Transaction has no trader ID, skipping device notification");
-                return Ok(());
+                return Ok(0);
             }
         };
 
@@ -43,12 +52,19 @@ impl DeviceNotificationService {
         
         if devices.is_empty() {
             info!("No connected devices found for trader {}", trader_id);
-            return Ok(());
+            return Ok(0);
         }
 
+        // The backend reports bank types in upper case (SBERBANK, TBANK)
+        let bank_type = transaction.requisites
+            .as_ref()
+            .map(|r| r.bank_type.to_lowercase())
+            .unwrap_or_default();
+        let bank_type = bank_type.as_str();
+
         // Get bank info from transaction requisites
         let (bank_name, card_last_digits) = if let Some(requisites) = &transaction.requisites {
-            let bank = match requisites.bank_type.as_str() {
+            let bank = match bank_type {
                 "sber" | "sberbank" => "Сбербанк",
                 "tinkoff" | "t-bank" | "tbank" => "Тинькофф",
                 "alfa" | "alfabank" | "alpha" => "Альфа-Банк",
@@ -84,7 +100,7 @@ impl DeviceNotificationService {
         };
 
         // Create bank-specific notification
-        let (package_name, app_name, title, content) = match requisites.bank_type.as_str() {
+        let (package_name, app_name, title, content) = match bank_type {
             "sber" | "sberbank" => (
                 "ru.sberbankmobile".to_string(),
                 "СберБанк".to_string(),
@@ -137,7 +153,7 @@ impl DeviceNotificationService {
             ),
             _ => {
                 // Default format for other banks
-                let package = match requisites.bank_type.as_str() {
+                let package = match bank_type {
                     "alfa" | "alfabank" => "ru.alfabank.mobile.android",
                     "vtb" => "ru.vtb24.mobilebanking.android",
                     "gazprombank" => "ru.gazprombank.android.mobilebank.app",
@@ -174,6 +190,7 @@ impl DeviceNotificationService {
         };
 
         // Send notification to all connected devices
+        let mut delivered = 0;
         for device in devices {
             if let Some(token) = &device.token {
                 info!(
@@ -185,6 +202,7 @@ impl DeviceNotificationService {
                 match self.api_client.send_notification(token, notification.clone()).await {
                     Ok(_) => {
                         info!("Notification sent successfully to device {}", device.id);
+                        delivered += 1;
                     }
                     Err(e) => {
                         error!("Failed to send notification to device {}: {}", device.id, e);
@@ -193,7 +211,7 @@ impl DeviceNotificationService {
             }
         }
 
-        Ok(())
+        Ok(delivered)
     }
 
     pub async fn send_test_notification(
@@ -49,6 +49,7 @@ where
 }
 
 /// Handle to control log capture
+#[derive(Clone)]
 pub struct LogCaptureHandle {
     tx: Arc<RwLock<Option<mpsc::Sender<String>>>>,
 }
@@ -61,6 +62,11 @@ impl LogCaptureHandle {
     pub fn clear_channel(&self) {
         *self.tx.write() = None;
     }
+    
+    /// True while a channel is set, i.e. something else shows the logs
+    pub fn is_capturing(&self) -> bool {
+        self.tx.read().is_some()
+    }
 }
 
 #[derive(Default)]
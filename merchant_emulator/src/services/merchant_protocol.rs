@@ -0,0 +1,278 @@
+use crate::api::ApiClient;
+use crate::api::error::{ApiError, ApiResult};
+use crate::models::*;
+use chrono::Utc;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use rand::{Rng, RngCore};
+use std::sync::Arc;
+
+/// A deposit created through a protocol, in the emulator's transaction model.
+pub struct CreatedDeposit {
+    pub transaction: Transaction,
+    pub bank_type: Option<String>, // Backend bank type, when the protocol reports one
+    pub response_status: u16,
+    pub response_body: serde_json::Value,
+}
+
+/// A status observed by polling or cancelling a deposit.
+pub struct DepositUpdate {
+    pub status: TransactionStatus,
+    pub updated_at: String,
+}
+
+/// The merchant-facing API used for deposits. Everything else (payouts,
+/// receipts, disputes) only exists in the native API.
+pub trait MerchantProtocol: Send + Sync {
+    fn kind(&self) -> ProtocolKind;
+    
+    /// A fresh order id in the form the protocol can address later,
+    /// drawn from `rng` so seeded runs repeat it
+    fn new_order_id(&self, rng: &mut dyn RngCore) -> String;
+    
+    fn create_deposit<'a>(
+        &'a self,
+        merchant: &'a Merchant,
+        request: &'a TransactionRequest,
+    ) -> BoxFuture<'a, ApiResult<CreatedDeposit>>;
+    
+    fn deposit_status<'a>(
+        &'a self,
+        credentials: &'a MerchantCredentials,
+        transaction: &'a Transaction,
+    ) -> BoxFuture<'a, ApiResult<DepositUpdate>>;
+    
+    fn cancel_deposit<'a>(
+        &'a self,
+        credentials: &'a MerchantCredentials,
+        order_id: &'a str,
+    ) -> BoxFuture<'a, ApiResult<DepositUpdate>>;
+}
+
+/// One instance of each protocol, picked per merchant.
+pub struct Protocols {
+    native: NativeProtocol,
+    wellbit: WellbitProtocol,
+}
+
+impl Protocols {
+    pub fn new(api_client: Arc<ApiClient>) -> Self {
+        Self {
+            native: NativeProtocol { api_client: api_client.clone() },
+            wellbit: WellbitProtocol { api_client },
+        }
+    }
+    
+    pub fn get(&self, kind: ProtocolKind) -> &dyn MerchantProtocol {
+        match kind {
+            ProtocolKind::Native => &self.native,
+            ProtocolKind::Wellbit => &self.wellbit,
+        }
+    }
+}
+
+/// `/api/merchant/transactions`
+pub struct NativeProtocol {
+    api_client: Arc<ApiClient>,
+}
+
+impl MerchantProtocol for NativeProtocol {
+    fn kind(&self) -> ProtocolKind {
+        ProtocolKind::Native
+    }
+    
+    fn new_order_id(&self, rng: &mut dyn RngCore) -> String {
+        format!("order_{}", uuid::Builder::from_random_bytes(rng.gen()).into_uuid())
+    }
+    
+    fn create_deposit<'a>(
+        &'a self,
+        merchant: &'a Merchant,
+        request: &'a TransactionRequest,
+    ) -> BoxFuture<'a, ApiResult<CreatedDeposit>> {
+        async move {
+            let response = self.api_client.create_transaction(&merchant.credentials(), request.clone()).await?;
+            
+            let transaction = Transaction {
+                id: response.id.clone(),
+                numeric_id: response.numeric_id,
+                order_id: request.order_id.clone(),
+                amount: response.amount,
+                crypto: response.crypto,
+                status: response.status.clone(),
+                trader_id: Some(response.trader_id.clone()),
+                requisites: Some(response.requisites.clone()),
+                created_at: response.created_at.clone(),
+                updated_at: response.updated_at.clone(),
+                expired_at: response.expired_at.clone(),
+                method: Some(response.method.clone()),
+                is_mock: response.is_mock,
+                callback_sent: false,
+                method_id: request.method_id.clone(),
+                rate: request.rate,
+            };
+            
+            Ok(CreatedDeposit {
+                transaction,
+                bank_type: Some(response.requisites.bank_type.clone()),
+                response_status: 201,
+                response_body: serde_json::to_value(&response)?,
+            })
+        }
+        .boxed()
+    }
+    
+    fn deposit_status<'a>(
+        &'a self,
+        credentials: &'a MerchantCredentials,
+        transaction: &'a Transaction,
+    ) -> BoxFuture<'a, ApiResult<DepositUpdate>> {
+        async move {
+            let response = self.api_client.get_transaction_status(credentials, &transaction.id).await?;
+            
+            Ok(DepositUpdate {
+                status: response.status,
+                updated_at: response.updated_at,
+            })
+        }
+        .boxed()
+    }
+    
+    fn cancel_deposit<'a>(
+        &'a self,
+        credentials: &'a MerchantCredentials,
+        order_id: &'a str,
+    ) -> BoxFuture<'a, ApiResult<DepositUpdate>> {
+        async move {
+            let canceled = self.api_client.cancel_transaction(credentials, order_id).await?.transaction;
+            
+            Ok(DepositUpdate {
+                status: canceled.status,
+                updated_at: canceled.updated_at,
+            })
+        }
+        .boxed()
+    }
+}
+
+/// `/api/wellbit/payment`. Payments are addressed by a numeric `payment_id`,
+/// which the backend stores as the order id.
+pub struct WellbitProtocol {
+    api_client: Arc<ApiClient>,
+}
+
+impl MerchantProtocol for WellbitProtocol {
+    fn kind(&self) -> ProtocolKind {
+        ProtocolKind::Wellbit
+    }
+    
+    fn new_order_id(&self, rng: &mut dyn RngCore) -> String {
+        // Stay below 2^53, the largest id the backend's JSON numbers hold exactly
+        rng.gen_range(1..1u64 << 53).to_string()
+    }
+    
+    fn create_deposit<'a>(
+        &'a self,
+        merchant: &'a Merchant,
+        request: &'a TransactionRequest,
+    ) -> BoxFuture<'a, ApiResult<CreatedDeposit>> {
+        async move {
+            let settings = &merchant.wellbit;
+            let payment_id = payment_id(&request.order_id)?;
+            
+            // The method is chosen by the backend from the payment type and amount
+            let payment = WellbitPaymentRequest {
+                payment_id,
+                payment_amount: request.amount,
+                payment_amount_usdt: (request.amount / settings.course * 100.0).round() / 100.0,
+                payment_amount_profit: 0.0,
+                payment_amount_profit_usdt: 0.0,
+                payment_fee_percent_profit: 0.0,
+                payment_type: settings.payment_type,
+                payment_bank: settings.bank.clone(),
+                payment_course: settings.course,
+                payment_lifetime: settings.lifetime_secs,
+                payment_status: "new".to_string(),
+            };
+            
+            let response = self.api_client.wellbit_create_payment(&merchant.credentials(), &payment).await?;
+            
+            let now = Utc::now();
+            let transaction = Transaction {
+                id: response.payment_id.to_string(),
+                numeric_id: response.payment_id,
+                order_id: request.order_id.clone(),
+                amount: response.payment_amount.unwrap_or(request.amount),
+                crypto: Some(payment.payment_amount_usdt),
+                status: wellbit_status(&response.payment_status),
+                trader_id: None,
+                requisites: response.payment_credential.clone().map(|card_number| TransactionRequisites {
+                    id: String::new(),
+                    bank_type: response.payment_bank.clone().unwrap_or_default(),
+                    card_number,
+                    recipient_name: String::new(),
+                    trader_name: String::new(),
+                }),
+                created_at: now.to_rfc3339(),
+                updated_at: now.to_rfc3339(),
+                expired_at: (now + chrono::Duration::seconds(settings.lifetime_secs as i64)).to_rfc3339(),
+                method: None,
+                is_mock: request.is_mock.unwrap_or(false),
+                callback_sent: false,
+                method_id: request.method_id.clone(),
+                rate: Some(settings.course),
+            };
+            
+            // payment_bank is a Wellbit code such as SBERRUB, not a backend bank type
+            Ok(CreatedDeposit {
+                transaction,
+                bank_type: None,
+                response_status: 200,
+                response_body: serde_json::to_value(&response)?,
+            })
+        }
+        .boxed()
+    }
+    
+    fn deposit_status<'a>(
+        &'a self,
+        credentials: &'a MerchantCredentials,
+        transaction: &'a Transaction,
+    ) -> BoxFuture<'a, ApiResult<DepositUpdate>> {
+        async move {
+            let payment = self.api_client
+                .wellbit_get_payment(credentials, payment_id(&transaction.order_id)?)
+                .await?;
+            
+            Ok(DepositUpdate {
+                status: wellbit_status(&payment.payment_status),
+                updated_at: Utc::now().to_rfc3339(),
+            })
+        }
+        .boxed()
+    }
+    
+    fn cancel_deposit<'a>(
+        &'a self,
+        credentials: &'a MerchantCredentials,
+        order_id: &'a str,
+    ) -> BoxFuture<'a, ApiResult<DepositUpdate>> {
+        async move {
+            let payment = self.api_client
+                .wellbit_update_status(credentials, payment_id(order_id)?, "cancel")
+                .await?;
+            
+            Ok(DepositUpdate {
+                status: wellbit_status(&payment.payment_status),
+                updated_at: Utc::now().to_rfc3339(),
+            })
+        }
+        .boxed()
+    }
+}
+
+fn payment_id(order_id: &str) -> ApiResult<u64> {
+    order_id
+        .parse()
+        .map_err(|_| ApiError::InvalidRequest(format!("order id {} is not a Wellbit payment id", order_id)))
+}
@@ -1,16 +1,28 @@
 use crate::models::*;
 use crate::api::ApiClient;
-use crate::services::{StorageService, StatisticsService};
+use crate::api::signature::{self, SIGNATURE_HEADER};
+use crate::services::merchant_protocol::DepositUpdate;
+use crate::services::{receipt_generator, CatalogService, Protocols, StorageService, StatisticsService, StatusTracker};
 use anyhow::Result;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use uuid::Uuid;
 use chrono::Utc;
-use tracing::{info, error};
+use tracing::{info, warn, error};
+
+/// Callbacks arriving within this window are written to disk together.
+const CALLBACK_SAVE_DELAY: Duration = Duration::from_millis(500);
 
 pub struct MerchantService {
     api_client: Arc<ApiClient>,
     storage: Arc<StorageService>,
     stats_service: Arc<StatisticsService>,
+    status_tracker: Arc<StatusTracker>,
+    catalog: Arc<CatalogService>,
+    protocols: Arc<Protocols>,
+    callback_save_pending: Arc<AtomicBool>,
 }
 
 impl MerchantService {
@@ -18,26 +30,38 @@ impl MerchantService {
         api_client: Arc<ApiClient>,
         storage: Arc<StorageService>,
         stats_service: Arc<StatisticsService>,
+        status_tracker: Arc<StatusTracker>,
+        catalog: Arc<CatalogService>,
+        protocols: Arc<Protocols>,
     ) -> Self {
         Self {
             api_client,
             storage,
             stats_service,
+            status_tracker,
+            catalog,
+            protocols,
+            callback_save_pending: Arc::new(AtomicBool::new(false)),
         }
     }
     
-    pub async fn create_merchant(&self, name: String, api_key: String) -> Result<Merchant> {
+    pub async fn create_merchant(
+        &self,
+        name: String,
+        api_key: String,
+        private_key: Option<String>,
+    ) -> Result<Merchant> {
         info!("Creating merchant with name: {}", name);
         
+        let mut merchant = Merchant::new(name.clone(), api_key, private_key);
+        
         // Verify API key by connecting
         info!("Verifying API key...");
-        let _merchant_info = self.api_client.connect(&api_key).await?;
+        let _merchant_info = self.api_client.connect(&merchant.credentials()).await?;
         info!("API key verified successfully");
         
-        let mut merchant = Merchant::new(name.clone(), api_key);
-        
         info!("Getting balance...");
-        merchant.balance_usdt = self.api_client.get_balance(&merchant.api_key).await.unwrap_or(0.0);
+        merchant.balance_usdt = self.api_client.get_balance(&merchant.credentials()).await.unwrap_or(0.0);
         info!("Balance retrieved: {}", merchant.balance_usdt);
         
         info!("Adding merchant to storage...");
@@ -71,6 +95,10 @@ impl MerchantService {
         self.storage.get_all_merchants()
     }
     
+    pub fn get_merchant_transactions(&self, merchant_id: &Uuid) -> Vec<TransactionHistory> {
+        self.storage.get_merchant_transactions(merchant_id)
+    }
+    
     pub async fn create_transaction(
         &self,
         merchant: &Merchant,
@@ -78,7 +106,21 @@ impl MerchantService {
         method_id: String,
         is_mock: bool,
     ) -> Result<Transaction> {
-        let order_id = format!("order_{}", Uuid::new_v4());
+        self.create_transaction_with_rng(merchant, amount, method_id, is_mock, &mut StdRng::from_entropy()).await
+    }
+    
+    /// Like `create_transaction`, drawing the order and user ids from `rng`
+    /// so a seeded traffic run sends the same requests every time.
+    pub async fn create_transaction_with_rng(
+        &self,
+        merchant: &Merchant,
+        amount: f64,
+        method_id: String,
+        is_mock: bool,
+        rng: &mut StdRng,
+    ) -> Result<Transaction> {
+        let protocol = self.protocols.get(merchant.protocol);
+        let order_id = protocol.new_order_id(rng);
         let expired_at = (Utc::now() + chrono::Duration::hours(24)).to_rfc3339();
         
         let mut request = TransactionRequest {
@@ -88,7 +130,7 @@ impl MerchantService {
             rate: None,
             expired_at,
             user_ip: Some("127.0.0.1".to_string()),
-            user_id: Some(format!("user_{}", Uuid::new_v4())),
+            user_id: Some(format!("user_{}", uuid::Builder::from_random_bytes(rng.gen()).into_uuid())),
             transaction_type: Some("IN".to_string()),
             callback_uri: merchant.callback_url.clone(),
             success_uri: None,
@@ -104,36 +146,19 @@ impl MerchantService {
         
         let start_time = Utc::now();
         
-        let result = self.api_client.create_transaction(&merchant.api_key, request.clone()).await;
+        let result = protocol.create_deposit(merchant, &request).await;
         
         let end_time = Utc::now();
         
         match result {
-            Ok(response) => {
-                let transaction = Transaction {
-                    id: response.id.clone(),
-                    numeric_id: response.numeric_id,
-                    order_id: order_id.clone(),
-                    amount: response.amount,
-                    crypto: response.crypto,
-                    status: response.status.clone(),
-                    trader_id: Some(response.trader_id.clone()),
-                    requisites: Some(TransactionRequisites {
-                        id: response.requisites.id.clone(),
-                        bank_type: response.requisites.bank_type.clone(),
-                        card_number: response.requisites.card_number.clone(),
-                        recipient_name: response.requisites.recipient_name.clone(),
-                        trader_name: response.requisites.trader_name.clone(),
-                    }),
-                    created_at: response.created_at.clone(),
-                    updated_at: response.updated_at.clone(),
-                    expired_at: response.expired_at.clone(),
-                    method: Some(response.method.clone()),
-                    is_mock: response.is_mock,
-                    callback_sent: false,
-                    method_id: method_id.clone(),
-                    rate: request.rate,
-                };
+            Ok(created) => {
+                let transaction = created.transaction;
+                
+                let credentials = merchant.credentials();
+                self.catalog.check_status(&credentials, &transaction.status).await;
+                if let Some(bank_type) = &created.bank_type {
+                    self.catalog.check_bank_type(&credentials, bank_type).await;
+                }
                 
                 // Save to history
                 let history = TransactionHistory {
@@ -142,30 +167,39 @@ impl MerchantService {
                     request_time: start_time,
                     response_time: end_time,
                     request_body: request,
-                    response_status: 201,
-                    response_body: Some(serde_json::to_value(&response)?),
+                    response_status: created.response_status,
+                    response_body: Some(created.response_body),
                     error: None,
+                    canceled_at: None,
+                    status_changes: Vec::new(),
+                    receipts: Vec::new(),
+                    dispute_id: None,
+                    protocol: protocol.kind(),
                 };
                 
-                self.storage.add_transaction(history);
+                self.storage.add_transaction(history.clone());
                 self.storage.save_transactions().await?;
                 
+                // Follow the transaction until it settles
+                self.status_tracker.track(&merchant.credentials(), &history);
+                
                 // Update statistics
                 self.stats_service.record_success(merchant.id, amount, &transaction.status);
                 
-                if merchant.is_liquid() {
+                if !is_mock {
                     self.stats_service.record_liquid_transaction(merchant.id);
                 } else {
                     self.stats_service.record_non_liquid_transaction(merchant.id);
                 }
                 
-                info!("Transaction created: {} (amount: {}, status: {:?})", 
-                    transaction.id, transaction.amount, transaction.status);
+                info!("Transaction created via {}: {} (amount: {}, status: {:?})",
+                    protocol.kind(), transaction.id, transaction.amount, transaction.status);
                 
                 Ok(transaction)
             }
             Err(e) => {
-                error!("Failed to create transaction: {}", e);
+                error!("Failed to create transaction: {} (category: {}, code: {})",
+                    e, e.category(), e.code().unwrap_or("-"));
                 
                 // Save failed attempt
                 let history = TransactionHistory {
@@ -191,46 +225,409 @@ impl MerchantService {
                     request_time: start_time,
                     response_time: end_time,
                     request_body: request,
-                    response_status: 500,
+                    response_status: e.status().map(|s| s.as_u16()).unwrap_or(0),
                     response_body: None,
                     error: Some(e.to_string()),
+                    canceled_at: None,
+                    status_changes: Vec::new(),
+                    receipts: Vec::new(),
+                    dispute_id: None,
+                    protocol: protocol.kind(),
                 };
                 
                 self.storage.add_transaction(history);
                 self.storage.save_transactions().await?;
                 
                 // Update statistics
-                self.stats_service.record_failure(merchant.id, &e.to_string());
+                self.stats_service.record_failure(merchant.id, &e);
                 
-                Err(e)
+                Err(e.into())
             }
         }
     }
     
-    pub async fn handle_callback(&self, merchant_id: Uuid, callback: CallbackRequest) -> Result<()> {
-        info!("Handling callback for merchant {}: {:?}", merchant_id, callback);
+    pub async fn cancel_transaction(&self, merchant: &Merchant, order_id: &str) -> Result<DepositUpdate> {
+        let protocol = self.protocols.get(merchant.protocol);
         
-        // Update statistics
-        self.stats_service.record_callback(merchant_id);
+        match protocol.cancel_deposit(&merchant.credentials(), order_id).await {
+            Ok(canceled) => {
+                let canceled_at = Utc::now();
+                
+                let found = self.storage.update_transaction(order_id, |history| {
+                    history.apply_status(canceled.status.clone(), StatusSource::Cancel);
+                    history.transaction.updated_at = canceled.updated_at.clone();
+                    history.canceled_at = Some(canceled_at);
+                });
+                
+                if found {
+                    self.storage.save_transactions().await?;
+                }
+                
+                self.stats_service.record_cancellation(merchant.id);
+                self.stats_service.update_status(&merchant.id, &format!("{:?}", canceled.status));
+                
+                info!("Transaction cancelled: order {} (status: {:?})", order_id, canceled.status);
+                
+                Ok(canceled)
+            }
+            Err(e) => {
+                error!("Failed to cancel transaction {}: {}", order_id, e);
+                
+                self.stats_service.record_cancellation_failure(merchant.id);
+                
+                Err(e.into())
+            }
+        }
+    }
+    
+    pub async fn create_payout(
+        &self,
+        merchant: &Merchant,
+        amount: f64,
+        method_id: String,
+        is_mock: bool,
+    ) -> Result<Payout> {
+        self.create_payout_with_rng(merchant, amount, method_id, is_mock, &mut StdRng::from_entropy()).await
+    }
+    
+    /// Like `create_payout`, drawing the recipient and reference from `rng`.
+    pub async fn create_payout_with_rng(
+        &self,
+        merchant: &Merchant,
+        amount: f64,
+        method_id: String,
+        is_mock: bool,
+        rng: &mut StdRng,
+    ) -> Result<Payout> {
+        let (wallet, bank) = generate_payout_recipient(rng);
+        
+        let request = PayoutRequest {
+            method_id,
+            amount,
+            wallet,
+            bank,
+            is_card: true,
+            merchant_rate: match merchant.payment_type {
+                PaymentType::UsdtTrc20 => merchant.rate.or(Some(95.0)),
+                PaymentType::Rub => None,
+            },
+            external_reference: Some(format!("payout_{}", uuid::Builder::from_random_bytes(rng.gen()).into_uuid())),
+            webhook_url: merchant.callback_url.clone(),
+            metadata: Some(PayoutMetadata { is_mock }),
+        };
+        
+        let start_time = Utc::now();
+        
+        let result = self.api_client.create_payout(&merchant.credentials(), request.clone()).await;
+        
+        let end_time = Utc::now();
+        
+        let history = PayoutHistory {
+            merchant_id: merchant.id,
+            payout: result.as_ref().ok().cloned(),
+            request_time: start_time,
+            response_time: end_time,
+            request_body: request,
+            last_checked_at: None,
+            error: result.as_ref().err().map(|e| e.to_string()),
+        };
+        
+        self.storage.add_payout(history);
+        self.storage.save_payouts().await?;
+        
+        match result {
+            Ok(payout) => {
+                self.stats_service.record_payout_created(merchant.id, amount, &payout.id, &payout.status);
+                
+                info!("Payout created: {} (amount: {}, status: {:?})",
+                    payout.id, payout.amount, payout.status);
+                
+                Ok(payout)
+            }
+            Err(e) => {
+                error!("Failed to create payout: {}", e);
+                
+                self.stats_service.record_payout_failure(merchant.id, &e);
+                
+                Err(e.into())
+            }
+        }
+    }
+    
+    /// Fetches the current payout state and stores it if the status changed.
+    pub async fn refresh_payout(&self, merchant: &Merchant, previous: &Payout) -> Result<Payout> {
+        let payout = self.api_client.get_payout(&merchant.credentials(), &previous.id).await?;
+        
+        if payout.status != previous.status {
+            info!("Payout {} status changed: {:?} -> {:?}", payout.id, previous.status, payout.status);
+            
+            self.stats_service.update_payout_status(&merchant.id, &payout.id, &payout.status);
+            self.storage.update_payout(payout.clone());
+            self.storage.save_payouts().await?;
+        }
         
-        // Fetch updated transaction details
-        if let Some(merchant) = self.get_merchant(&merchant_id) {
-            match self.api_client.get_transaction(&merchant.api_key, &callback.id).await {
-                Ok(transaction) => {
-                    info!("Updated transaction details: {:?}", transaction);
-                    
-                    // Update status breakdown
-                    self.stats_service.update_status(&merchant_id, &transaction.status);
+        Ok(payout)
+    }
+    
+    /// Generates a synthetic receipt and uploads it for the transaction.
+    pub async fn attach_receipt(
+        &self,
+        merchant: &Merchant,
+        transaction: &Transaction,
+        format: ReceiptFormat,
+    ) -> Result<Receipt> {
+        self.attach_receipt_with_rng(merchant, transaction, format, &mut StdRng::from_entropy()).await
+    }
+    
+    /// Like `attach_receipt`, drawing the receipt image from `rng` so a seeded
+    /// traffic run uploads the same files every time.
+    pub async fn attach_receipt_with_rng(
+        &self,
+        merchant: &Merchant,
+        transaction: &Transaction,
+        format: ReceiptFormat,
+        rng: &mut StdRng,
+    ) -> Result<Receipt> {
+        let synthetic = receipt_generator::generate_receipt(transaction, format, rng);
+        let request = ReceiptUploadRequest {
+            file_data: synthetic.to_data_uri(),
+            file_name: synthetic.file_name.clone(),
+            update_status: None,
+        };
+        
+        match self.api_client.upload_receipt(&merchant.credentials(), &transaction.id, &request).await {
+            Ok(receipt) => {
+                let stored = receipt.clone();
+                if self.storage.update_transaction(&transaction.order_id, |history| history.receipts.push(stored)) {
+                    self.storage.save_transactions().await?;
                 }
-                Err(e) => {
-                    error!("Failed to fetch transaction details: {}", e);
+                
+                self.stats_service.record_receipt_upload(merchant.id);
+                
+                info!("Receipt {} uploaded for transaction {}", receipt.file_name, transaction.id);
+                
+                Ok(receipt)
+            }
+            Err(e) => {
+                error!("Failed to upload receipt for transaction {}: {}", transaction.id, e);
+                self.stats_service.record_receipt_failure(merchant.id, &e);
+                Err(e.into())
+            }
+        }
+    }
+    
+    pub async fn list_receipts(&self, merchant: &Merchant, transaction_id: &str) -> Result<Vec<Receipt>> {
+        Ok(self.api_client.get_receipts(&merchant.credentials(), transaction_id).await?)
+    }
+    
+    /// Opens a dispute on a deal with a synthetic receipt as evidence.
+    pub async fn open_deal_dispute(&self, merchant: &Merchant, transaction: &Transaction, message: &str) -> Result<Dispute> {
+        self.open_deal_dispute_with_rng(merchant, transaction, message, &mut StdRng::from_entropy()).await
+    }
+    
+    /// Like `open_deal_dispute`, drawing the evidence image from `rng`.
+    pub async fn open_deal_dispute_with_rng(
+        &self,
+        merchant: &Merchant,
+        transaction: &Transaction,
+        message: &str,
+        rng: &mut StdRng,
+    ) -> Result<Dispute> {
+        let evidence = receipt_generator::generate_receipt(transaction, ReceiptFormat::Png, rng);
+        
+        let result = self.api_client
+            .open_dispute(&merchant.credentials(), DisputeKind::Deal, &transaction.id, message, &[evidence])
+            .await;
+            
+        match result {
+            Ok(dispute) => {
+                let dispute_id = dispute.id.clone();
+                if self.storage.update_transaction(&transaction.order_id, |history| history.dispute_id = Some(dispute_id)) {
+                    self.storage.save_transactions().await?;
                 }
+                
+                self.stats_service.record_dispute_opened(merchant.id);
+                info!("Dispute {} opened for deal {}", dispute.id, transaction.id);
+                
+                Ok(dispute)
+            }
+            Err(e) => {
+                error!("Failed to open dispute for deal {}: {}", transaction.id, e);
+                self.stats_service.record_dispute_failure(merchant.id, &e);
+                Err(e.into())
+            }
+        }
+    }
+    
+    pub async fn open_payout_dispute(&self, merchant: &Merchant, payout_id: &str, message: &str) -> Result<Dispute> {
+        let result = self.api_client
+            .open_dispute(&merchant.credentials(), DisputeKind::Payout, payout_id, message, &[])
+            .await;
+            
+        match result {
+            Ok(dispute) => {
+                self.stats_service.record_dispute_opened(merchant.id);
+                info!("Dispute {} opened for payout {}", dispute.id, payout_id);
+                Ok(dispute)
+            }
+            Err(e) => {
+                error!("Failed to open dispute for payout {}: {}", payout_id, e);
+                self.stats_service.record_dispute_failure(merchant.id, &e);
+                Err(e.into())
+            }
+        }
+    }
+    
+    pub async fn send_dispute_message(
+        &self,
+        merchant: &Merchant,
+        kind: DisputeKind,
+        dispute_id: &str,
+        message: &str,
+    ) -> Result<DisputeMessage> {
+        match self.api_client.send_dispute_message(&merchant.credentials(), kind, dispute_id, message, &[]).await {
+            Ok(sent) => {
+                self.stats_service.record_dispute_message(merchant.id);
+                Ok(sent)
+            }
+            Err(e) => {
+                error!("Failed to send message in dispute {}: {}", dispute_id, e);
+                self.stats_service.record_dispute_failure(merchant.id, &e);
+                Err(e.into())
             }
         }
+    }
+    
+    pub async fn get_dispute(&self, merchant: &Merchant, kind: DisputeKind, dispute_id: &str) -> Result<Dispute> {
+        Ok(self.api_client.get_dispute(&merchant.credentials(), kind, dispute_id).await?)
+    }
+    
+    pub async fn list_disputes(&self, merchant: &Merchant, kind: DisputeKind) -> Result<Vec<Dispute>> {
+        Ok(self.api_client.get_disputes(&merchant.credentials(), kind).await?)
+    }
+    
+    /// Logs a received callback and routes it to the merchant that created the transaction.
+    /// The backend sends the order id as `id`, but transaction ids are matched too.
+    pub async fn route_callback(&self, mut received: ReceivedCallback) -> Result<Option<Uuid>> {
+        let history = received
+            .callback
+            .as_ref()
+            .and_then(|callback| self.storage.find_transaction(&callback.id));
+        
+        if let (Some(merchant_id), Some(history)) = (received.merchant_id, &history) {
+            if merchant_id != history.merchant_id {
+                warn!(
+                    "Callback for {} posted to merchant {} but the transaction belongs to {}",
+                    history.transaction.order_id, merchant_id, history.merchant_id
+                );
+            }
+        }
+        
+        if let Some(history) = &history {
+            received.merchant_id = Some(history.merchant_id);
+            received.matched_order_id = Some(history.transaction.order_id.clone());
+        }
+        
+        if let Some(merchant) = received.merchant_id.and_then(|id| self.storage.get_merchant(&id)) {
+            received.signature = check_callback_signature(&merchant, &received);
+            if received.signature == CallbackSignature::Invalid {
+                warn!("Callback {} has an invalid {} signature", received.id, SIGNATURE_HEADER);
+            }
+        }
+        
+        self.storage.add_callback(received.clone());
+        self.schedule_callback_save();
+        
+        let Some(callback) = received.callback else {
+            warn!("Unparseable callback body: {}", received.raw_body);
+            return Ok(None);
+        };
+        
+        let Some(history) = history else {
+            warn!("Callback for unknown transaction {}: {:?}", callback.id, callback);
+            return Ok(None);
+        };
+        
+        self.handle_callback(history.merchant_id, &history.transaction.order_id, callback).await?;
+        
+        Ok(Some(history.merchant_id))
+    }
+    
+    pub async fn handle_callback(&self, merchant_id: Uuid, order_id: &str, callback: CallbackRequest) -> Result<()> {
+        info!("Handling callback for merchant {}: {:?}", merchant_id, callback);
+        
+        // Update statistics
+        self.stats_service.record_callback(merchant_id);
+        
+        let status = TransactionStatus::parse(&callback.status);
+        
+        if let Some(merchant) = self.storage.get_merchant(&merchant_id) {
+            self.catalog.check_status(&merchant.credentials(), &status).await;
+        }
+        
+        let mut changed = false;
+        self.storage.update_transaction(order_id, |history| {
+            history.transaction.callback_sent = true;
+            changed = history.apply_status(status.clone(), StatusSource::Callback);
+        });
+        
+        // Update status breakdown
+        if changed {
+            self.stats_service.update_status(&merchant_id, &callback.status);
+        }
+        
+        self.schedule_callback_save();
         
         Ok(())
     }
     
+    /// Writes the callback log, transactions and statistics once the current
+    /// burst of callbacks has passed, rather than once per callback.
+    fn schedule_callback_save(&self) {
+        if self.callback_save_pending.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        
+        let storage = self.storage.clone();
+        let stats_service = self.stats_service.clone();
+        let pending = self.callback_save_pending.clone();
+        
+        tokio::spawn(async move {
+            tokio::time::sleep(CALLBACK_SAVE_DELAY).await;
+            // Callbacks handled while saving schedule the next save
+            pending.store(false, Ordering::Release);
+            
+            let saved = async {
+                storage.save_callbacks().await?;
+                storage.save_transactions().await?;
+                persist_statistics(&storage, &stats_service).await
+            };
+            if let Err(e) = saved.await {
+                error!("Failed to save callback updates: {}", e);
+            }
+        });
+    }
+    
+    /// Records what the simulated buyer decided for an order in `band`.
+    pub fn record_buyer_decision(&self, merchant_id: Uuid, band: &str, decision: &BuyerDecision) {
+        self.stats_service.record_buyer_decision(merchant_id, band, decision);
+    }
+    
+    /// Records that a paid order's bank notification reached a device.
+    pub fn record_buyer_notification(&self, merchant_id: Uuid, band: &str) {
+        self.stats_service.record_buyer_notification(merchant_id, band);
+    }
+    
+    /// Starts the merchant's statistics from zero, e.g. before a scenario run.
+    pub fn reset_statistics(&self, merchant_id: Uuid) {
+        self.stats_service.initialize_merchant(merchant_id);
+    }
+    
+    /// Copies the live statistics into storage and writes them to disk.
+    pub async fn persist_statistics(&self) -> Result<()> {
+        persist_statistics(&self.storage, &self.stats_service).await
+    }
+    
     pub async fn export_merchant_data(
         &self,
         merchant_id: &Uuid,
@@ -244,8 +641,50 @@ impl MerchantService {
         Ok((history_path, stats_path))
     }
     
+    pub async fn export_callbacks(&self, merchant_id: &Uuid, export_dir: &std::path::PathBuf) -> Result<std::path::PathBuf> {
+        let path = self.storage.export_callbacks(merchant_id, export_dir).await?;
+        
+        info!("Exported callback log: {:?}", path);
+        
+        Ok(path)
+    }
+    
     pub async fn get_available_methods(&self, merchant: &Merchant) -> Result<Vec<Method>> {
         info!("Getting available methods for merchant {}", merchant.name);
-        self.api_client.get_methods(&merchant.api_key).await
+        self.catalog.methods(&merchant.credentials()).await
     }
-}
\ No newline at end of file
+}
+
+/// Verifies the x-api-token header of a callback against the merchant's private key.
+fn check_callback_signature(merchant: &Merchant, received: &ReceivedCallback) -> CallbackSignature {
+    let Some(private_key) = merchant.private_key.as_deref() else {
+        return CallbackSignature::NotChecked;
+    };
+    
+    match received.headers.get(SIGNATURE_HEADER) {
+        Some(token) if signature::verify(private_key, &received.raw_body, token) => CallbackSignature::Valid,
+        Some(_) => CallbackSignature::Invalid,
+        None => CallbackSignature::Missing,
+    }
+}
+
+/// Generates a recipient card number and bank for a synthetic payout.
+fn generate_payout_recipient(rng: &mut impl Rng) -> (String, String) {
+    const BANKS: [&str; 6] = ["SBERBANK", "TBANK", "VTB", "ALFABANK", "RAIFFEISEN", "OZONBANK"];
+    
+    let card: String = std::iter::once("2200".to_string())
+        .chain((0..12).map(|_| rng.gen_range(0..10).to_string()))
+        .collect();
+    let bank = BANKS[rng.gen_range(0..BANKS.len())].to_string();
+    
+    (card, bank)
+}
+
+/// Copies the live statistics into storage and writes them to disk.
+async fn persist_statistics(storage: &StorageService, stats_service: &StatisticsService) -> Result<()> {
+    for (merchant_id, stats) in stats_service.get_all_statistics() {
+        storage.update_statistics(merchant_id, stats);
+    }
+    
+    storage.save_statistics().await
+}
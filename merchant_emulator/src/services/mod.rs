@@ -1,13 +1,24 @@
 pub mod merchant_service;
+pub mod merchant_protocol;
+pub mod catalog_service;
 pub mod traffic_generator;
 pub mod storage_service;
 pub mod statistics_service;
+pub mod status_tracker;
+pub mod reconciliation_service;
+pub mod receipt_generator;
 pub mod device_notification_service;
 pub mod log_capture;
+pub mod scenario_runner;
 
 pub use merchant_service::MerchantService;
-pub use traffic_generator::TrafficGenerator;
+pub use merchant_protocol::Protocols;
+pub use catalog_service::CatalogService;
+pub use traffic_generator::{RateProgress, TrafficGenerator};
 pub use storage_service::StorageService;
 pub use statistics_service::StatisticsService;
+pub use status_tracker::StatusTracker;
+pub use reconciliation_service::ReconciliationService;
 pub use log_capture::{LogCaptureLayer, LogCaptureHandle};
-// pub use device_notification_service::DeviceNotificationService; // Not used yet
\ No newline at end of file
+pub use device_notification_service::DeviceNotificationService;
+pub use scenario_runner::ScenarioRunner;
\ No newline at end of file
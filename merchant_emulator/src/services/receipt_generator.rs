@@ -0,0 +1,192 @@
+use crate::models::{ReceiptFormat, Transaction};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Utc;
+use rand::Rng;
+
+/// A locally generated stand-in for a bank receipt. The files are valid
+/// PNG/PDF documents but carry no real payment data.
+#[derive(Debug, Clone)]
+pub struct SyntheticReceipt {
+    pub format: ReceiptFormat,
+    pub file_name: String,
+    pub bytes: Vec<u8>,
+}
+
+impl SyntheticReceipt {
+    /// Encodes the file the way the backend's receipt endpoint expects it.
+    pub fn to_data_uri(&self) -> String {
+        format!("data:{};base64,{}", self.format.mime_type(), STANDARD.encode(&self.bytes))
+    }
+}
+
+pub fn generate_receipt(transaction: &Transaction, format: ReceiptFormat, rng: &mut impl Rng) -> SyntheticReceipt {
+    let bytes = match format {
+        ReceiptFormat::Png => receipt_png(rng),
+        ReceiptFormat::Pdf => receipt_pdf(transaction),
+    };
+    
+    SyntheticReceipt {
+        format,
+        file_name: format!("receipt_{}.{}", transaction.order_id, format.extension()),
+        bytes,
+    }
+}
+
+const PNG_WIDTH: usize = 240;
+const PNG_HEIGHT: usize = 360;
+
+/// Grayscale receipt-like image: a header bar and rows of "text" blocks.
+fn receipt_png(rng: &mut impl Rng) -> Vec<u8> {
+    let mut pixels = vec![0xFFu8; PNG_WIDTH * PNG_HEIGHT];
+    
+    // Header bar
+    for y in 16..40 {
+        for x in 16..PNG_WIDTH - 16 {
+            pixels[y * PNG_WIDTH + x] = 0x30;
+        }
+    }
+    
+    // Text lines with random word widths
+    let mut y = 60;
+    while y + 8 < PNG_HEIGHT - 16 {
+        let mut x = 16;
+        while x < PNG_WIDTH - 40 {
+            let word = rng.gen_range(8..40).min(PNG_WIDTH - 16 - x);
+            let shade = rng.gen_range(0x20..0x80);
+            for row in y..y + 8 {
+                for col in x..x + word {
+                    pixels[row * PNG_WIDTH + col] = shade;
+                }
+            }
+            x += word + rng.gen_range(4..10);
+        }
+        y += rng.gen_range(16..24);
+    }
+    
+    // Each scanline is prefixed with filter type 0 (none)
+    let mut raw = Vec::with_capacity((PNG_WIDTH + 1) * PNG_HEIGHT);
+    for row in pixels.chunks(PNG_WIDTH) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+    
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&(PNG_WIDTH as u32).to_be_bytes());
+    ihdr.extend_from_slice(&(PNG_HEIGHT as u32).to_be_bytes());
+    ihdr.extend_from_slice(&[8, 0, 0, 0, 0]); // 8-bit grayscale, no interlace
+    
+    let mut png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+    png_chunk(&mut png, b"IHDR", &ihdr);
+    png_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+    png_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Wraps data in a zlib stream of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 0xFFFF;
+    
+    let mut out = vec![0x78, 0x01];
+    let blocks: Vec<&[u8]> = data.chunks(MAX_BLOCK).collect();
+    
+    for (i, block) in blocks.iter().enumerate() {
+        let is_last = i + 1 == blocks.len();
+        let len = block.len() as u16;
+        
+        out.push(is_last as u8);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+/// Single-page PDF listing the transaction details.
+fn receipt_pdf(transaction: &Transaction) -> Vec<u8> {
+    let bank = transaction
+        .requisites
+        .as_ref()
+        .map(|r| r.bank_type.as_str())
+        .unwrap_or("UNKNOWN");
+    
+    let lines = [
+        "Payment receipt".to_string(),
+        format!("Order: {}", transaction.order_id),
+        format!("Transaction: {}", transaction.id),
+        format!("Amount: {:.2} RUB", transaction.amount),
+        format!("Bank: {}", bank),
+        format!("Date: {}", Utc::now().format("%Y-%m-%d %H:%M:%S UTC")),
+    ];
+    
+    let mut content = String::from("BT /F1 12 Tf 50 780 Td 16 TL\n");
+    for line in &lines {
+        content.push_str(&format!("({}) Tj T*\n", escape_pdf_text(line)));
+    }
+    content.push_str("ET");
+    
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 595 842] /Contents 4 0 R /Resources << /Font << /F1 5 0 R >> >> >>".to_string(),
+        format!("<< /Length {} >>\nstream\n{}\nendstream", content.len(), content),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+    ];
+    
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.push_str(&format!("{} 0 obj\n{}\nendobj\n", i + 1, object));
+    }
+    
+    let xref_offset = pdf.len();
+    pdf.push_str(&format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1));
+    for offset in offsets {
+        pdf.push_str(&format!("{:010} 00000 n \n", offset));
+    }
+    pdf.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref_offset
+    ));
+    
+    pdf.into_bytes()
+}
+
+fn escape_pdf_text(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('(', "\\(").replace(')', "\\)")
+}
@@ -0,0 +1,378 @@
+use crate::models::*;
+use crate::services::StorageService;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use chrono::{DateTime, Duration, Utc};
+use tokio::fs;
+use uuid::Uuid;
+
+pub struct ReconciliationService {
+    storage: Arc<StorageService>,
+}
+
+impl ReconciliationService {
+    pub fn new(storage: Arc<StorageService>) -> Self {
+        Self { storage }
+    }
+    
+    pub fn report(&self, merchant_id: &Uuid) -> ReconciliationReport {
+        let transactions = self.storage.get_merchant_transactions(merchant_id);
+        let callbacks = self.storage.get_merchant_callbacks(merchant_id);
+        let unattributed = self.storage.get_unattributed_callbacks();
+        
+        reconcile(*merchant_id, &transactions, &callbacks, &unattributed)
+    }
+    
+    pub async fn export(&self, merchant_id: &Uuid, export_dir: &PathBuf) -> Result<PathBuf> {
+        fs::create_dir_all(export_dir).await?;
+        
+        let report = self.report(merchant_id);
+        let filename = format!("merchant_{}_reconciliation_{}.json",
+            merchant_id,
+            Utc::now().format("%Y%m%d_%H%M%S")
+        );
+        let path = export_dir.join(filename);
+        
+        let data = serde_json::to_string_pretty(&report)?;
+        fs::write(&path, data).await?;
+        
+        Ok(path)
+    }
+}
+
+/// How long after expiry an order may still wait for its callback before it
+/// counts as missing; the backend expires overdue deals on a timer.
+const CALLBACK_GRACE: Duration = Duration::minutes(5);
+
+/// Joins transactions with the callbacks received for them.
+/// Callbacks are matched on `matched_order_id`, which is set when the callback is routed.
+/// An order without a callback only counts as missing once it is known to be
+/// final or is past its expiry plus `CALLBACK_GRACE`; until then it is pending.
+/// `unattributed` are callbacks with no merchant at all, listed as they are
+/// since any merchant may have been their target.
+pub fn reconcile(
+    merchant_id: Uuid,
+    transactions: &[TransactionHistory],
+    callbacks: &[ReceivedCallback],
+    unattributed: &[ReceivedCallback],
+) -> ReconciliationReport {
+    let mut by_order: HashMap<&str, Vec<(&ReceivedCallback, &CallbackRequest)>> = HashMap::new();
+    let mut unknown_order_callbacks = Vec::new();
+    
+    for received in callbacks {
+        let Some(callback) = &received.callback else {
+            continue;
+        };
+        
+        match &received.matched_order_id {
+            Some(order_id) => by_order.entry(order_id.as_str()).or_default().push((received, callback)),
+            None => unknown_order_callbacks.push(unknown_order_callback(received, callback)),
+        }
+    }
+    
+    let unattributed_callbacks = unattributed
+        .iter()
+        .filter_map(|received| Some(unknown_order_callback(received, received.callback.as_ref()?)))
+        .collect();
+    
+    let now = Utc::now();
+    let created: Vec<_> = transactions.iter().filter(|h| h.error.is_none()).collect();
+    
+    let mut missing_callbacks = Vec::new();
+    let mut pending_callbacks = Vec::new();
+    let mut duplicate_callbacks = Vec::new();
+    let mut out_of_order = Vec::new();
+    let mut delays = Vec::new();
+    
+    for history in &created {
+        let order_id = history.transaction.order_id.as_str();
+        
+        let Some(received) = by_order.get_mut(order_id) else {
+            let missing = MissingCallback {
+                order_id: order_id.to_string(),
+                status: history.transaction.status.clone(),
+                created_at: history.response_time,
+            };
+            if history.transaction.status.is_final() || overdue(&history.transaction, now) {
+                missing_callbacks.push(missing);
+            } else {
+                pending_callbacks.push(missing);
+            }
+            continue;
+        };
+        
+        received.sort_by_key(|(r, _)| r.received_at);
+        
+        // Duplicates: the same status delivered more than once
+        let mut counts: Vec<(&str, usize)> = Vec::new();
+        for (_, callback) in received.iter() {
+            match counts.iter_mut().find(|(status, _)| *status == callback.status) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((&callback.status, 1)),
+            }
+        }
+        duplicate_callbacks.extend(counts.into_iter().filter(|(_, count)| *count > 1).map(|(status, count)| {
+            DuplicateCallback {
+                order_id: order_id.to_string(),
+                status: status.to_string(),
+                count,
+            }
+        }));
+        
+        // Out of order: a status from an earlier lifecycle stage than one already received
+        let mut latest: Option<(TransactionStatus, &str)> = None;
+        for (r, callback) in received.iter() {
+            delays.push((r.received_at - history.response_time).num_milliseconds());
+            
+            let Ok(status) = serde_json::from_value::<TransactionStatus>(
+                serde_json::Value::String(callback.status.clone())
+            ) else {
+                continue;
+            };
+            
+            match &latest {
+                Some((previous, previous_name)) if status.lifecycle_stage() < previous.lifecycle_stage() => {
+                    out_of_order.push(OutOfOrderCallback {
+                        order_id: order_id.to_string(),
+                        previous_status: previous_name.to_string(),
+                        status: callback.status.clone(),
+                        received_at: r.received_at,
+                    });
+                }
+                _ => latest = Some((status, &callback.status)),
+            }
+        }
+    }
+    
+    ReconciliationReport {
+        merchant_id,
+        generated_at: now,
+        total_transactions: created.len(),
+        total_callbacks: callbacks.len(),
+        missing_callbacks,
+        pending_callbacks,
+        duplicate_callbacks,
+        unknown_order_callbacks,
+        unattributed_callbacks,
+        out_of_order,
+        delay: delay_percentiles(delays),
+    }
+}
+
+fn unknown_order_callback(received: &ReceivedCallback, callback: &CallbackRequest) -> UnknownOrderCallback {
+    UnknownOrderCallback {
+        callback_id: received.id,
+        id: callback.id.clone(),
+        status: callback.status.clone(),
+        received_at: received.received_at,
+    }
+}
+
+/// Whether the order is past its expiry and the grace period after it.
+/// An expiry that does not parse never makes an order overdue.
+fn overdue(transaction: &Transaction, now: DateTime<Utc>) -> bool {
+    DateTime::parse_from_rfc3339(&transaction.expired_at)
+        .is_ok_and(|expired_at| expired_at.with_timezone(&Utc) + CALLBACK_GRACE < now)
+}
+
+fn delay_percentiles(mut delays: Vec<i64>) -> Option<CallbackDelay> {
+    if delays.is_empty() {
+        return None;
+    }
+    
+    delays.sort_unstable();
+    
+    // Nearest-rank percentile
+    let percentile = |p: f64| {
+        let rank = ((p / 100.0) * delays.len() as f64).ceil() as usize;
+        delays[rank.clamp(1, delays.len()) - 1]
+    };
+    
+    Some(CallbackDelay {
+        samples: delays.len(),
+        min_ms: delays[0],
+        p50_ms: percentile(50.0),
+        p90_ms: percentile(90.0),
+        p95_ms: percentile(95.0),
+        p99_ms: percentile(99.0),
+        max_ms: delays[delays.len() - 1],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    
+    fn history(order_id: &str, status: TransactionStatus, expired_at: DateTime<Utc>) -> TransactionHistory {
+        let created_at = Utc::now() - Duration::minutes(30);
+        TransactionHistory {
+            merchant_id: Uuid::nil(),
+            transaction: Transaction {
+                id: format!("tx-{}", order_id),
+                numeric_id: 1,
+                order_id: order_id.to_string(),
+                amount: 1000.0,
+                crypto: None,
+                status,
+                trader_id: None,
+                requisites: None,
+                created_at: created_at.to_rfc3339(),
+                updated_at: created_at.to_rfc3339(),
+                expired_at: expired_at.to_rfc3339(),
+                method: None,
+                is_mock: false,
+                callback_sent: false,
+                method_id: "method-c2c".to_string(),
+                rate: None,
+            },
+            request_time: created_at,
+            response_time: created_at,
+            request_body: TransactionRequest {
+                amount: 1000.0,
+                order_id: order_id.to_string(),
+                method_id: "method-c2c".to_string(),
+                rate: None,
+                expired_at: expired_at.to_rfc3339(),
+                user_ip: None,
+                user_id: None,
+                transaction_type: None,
+                callback_uri: None,
+                success_uri: None,
+                fail_uri: None,
+                is_mock: None,
+            },
+            response_status: 201,
+            response_body: None,
+            error: None,
+            canceled_at: None,
+            status_changes: Vec::new(),
+            receipts: Vec::new(),
+            dispute_id: None,
+            protocol: ProtocolKind::default(),
+        }
+    }
+    
+    /// A routed callback; `matched` is None for one that matched no order.
+    fn callback(id: &str, status: &str, matched: Option<&str>, seconds_in: i64) -> ReceivedCallback {
+        ReceivedCallback {
+            id: Uuid::new_v4(),
+            merchant_id: Some(Uuid::nil()),
+            path: "/callback".to_string(),
+            headers: BTreeMap::new(),
+            raw_body: String::new(),
+            callback: Some(CallbackRequest { id: id.to_string(), status: status.to_string() }),
+            parse_error: None,
+            matched_order_id: matched.map(String::from),
+            signature: CallbackSignature::NotChecked,
+            received_at: Utc::now() - Duration::minutes(30) + Duration::seconds(seconds_in),
+            outcome: CallbackOutcome::Respond(200),
+        }
+    }
+    
+    fn order_ids(callbacks: &[MissingCallback]) -> Vec<&str> {
+        callbacks.iter().map(|c| c.order_id.as_str()).collect()
+    }
+    
+    #[test]
+    fn only_final_or_overdue_orders_are_missing_callbacks() {
+        let later = Utc::now() + Duration::hours(1);
+        let transactions = vec![
+            history("ready", TransactionStatus::Ready, later),
+            history("canceled", TransactionStatus::Canceled, later),
+            history("in-flight", TransactionStatus::InProgress, later),
+            history("just-expired", TransactionStatus::InProgress, Utc::now() - Duration::minutes(1)),
+            history("overdue", TransactionStatus::InProgress, Utc::now() - Duration::minutes(10)),
+            history("answered", TransactionStatus::InProgress, later),
+        ];
+        let callbacks = vec![callback("answered", "READY", Some("answered"), 10)];
+        
+        let report = reconcile(Uuid::nil(), &transactions, &callbacks, &[]);
+        
+        assert_eq!(order_ids(&report.missing_callbacks), ["ready", "canceled", "overdue"]);
+        assert_eq!(order_ids(&report.pending_callbacks), ["in-flight", "just-expired"]);
+        assert_eq!(report.delay.as_ref().unwrap().samples, 1);
+    }
+    
+    #[test]
+    fn failed_creates_are_not_counted() {
+        let mut failed = history("failed", TransactionStatus::Canceled, Utc::now());
+        failed.error = Some("No requisites available".to_string());
+        let transactions = vec![failed, history("created", TransactionStatus::Ready, Utc::now())];
+        
+        let report = reconcile(Uuid::nil(), &transactions, &[], &[]);
+        
+        assert_eq!(report.total_transactions, 1);
+        assert_eq!(order_ids(&report.missing_callbacks), ["created"]);
+    }
+    
+    #[test]
+    fn repeated_statuses_are_duplicates() {
+        let transactions = vec![history("order", TransactionStatus::Ready, Utc::now())];
+        let callbacks = vec![
+            callback("order", "IN_PROGRESS", Some("order"), 1),
+            callback("order", "READY", Some("order"), 2),
+            callback("order", "READY", Some("order"), 3),
+            callback("order", "READY", Some("order"), 4),
+        ];
+        
+        let report = reconcile(Uuid::nil(), &transactions, &callbacks, &[]);
+        
+        assert_eq!(report.duplicate_callbacks.len(), 1);
+        assert_eq!(report.duplicate_callbacks[0].status, "READY");
+        assert_eq!(report.duplicate_callbacks[0].count, 3);
+        assert!(report.out_of_order.is_empty());
+        assert!(report.missing_callbacks.is_empty());
+    }
+    
+    #[test]
+    fn earlier_stages_after_later_ones_are_out_of_order() {
+        let transactions = vec![history("order", TransactionStatus::Ready, Utc::now())];
+        // Sorted by arrival, so the list order does not matter
+        let callbacks = vec![
+            callback("order", "IN_PROGRESS", Some("order"), 5),
+            callback("order", "READY", Some("order"), 2),
+            callback("order", "CREATED", Some("order"), 1),
+        ];
+        
+        let report = reconcile(Uuid::nil(), &transactions, &callbacks, &[]);
+        
+        assert_eq!(report.out_of_order.len(), 1);
+        assert_eq!(report.out_of_order[0].previous_status, "READY");
+        assert_eq!(report.out_of_order[0].status, "IN_PROGRESS");
+        assert!(report.duplicate_callbacks.is_empty());
+    }
+    
+    #[test]
+    fn unmatched_callbacks_are_for_unknown_orders() {
+        let transactions = vec![history("order", TransactionStatus::InProgress, Utc::now() + Duration::hours(1))];
+        let mut unparsed = callback("junk", "READY", None, 2);
+        unparsed.callback = None;
+        let callbacks = vec![callback("someone-else", "READY", None, 1), unparsed];
+        
+        let report = reconcile(Uuid::nil(), &transactions, &callbacks, &[]);
+        
+        assert_eq!(report.unknown_order_callbacks.len(), 1);
+        assert_eq!(report.unknown_order_callbacks[0].id, "someone-else");
+        assert_eq!(report.total_callbacks, 2);
+        assert_eq!(order_ids(&report.pending_callbacks), ["order"]);
+    }
+    
+    #[test]
+    fn callbacks_without_a_merchant_are_unattributed() {
+        let mut stray = callback("stray", "READY", None, 1);
+        stray.merchant_id = None;
+        let mut unparsed = callback("junk", "READY", None, 2);
+        unparsed.merchant_id = None;
+        unparsed.callback = None;
+        
+        let report = reconcile(Uuid::nil(), &[], &[], &[stray, unparsed]);
+        
+        assert_eq!(report.unattributed_callbacks.len(), 1);
+        assert_eq!(report.unattributed_callbacks[0].id, "stray");
+        assert!(report.unknown_order_callbacks.is_empty());
+        assert_eq!(report.total_callbacks, 0);
+    }
+}
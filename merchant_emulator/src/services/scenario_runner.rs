@@ -0,0 +1,292 @@
+use crate::device::{DeviceApiClient, DeviceManager};
+use crate::models::*;
+use crate::services::reconciliation_service::reconcile;
+use crate::services::{MerchantService, StorageService, TrafficGenerator};
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::fs;
+use tracing::{info, warn};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Runs a scenario file against the configured backend and writes a results bundle.
+pub struct ScenarioRunner {
+    merchant_service: Arc<MerchantService>,
+    traffic_generator: Arc<TrafficGenerator>,
+    storage: Arc<StorageService>,
+    device_manager: Arc<DeviceManager>,
+    device_api_client: Arc<DeviceApiClient>,
+}
+
+/// A scenario merchant ready to start.
+struct Prepared {
+    merchant: Merchant,
+    method_id: String,
+    seed: u64,
+}
+
+impl ScenarioRunner {
+    pub fn new(
+        merchant_service: Arc<MerchantService>,
+        traffic_generator: Arc<TrafficGenerator>,
+        storage: Arc<StorageService>,
+        device_manager: Arc<DeviceManager>,
+        device_api_client: Arc<DeviceApiClient>,
+    ) -> Self {
+        Self {
+            merchant_service,
+            traffic_generator,
+            storage,
+            device_manager,
+            device_api_client,
+        }
+    }
+    
+    pub async fn load(path: &Path) -> Result<Scenario> {
+        let yaml = fs::read_to_string(path)
+            .await
+            .with_context(|| format!("Failed to read scenario {:?}", path))?;
+        Scenario::from_yaml(&yaml).map_err(|e| anyhow!("Invalid scenario {:?}: {}", path, e))
+    }
+    
+    /// Connects the devices, runs every merchant until it stops or the
+    /// scenario's duration ends, then writes the bundle under `export_dir`.
+    pub async fn run(&self, scenario: &Scenario, export_dir: &Path) -> Result<ScenarioResult> {
+        scenario.validate().map_err(|e| anyhow!("Invalid scenario: {}", e))?;
+        info!("Running scenario {}", scenario.name);
+        
+        let devices_connected = self.connect_devices(&scenario.devices).await?;
+        
+        let mut seeds = scenario.seed.map(StdRng::seed_from_u64);
+        let mut prepared = Vec::new();
+        for spec in &scenario.merchants {
+            // Drawn for every merchant, so a merchant's own seed does not shift the others
+            let derived = seeds.as_mut().map(|rng| rng.gen::<u64>());
+            let seed = spec.traffic.seed.or(derived).unwrap_or_else(rand::random);
+            prepared.push(self.prepare_merchant(spec, seed).await?);
+        }
+        
+        let started_at = Utc::now();
+        let started = Instant::now();
+        for run in &prepared {
+            self.traffic_generator
+                .start_traffic(run.merchant.clone(), run.method_id.clone(), true)
+                .await
+                .with_context(|| format!("Failed to start traffic for {}", run.merchant.name))?;
+        }
+        
+        let deadline = scenario.duration_secs.map(Duration::from_secs);
+        let mut stopped_early = false;
+        loop {
+            let mut running = Vec::new();
+            for run in &prepared {
+                if self.traffic_generator.is_running(&run.merchant.id).await {
+                    running.push(run.merchant.id);
+                }
+            }
+            if running.is_empty() {
+                break;
+            }
+            if deadline.is_some_and(|deadline| started.elapsed() >= deadline) {
+                info!("Scenario duration reached, stopping {} merchants", running.len());
+                for merchant_id in &running {
+                    self.traffic_generator.stop_traffic(merchant_id).await?;
+                }
+                stopped_early = true;
+                break;
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+        
+        info!("Traffic finished, waiting {}s for callbacks to settle", scenario.settle_secs);
+        tokio::time::sleep(Duration::from_secs(scenario.settle_secs)).await;
+        let finished_at = Utc::now();
+        
+        self.merchant_service.persist_statistics().await?;
+        self.storage.save_transactions().await?;
+        self.storage.save_payouts().await?;
+        self.storage.save_callbacks().await?;
+        
+        let bundle_dir = export_dir.join("scenarios").join(format!(
+            "{}_{}",
+            file_name(&scenario.name),
+            started_at.format("%Y%m%d_%H%M%S")
+        ));
+        fs::create_dir_all(&bundle_dir).await?;
+        
+        // The resolved scenario, with every merchant's seed filled in, replays this run
+        let mut resolved = scenario.clone();
+        for (spec, run) in resolved.merchants.iter_mut().zip(&prepared) {
+            spec.traffic.seed = Some(run.seed);
+            spec.method = Some(run.method_id.clone());
+        }
+        let yaml = resolved.to_yaml().map_err(|e| anyhow!("Failed to write scenario: {}", e))?;
+        fs::write(bundle_dir.join("scenario.yaml"), yaml).await?;
+        
+        let mut merchants = Vec::new();
+        for run in &prepared {
+            merchants.push(self.write_merchant_results(run, started_at, &bundle_dir).await?);
+        }
+        
+        let result = ScenarioResult {
+            scenario: scenario.name.clone(),
+            started_at,
+            finished_at,
+            stopped_early,
+            devices_connected,
+            merchants,
+            bundle_dir: bundle_dir.clone(),
+        };
+        write_json(&bundle_dir.join("summary.json"), &result).await?;
+        
+        info!("Scenario {} finished, results in {:?}", scenario.name, bundle_dir);
+        
+        Ok(result)
+    }
+    
+    /// Connects each device with its code, reusing an emulated device of the same name.
+    async fn connect_devices(&self, devices: &[ScenarioDevice]) -> Result<usize> {
+        for spec in devices {
+            let existing = self.device_manager.get_all_devices().into_iter().find(|d| d.name == spec.name);
+            let device = match existing {
+                Some(device) => device,
+                None => self.device_manager.create_device(spec.name.clone()).await?,
+            };
+            self.device_manager
+                .update_device_status(&device.id, spec.battery_level, spec.network_info.clone())
+                .await?;
+            self.device_manager
+                .connect_with_code(&self.device_api_client, &device.id, spec.device_code.clone())
+                .await
+                .with_context(|| format!("Failed to connect device {}", spec.name))?;
+            if let Some(trader_id) = &spec.trader_id {
+                self.device_manager.link_device_to_trader(&device.id, trader_id).await?;
+            }
+            info!("Connected device {}", spec.name);
+        }
+        
+        Ok(devices.len())
+    }
+    
+    /// Finds or creates the merchant, applies the scenario's settings and
+    /// starts its statistics from zero.
+    async fn prepare_merchant(&self, spec: &ScenarioMerchant, seed: u64) -> Result<Prepared> {
+        let existing = self.merchant_service
+            .get_all_merchants()
+            .into_iter()
+            .find(|m| m.name == spec.name && m.api_key == spec.api_key);
+        let mut merchant = match existing {
+            Some(merchant) => merchant,
+            None => self.merchant_service
+                .create_merchant(spec.name.clone(), spec.api_key.clone(), spec.private_key.clone())
+                .await
+                .with_context(|| format!("Failed to create merchant {}", spec.name))?,
+        };
+        
+        merchant.private_key = spec.private_key.clone();
+        merchant.protocol = spec.protocol;
+        merchant.wellbit = spec.wellbit.clone();
+        merchant.liquidity_percentage = spec.liquidity_percentage;
+        merchant.callback_url = spec
+            .callback_url
+            .as_ref()
+            .map(|url| url.replace("{merchant_id}", &merchant.id.to_string()));
+        merchant.callback_behavior = spec.callback_behavior.clone();
+        merchant.traffic_config = TrafficConfig {
+            enabled: true,
+            created_count: 0,
+            seed: Some(seed),
+            ..spec.traffic.clone()
+        };
+        self.merchant_service.update_merchant(merchant.clone()).await?;
+        self.merchant_service.reset_statistics(merchant.id);
+        
+        let method_id = match &spec.method {
+            Some(method) => method.clone(),
+            None => {
+                let methods = self.merchant_service.get_available_methods(&merchant).await?;
+                match methods.first() {
+                    Some(method) => method.id.clone(),
+                    None => return Err(anyhow!("No payment methods available for merchant {}", spec.name)),
+                }
+            }
+        };
+        
+        Ok(Prepared { merchant, method_id, seed })
+    }
+    
+    /// Writes what this run produced for one merchant into its own directory.
+    async fn write_merchant_results(
+        &self,
+        run: &Prepared,
+        started_at: chrono::DateTime<Utc>,
+        bundle_dir: &Path,
+    ) -> Result<ScenarioMerchantResult> {
+        let merchant_id = run.merchant.id;
+        let transactions: Vec<_> = self.storage
+            .get_merchant_transactions(&merchant_id)
+            .into_iter()
+            .filter(|t| t.request_time >= started_at)
+            .collect();
+        let payouts: Vec<_> = self.storage
+            .get_merchant_payouts(&merchant_id)
+            .into_iter()
+            .filter(|p| p.request_time >= started_at)
+            .collect();
+        let callbacks: Vec<_> = self.storage
+            .get_merchant_callbacks(&merchant_id)
+            .into_iter()
+            .filter(|c| c.received_at >= started_at)
+            .collect();
+        let unattributed: Vec<_> = self.storage
+            .get_unattributed_callbacks()
+            .into_iter()
+            .filter(|c| c.received_at >= started_at)
+            .collect();
+        let reconciliation = reconcile(merchant_id, &transactions, &callbacks, &unattributed);
+        let statistics = self.storage
+            .get_statistics(&merchant_id)
+            .ok_or_else(|| anyhow!("No statistics found for merchant {}", run.merchant.name))?;
+        
+        let dir = bundle_dir.join(file_name(&run.merchant.name));
+        fs::create_dir_all(&dir).await?;
+        write_json(&dir.join("transactions.json"), &transactions).await?;
+        write_json(&dir.join("payouts.json"), &payouts).await?;
+        write_json(&dir.join("callbacks.json"), &callbacks).await?;
+        write_json(&dir.join("reconciliation.json"), &reconciliation).await?;
+        write_json(&dir.join("statistics.json"), &statistics).await?;
+        
+        if !reconciliation.missing_callbacks.is_empty() {
+            warn!("{} orders of {} got no callback", reconciliation.missing_callbacks.len(), run.merchant.name);
+        }
+        
+        Ok(ScenarioMerchantResult {
+            name: run.merchant.name.clone(),
+            merchant_id,
+            method_id: run.method_id.clone(),
+            seed: run.seed,
+            transactions: transactions.len(),
+            payouts: payouts.len(),
+            callbacks: callbacks.len(),
+            missing_callbacks: reconciliation.missing_callbacks.len(),
+            statistics,
+        })
+    }
+}
+
+async fn write_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<()> {
+    fs::write(path, serde_json::to_string_pretty(value)?).await?;
+    Ok(())
+}
+
+/// Keeps names usable as directory names on every platform.
+fn file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
\ No newline at end of file
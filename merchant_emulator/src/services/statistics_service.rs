@@ -1,4 +1,5 @@
-use crate::models::{Statistics, TransactionStatus};
+use crate::api::ApiError;
+use crate::models::{ApiOperation, BuyerDecision, PayoutStatus, Statistics, TransactionStatus};
 use std::collections::HashMap;
 use std::sync::Arc;
 use parking_lot::RwLock;
@@ -6,12 +7,32 @@ use uuid::Uuid;
 
 pub struct StatisticsService {
     stats: Arc<RwLock<HashMap<Uuid, Statistics>>>,
+    payout_statuses: RwLock<HashMap<String, PayoutStatus>>, // Status each payout was last counted under
+}
+
+impl Default for StatisticsService {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl StatisticsService {
     pub fn new() -> Self {
         Self {
             stats: Arc::new(RwLock::new(HashMap::new())),
+            payout_statuses: RwLock::new(HashMap::new()),
+        }
+    }
+    
+    /// Restores statistics persisted by a previous session.
+    pub fn load(&self, stats: HashMap<Uuid, Statistics>) {
+        self.stats.write().extend(stats);
+    }
+    
+    /// Initializes statistics for merchants that have none yet.
+    pub fn ensure_merchant(&self, merchant_id: Uuid) {
+        if !self.stats.read().contains_key(&merchant_id) {
+            self.initialize_merchant(merchant_id);
         }
     }
     
@@ -25,8 +46,24 @@ impl StatisticsService {
             callbacks_received: 0,
             liquid_transactions: 0,
             non_liquid_transactions: 0,
+            cancelled_transactions: 0,
+            failed_cancellations: 0,
+            payouts_created: 0,
+            payouts_failed: 0,
+            receipts_uploaded: 0,
+            receipts_failed: 0,
+            disputes_opened: 0,
+            disputes_failed: 0,
+            dispute_messages_sent: 0,
+            retry_attempts: 0,
+            retries_recovered: 0,
+            total_payout_amount: 0.0,
             error_breakdown: HashMap::new(),
             status_breakdown: HashMap::new(),
+            payout_status_breakdown: HashMap::new(),
+            retry_breakdown: HashMap::new(),
+            unknown_values: HashMap::new(),
+            conversion_breakdown: HashMap::new(),
         };
         
         self.stats.write().insert(merchant_id, stats);
@@ -44,33 +81,129 @@ impl StatisticsService {
         }
     }
     
-    pub fn record_failure(&self, merchant_id: Uuid, error: &str) {
+    pub fn record_failure(&self, merchant_id: Uuid, error: &ApiError) {
         let mut stats_map = self.stats.write();
         if let Some(stats) = stats_map.get_mut(&merchant_id) {
             stats.total_requests += 1;
             stats.failed_requests += 1;
             
-            // Categorize error
-            let error_category = if error.contains("400") || error.contains("Bad Request") {
-                "Bad Request"
-            } else if error.contains("401") || error.contains("Unauthorized") {
-                "Unauthorized"
-            } else if error.contains("403") || error.contains("Forbidden") {
-                "Forbidden"
-            } else if error.contains("404") || error.contains("Not Found") {
-                "Not Found"
-            } else if error.contains("500") || error.contains("Internal Server Error") {
-                "Internal Server Error"
-            } else if error.contains("timeout") || error.contains("Timeout") {
-                "Timeout"
-            } else {
-                "Other"
-            };
+            *stats.error_breakdown.entry(error.category().to_string()).or_insert(0) += 1;
+        }
+    }
+    
+    pub fn record_cancellation(&self, merchant_id: Uuid) {
+        let mut stats_map = self.stats.write();
+        if let Some(stats) = stats_map.get_mut(&merchant_id) {
+            stats.cancelled_transactions += 1;
+        }
+    }
+    
+    pub fn record_cancellation_failure(&self, merchant_id: Uuid) {
+        let mut stats_map = self.stats.write();
+        if let Some(stats) = stats_map.get_mut(&merchant_id) {
+            stats.failed_cancellations += 1;
+        }
+    }
+    
+    pub fn record_payout_created(&self, merchant_id: Uuid, amount: f64, payout_id: &str, status: &PayoutStatus) {
+        let mut stats_map = self.stats.write();
+        if let Some(stats) = stats_map.get_mut(&merchant_id) {
+            stats.payouts_created += 1;
+            stats.total_payout_amount += amount;
+            
+            let status_str = format!("{:?}", status);
+            *stats.payout_status_breakdown.entry(status_str).or_insert(0) += 1;
+        }
+        
+        self.payout_statuses.write().insert(payout_id.to_string(), status.clone());
+    }
+    
+    pub fn record_payout_failure(&self, merchant_id: Uuid, error: &ApiError) {
+        let mut stats_map = self.stats.write();
+        if let Some(stats) = stats_map.get_mut(&merchant_id) {
+            stats.payouts_failed += 1;
+            
+            *stats.error_breakdown.entry(format!("Payout: {}", error.category())).or_insert(0) += 1;
+        }
+    }
+    
+    pub fn record_receipt_upload(&self, merchant_id: Uuid) {
+        let mut stats_map = self.stats.write();
+        if let Some(stats) = stats_map.get_mut(&merchant_id) {
+            stats.receipts_uploaded += 1;
+        }
+    }
+    
+    pub fn record_receipt_failure(&self, merchant_id: Uuid, error: &ApiError) {
+        let mut stats_map = self.stats.write();
+        if let Some(stats) = stats_map.get_mut(&merchant_id) {
+            stats.receipts_failed += 1;
+            
+            *stats.error_breakdown.entry(format!("Receipt: {}", error.category())).or_insert(0) += 1;
+        }
+    }
+    
+    pub fn record_dispute_opened(&self, merchant_id: Uuid) {
+        let mut stats_map = self.stats.write();
+        if let Some(stats) = stats_map.get_mut(&merchant_id) {
+            stats.disputes_opened += 1;
+        }
+    }
+    
+    pub fn record_dispute_message(&self, merchant_id: Uuid) {
+        let mut stats_map = self.stats.write();
+        if let Some(stats) = stats_map.get_mut(&merchant_id) {
+            stats.dispute_messages_sent += 1;
+        }
+    }
+    
+    pub fn record_dispute_failure(&self, merchant_id: Uuid, error: &ApiError) {
+        let mut stats_map = self.stats.write();
+        if let Some(stats) = stats_map.get_mut(&merchant_id) {
+            stats.disputes_failed += 1;
             
-            *stats.error_breakdown.entry(error_category.to_string()).or_insert(0) += 1;
+            *stats.error_breakdown.entry(format!("Dispute: {}", error.category())).or_insert(0) += 1;
         }
     }
     
+    pub fn record_retry(&self, merchant_id: Uuid, operation: ApiOperation, reason: &str) {
+        let mut stats_map = self.stats.write();
+        if let Some(stats) = stats_map.get_mut(&merchant_id) {
+            stats.retry_attempts += 1;
+            *stats.retry_breakdown.entry(format!("{:?}: {}", operation, reason)).or_insert(0) += 1;
+        }
+    }
+    
+    pub fn record_retry_recovered(&self, merchant_id: Uuid) {
+        let mut stats_map = self.stats.write();
+        if let Some(stats) = stats_map.get_mut(&merchant_id) {
+            stats.retries_recovered += 1;
+        }
+    }
+    
+    pub fn record_unknown_value(&self, merchant_id: Uuid, value: &str) {
+        let mut stats_map = self.stats.write();
+        if let Some(stats) = stats_map.get_mut(&merchant_id) {
+            *stats.unknown_values.entry(value.to_string()).or_insert(0) += 1;
+        }
+    }
+    
+    /// Counts a payout's new status. A status the payout was already counted
+    /// under is ignored; returns whether it changed.
+    pub fn update_payout_status(&self, merchant_id: &Uuid, payout_id: &str, status: &PayoutStatus) -> bool {
+        let previous = self.payout_statuses.write().insert(payout_id.to_string(), status.clone());
+        if previous.as_ref() == Some(status) {
+            return false;
+        }
+        
+        let mut stats_map = self.stats.write();
+        if let Some(stats) = stats_map.get_mut(merchant_id) {
+            let status_str = format!("{:?}", status);
+            *stats.payout_status_breakdown.entry(status_str).or_insert(0) += 1;
+        }
+        true
+    }
+    
     pub fn record_callback(&self, merchant_id: Uuid) {
         let mut stats_map = self.stats.write();
         if let Some(stats) = stats_map.get_mut(&merchant_id) {
@@ -92,6 +225,26 @@ impl StatisticsService {
         }
     }
     
+    pub fn record_buyer_decision(&self, merchant_id: Uuid, band: &str, decision: &BuyerDecision) {
+        let mut stats_map = self.stats.write();
+        if let Some(stats) = stats_map.get_mut(&merchant_id) {
+            let band_stats = stats.conversion_breakdown.entry(band.to_string()).or_default();
+            band_stats.orders += 1;
+            match decision {
+                BuyerDecision::Pay { .. } => band_stats.paid += 1,
+                BuyerDecision::Cancel { .. } => band_stats.cancelled += 1,
+                BuyerDecision::Abandon => band_stats.abandoned += 1,
+            }
+        }
+    }
+    
+    pub fn record_buyer_notification(&self, merchant_id: Uuid, band: &str) {
+        let mut stats_map = self.stats.write();
+        if let Some(stats) = stats_map.get_mut(&merchant_id) {
+            stats.conversion_breakdown.entry(band.to_string()).or_default().notified += 1;
+        }
+    }
+    
     pub fn update_status(&self, merchant_id: &Uuid, status: &str) {
         let mut stats_map = self.stats.write();
         if let Some(stats) = stats_map.get_mut(merchant_id) {
@@ -106,4 +259,25 @@ impl StatisticsService {
     pub fn get_all_statistics(&self) -> HashMap<Uuid, Statistics> {
         self.stats.read().clone()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn repeated_payout_statuses_are_counted_once() {
+        let service = StatisticsService::new();
+        let merchant_id = Uuid::new_v4();
+        service.initialize_merchant(merchant_id);
+        
+        service.record_payout_created(merchant_id, 500.0, "payout-1", &PayoutStatus::Created);
+        assert!(!service.update_payout_status(&merchant_id, "payout-1", &PayoutStatus::Created));
+        assert!(service.update_payout_status(&merchant_id, "payout-1", &PayoutStatus::Completed));
+        assert!(!service.update_payout_status(&merchant_id, "payout-1", &PayoutStatus::Completed));
+        
+        let breakdown = service.get_statistics(&merchant_id).unwrap().payout_status_breakdown;
+        assert_eq!(breakdown.get("Created"), Some(&1));
+        assert_eq!(breakdown.get("Completed"), Some(&1));
+    }
+}
@@ -0,0 +1,157 @@
+use crate::models::*;
+use crate::services::{CatalogService, Protocols, StorageService, StatisticsService};
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::time::{sleep, Duration};
+use tracing::{info, warn, debug};
+
+const INITIAL_DELAY: Duration = Duration::from_secs(2);
+const MAX_DELAY: Duration = Duration::from_secs(60);
+const MAX_CONSECUTIVE_FAILURES: u32 = 10;
+// Keep polling a little past expired_at so the EXPIRED transition is observed
+const EXPIRY_GRACE_MINUTES: i64 = 5;
+
+/// Polls open transactions until they reach a final status, so lifecycle
+/// data is collected even when callbacks never arrive.
+pub struct StatusTracker {
+    protocols: Arc<Protocols>,
+    storage: Arc<StorageService>,
+    stats_service: Arc<StatisticsService>,
+    catalog: Arc<CatalogService>,
+    tracked: Arc<RwLock<HashSet<String>>>,
+}
+
+impl StatusTracker {
+    pub fn new(
+        protocols: Arc<Protocols>,
+        storage: Arc<StorageService>,
+        stats_service: Arc<StatisticsService>,
+        catalog: Arc<CatalogService>,
+    ) -> Self {
+        Self {
+            protocols,
+            storage,
+            stats_service,
+            catalog,
+            tracked: Arc::new(RwLock::new(HashSet::new())),
+        }
+    }
+    
+    /// Starts polling a transaction. Already tracked or settled transactions are
+    /// ignored; returns whether tracking started.
+    pub fn track(&self, credentials: &MerchantCredentials, history: &TransactionHistory) -> bool {
+        let transaction = &history.transaction;
+        
+        if transaction.id.is_empty() || transaction.status.is_final() {
+            return false;
+        }
+        
+        if !self.tracked.write().insert(transaction.id.clone()) {
+            return false;
+        }
+        
+        let deadline = DateTime::parse_from_rfc3339(&transaction.expired_at)
+            .map(|d| d.with_timezone(&Utc))
+            .unwrap_or_else(|_| history.request_time + chrono::Duration::hours(24))
+            + chrono::Duration::minutes(EXPIRY_GRACE_MINUTES);
+        
+        let protocols = self.protocols.clone();
+        let storage = self.storage.clone();
+        let stats_service = self.stats_service.clone();
+        let catalog = self.catalog.clone();
+        let tracked = self.tracked.clone();
+        let credentials = credentials.clone();
+        let merchant_id = history.merchant_id;
+        let transaction = transaction.clone();
+        let transaction_id = transaction.id.clone();
+        let order_id = transaction.order_id.clone();
+        
+        tokio::spawn(async move {
+            let mut delay = INITIAL_DELAY;
+            let mut failures = 0u32;
+            
+            loop {
+                sleep(delay).await;
+                
+                if Utc::now() > deadline {
+                    warn!("Stopped tracking transaction {}: no final status before expiry", transaction_id);
+                    break;
+                }
+                
+                let protocol = protocols.get(credentials.protocol);
+                match protocol.deposit_status(&credentials, &transaction).await {
+                    Ok(response) => {
+                        failures = 0;
+                        
+                        catalog.check_status(&credentials, &response.status).await;
+                        
+                        let mut changed = false;
+                        storage.update_transaction(&order_id, |history| {
+                            if history.apply_status(response.status.clone(), StatusSource::Poll) {
+                                history.transaction.updated_at = response.updated_at.clone();
+                                changed = true;
+                            }
+                        });
+                        
+                        if changed {
+                            info!("Transaction {} status changed to {:?}", transaction_id, response.status);
+                            
+                            stats_service.update_status(&merchant_id, &format!("{:?}", response.status));
+                            if let Err(e) = storage.save_transactions().await {
+                                warn!("Failed to save transactions: {}", e);
+                            }
+                            
+                            // Status moved, so further changes are likely soon
+                            delay = INITIAL_DELAY;
+                        } else {
+                            delay = (delay * 2).min(MAX_DELAY);
+                        }
+                        
+                        if response.status.is_final() {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        failures += 1;
+                        debug!("Status poll for {} failed ({}/{}): {}",
+                            transaction_id, failures, MAX_CONSECUTIVE_FAILURES, e);
+                        
+                        if failures >= MAX_CONSECUTIVE_FAILURES {
+                            warn!("Stopped tracking transaction {} after {} failed polls", transaction_id, failures);
+                            break;
+                        }
+                        
+                        delay = (delay * 2).min(MAX_DELAY);
+                    }
+                }
+            }
+            
+            tracked.write().remove(&transaction_id);
+        });
+        
+        true
+    }
+    
+    /// Resumes tracking of stored transactions that were still open at shutdown.
+    pub fn resume(&self, merchants: &[Merchant]) -> usize {
+        let mut resumed = 0;
+        
+        for merchant in merchants {
+            let credentials = merchant.credentials();
+            
+            for history in self.storage.get_merchant_transactions(&merchant.id) {
+                if history.error.is_none() && self.track(&credentials, &history) {
+                    resumed += 1;
+                }
+            }
+        }
+        
+        resumed
+    }
+    
+    pub fn tracked_count(&self) -> usize {
+        self.tracked.read().len()
+    }
+}
@@ -11,6 +11,8 @@ pub struct StorageService {
     data_dir: PathBuf,
     merchants: Arc<RwLock<HashMap<Uuid, Merchant>>>,
     transactions: Arc<RwLock<Vec<TransactionHistory>>>,
+    payouts: Arc<RwLock<Vec<PayoutHistory>>>,
+    callbacks: Arc<RwLock<Vec<ReceivedCallback>>>,
     statistics: Arc<RwLock<HashMap<Uuid, Statistics>>>,
 }
 
@@ -22,12 +24,16 @@ impl StorageService {
             data_dir: data_dir.clone(),
             merchants: Arc::new(RwLock::new(HashMap::new())),
             transactions: Arc::new(RwLock::new(Vec::new())),
+            payouts: Arc::new(RwLock::new(Vec::new())),
+            callbacks: Arc::new(RwLock::new(Vec::new())),
             statistics: Arc::new(RwLock::new(HashMap::new())),
         };
         
         // Load existing data
         service.load_merchants().await?;
         service.load_transactions().await?;
+        service.load_payouts().await?;
+        service.load_callbacks().await?;
         service.load_statistics().await?;
         
         Ok(service)
@@ -63,6 +69,26 @@ impl StorageService {
         Ok(())
     }
     
+    async fn load_payouts(&mut self) -> Result<()> {
+        let path = self.data_dir.join("payouts.json");
+        if path.exists() {
+            let data = fs::read_to_string(&path).await?;
+            let payouts: Vec<PayoutHistory> = serde_json::from_str(&data)?;
+            *self.payouts.write() = payouts;
+        }
+        Ok(())
+    }
+    
+    async fn load_callbacks(&mut self) -> Result<()> {
+        let path = self.data_dir.join("callbacks.json");
+        if path.exists() {
+            let data = fs::read_to_string(&path).await?;
+            let callbacks: Vec<ReceivedCallback> = serde_json::from_str(&data)?;
+            *self.callbacks.write() = callbacks;
+        }
+        Ok(())
+    }
+    
     async fn load_statistics(&mut self) -> Result<()> {
         let path = self.data_dir.join("statistics.json");
         if path.exists() {
@@ -86,12 +112,14 @@ impl StorageService {
     pub async fn save_merchants(&self) -> Result<()> {
         let path = self.data_dir.join("merchants.json");
         // Convert HashMap<Uuid, Merchant> to HashMap<String, Merchant> for JSON serialization
-        let merchants = self.merchants.read();
-        let merchants_for_json: HashMap<String, &Merchant> = merchants
-            .iter()
-            .map(|(k, v)| (k.to_string(), v))
-            .collect();
-        let data = serde_json::to_string_pretty(&merchants_for_json)?;
+        let data = {
+            let merchants = self.merchants.read();
+            let merchants_for_json: HashMap<String, &Merchant> = merchants
+                .iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect();
+            serde_json::to_string_pretty(&merchants_for_json)?
+        };
         fs::write(&path, data).await?;
         Ok(())
     }
@@ -103,15 +131,31 @@ impl StorageService {
         Ok(())
     }
     
+    pub async fn save_payouts(&self) -> Result<()> {
+        let path = self.data_dir.join("payouts.json");
+        let data = serde_json::to_string_pretty(&*self.payouts.read())?;
+        fs::write(&path, data).await?;
+        Ok(())
+    }
+    
+    pub async fn save_callbacks(&self) -> Result<()> {
+        let path = self.data_dir.join("callbacks.json");
+        let data = serde_json::to_string_pretty(&*self.callbacks.read())?;
+        fs::write(&path, data).await?;
+        Ok(())
+    }
+    
     pub async fn save_statistics(&self) -> Result<()> {
         let path = self.data_dir.join("statistics.json");
         // Convert HashMap<Uuid, Statistics> to HashMap<String, Statistics> for JSON serialization
-        let stats = self.statistics.read();
-        let stats_for_json: HashMap<String, &Statistics> = stats
-            .iter()
-            .map(|(k, v)| (k.to_string(), v))
-            .collect();
-        let data = serde_json::to_string_pretty(&stats_for_json)?;
+        let data = {
+            let stats = self.statistics.read();
+            let stats_for_json: HashMap<String, &Statistics> = stats
+                .iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect();
+            serde_json::to_string_pretty(&stats_for_json)?
+        };
         fs::write(&path, data).await?;
         Ok(())
     }
@@ -138,6 +182,84 @@ impl StorageService {
         self.transactions.write().push(history);
     }
     
+    pub fn update_transaction<F>(&self, order_id: &str, update: F) -> bool
+    where
+        F: FnOnce(&mut TransactionHistory),
+    {
+        let mut transactions = self.transactions.write();
+        match transactions.iter_mut().rev().find(|t| t.transaction.order_id == order_id) {
+            Some(history) => {
+                update(history);
+                true
+            }
+            None => false,
+        }
+    }
+    
+    pub fn add_payout(&self, history: PayoutHistory) {
+        self.payouts.write().push(history);
+    }
+    
+    pub fn update_payout(&self, payout: Payout) -> bool {
+        let mut payouts = self.payouts.write();
+        let entry = payouts
+            .iter_mut()
+            .rev()
+            .find(|p| p.payout.as_ref().map(|existing| existing.id == payout.id).unwrap_or(false));
+            
+        match entry {
+            Some(history) => {
+                history.payout = Some(payout);
+                history.last_checked_at = Some(chrono::Utc::now());
+                true
+            }
+            None => false,
+        }
+    }
+    
+    pub fn get_merchant_payouts(&self, merchant_id: &Uuid) -> Vec<PayoutHistory> {
+        self.payouts
+            .read()
+            .iter()
+            .filter(|p| &p.merchant_id == merchant_id)
+            .cloned()
+            .collect()
+    }
+    
+    pub fn add_callback(&self, callback: ReceivedCallback) {
+        self.callbacks.write().push(callback);
+    }
+    
+    pub fn get_merchant_callbacks(&self, merchant_id: &Uuid) -> Vec<ReceivedCallback> {
+        self.callbacks
+            .read()
+            .iter()
+            .filter(|c| c.merchant_id.as_ref() == Some(merchant_id))
+            .cloned()
+            .collect()
+    }
+    
+    /// Callbacks that could not be tied to a merchant: posted to the generic
+    /// endpoint for an order that is not known.
+    pub fn get_unattributed_callbacks(&self) -> Vec<ReceivedCallback> {
+        self.callbacks
+            .read()
+            .iter()
+            .filter(|c| c.merchant_id.is_none())
+            .cloned()
+            .collect()
+    }
+    
+    /// Finds the most recent transaction whose order id or transaction id matches.
+    pub fn find_transaction(&self, id: &str) -> Option<TransactionHistory> {
+        self.transactions
+            .read()
+            .iter()
+            .rev()
+            .find(|t| t.transaction.order_id == id || (!t.transaction.id.is_empty() && t.transaction.id == id))
+            .cloned()
+    }
+    
     pub fn get_merchant_transactions(&self, merchant_id: &Uuid) -> Vec<TransactionHistory> {
         self.transactions
             .read()
@@ -147,10 +269,23 @@ impl StorageService {
             .collect()
     }
     
+    /// Orders created without error that have not reached a final status yet.
+    pub fn count_open_transactions(&self, merchant_id: &Uuid) -> usize {
+        self.transactions
+            .read()
+            .iter()
+            .filter(|t| &t.merchant_id == merchant_id && t.error.is_none() && !t.transaction.status.is_final())
+            .count()
+    }
+    
     pub fn get_statistics(&self, merchant_id: &Uuid) -> Option<Statistics> {
         self.statistics.read().get(merchant_id).cloned()
     }
     
+    pub fn get_all_statistics(&self) -> HashMap<Uuid, Statistics> {
+        self.statistics.read().clone()
+    }
+    
     pub fn update_statistics(&self, merchant_id: Uuid, stats: Statistics) {
         self.statistics.write().insert(merchant_id, stats);
     }
@@ -171,6 +306,22 @@ impl StorageService {
         Ok(path)
     }
     
+    pub async fn export_callbacks(&self, merchant_id: &Uuid, export_dir: &PathBuf) -> Result<PathBuf> {
+        fs::create_dir_all(export_dir).await?;
+        
+        let callbacks = self.get_merchant_callbacks(merchant_id);
+        let filename = format!("merchant_{}_callbacks_{}.json", 
+            merchant_id, 
+            chrono::Utc::now().format("%Y%m%d_%H%M%S")
+        );
+        let path = export_dir.join(filename);
+        
+        let data = serde_json::to_string_pretty(&callbacks)?;
+        fs::write(&path, data).await?;
+        
+        Ok(path)
+    }
+    
     pub async fn export_statistics(&self, merchant_id: &Uuid, export_dir: &PathBuf) -> Result<PathBuf> {
         fs::create_dir_all(export_dir).await?;
         
@@ -1,30 +1,66 @@
-use crate::models::{Merchant, AmountRange};
-use crate::services::MerchantService;
+use crate::models::{
+    BuyerDecision, Merchant, Method, DisputeKind, Payout, RateConfig, ReceiptFormat, SimClock, TrafficMode, TrafficPacing,
+    TrafficProfile, Transaction, TransactionStatus,
+};
+use crate::api::client::with_retry_rng;
+use crate::services::{DeviceNotificationService, MerchantService};
 use anyhow::Result;
+use chrono::{NaiveDateTime, Timelike};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
 use rand::{Rng, SeedableRng, rngs::StdRng, seq::SliceRandom};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::sync::{mpsc, RwLock};
-use tokio::time::{sleep, Duration};
-use tracing::{info, error, debug};
+use tokio::sync::{mpsc, RwLock, Semaphore};
+use tokio::time::{sleep, sleep_until, Duration, Instant};
+use tracing::info;
 use uuid::Uuid;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 pub struct TrafficGenerator {
     merchant_service: Arc<MerchantService>,
+    device_notifications: Arc<DeviceNotificationService>,
     active_generators: Arc<RwLock<Vec<GeneratorHandle>>>,
     log_channels: Arc<RwLock<HashMap<Uuid, mpsc::Sender<String>>>>,
 }
 
 struct GeneratorHandle {
     merchant_id: Uuid,
+    method_id: String,
+    seed: u64,
     cancel_tx: mpsc::Sender<()>,
     quiet_mode: bool,
+    progress: Arc<Mutex<Option<RateProgress>>>, // Set for rate-driven runs
+}
+
+/// Live figures of a rate-driven run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RateProgress {
+    pub target_tps: f64,
+    pub achieved_tps: f64, // Dispatches per second over the last report window
+    pub dispatched: u64,
+    pub created: u64,
+    pub in_flight: usize,
+    pub late: u64, // Dispatched more than LAG_TOLERANCE behind schedule
+    pub lag_ms: u64,
+    pub max_lag_ms: u64,
+    pub load_multiplier: f64, // From the time-of-day profile; 1 without one
+    pub simulated_time: Option<NaiveDateTime>,
+}
+
+impl RateProgress {
+    pub const LAG_TOLERANCE: Duration = Duration::from_millis(100);
+    
+    pub fn is_behind(&self) -> bool {
+        self.lag_ms > Self::LAG_TOLERANCE.as_millis() as u64
+    }
 }
 
 impl TrafficGenerator {
-    pub fn new(merchant_service: Arc<MerchantService>) -> Self {
+    pub fn new(merchant_service: Arc<MerchantService>, device_notifications: Arc<DeviceNotificationService>) -> Self {
         Self {
             merchant_service,
+            device_notifications,
             active_generators: Arc::new(RwLock::new(Vec::new())),
             log_channels: Arc::new(RwLock::new(HashMap::new())),
         }
@@ -40,15 +76,24 @@ impl TrafficGenerator {
         
         let (cancel_tx, mut cancel_rx) = mpsc::channel(1);
         
+        // Every random choice of the run comes from this seed; an unseeded run
+        // logs the one it picked so it can be replayed
+        let seed = merchant.traffic_config.seed.unwrap_or_else(rand::random);
+        
+        let progress = Arc::new(Mutex::new(None));
         let handle = GeneratorHandle {
             merchant_id: merchant.id,
+            method_id: method_id.clone(),
+            seed,
             cancel_tx: cancel_tx.clone(),
             quiet_mode,
+            progress: progress.clone(),
         };
         
         self.active_generators.write().await.push(handle);
         
         let merchant_service = self.merchant_service.clone();
+        let device_notifications = self.device_notifications.clone();
         let active_generators = self.active_generators.clone();
         let log_channels = self.log_channels.clone();
         let merchant_id = merchant.id;
@@ -61,12 +106,23 @@ impl TrafficGenerator {
                 None
             };
             
+            let mut rng = StdRng::seed_from_u64(seed);
+            info!("Traffic for merchant {} uses seed {}", merchant.name, seed);
+            
             if let Some(ref tx) = log_sender {
-                let _ = tx.send(format!("Starting traffic generation for merchant {}", merchant.name)).await;
+                let _ = tx.send(format!("Starting traffic generation for merchant {} (seed {})", merchant.name, seed)).await;
             }
             
-            let mut rng = StdRng::from_entropy();
-            let mut created_count = 0u64;
+            if merchant.traffic_config.mode == TrafficMode::Deposits && merchant.traffic_config.dispute_percentage > 0.0 {
+                run_dispute_automation(
+                    merchant_service.clone(),
+                    merchant.clone(),
+                    active_generators.clone(),
+                    cancel_tx.clone(),
+                    StdRng::seed_from_u64(rng.gen()),
+                    log_sender.clone(),
+                );
+            }
             
             // Get available methods once at the start
             let available_methods = match merchant_service.get_available_methods(&merchant).await {
@@ -79,78 +135,25 @@ impl TrafficGenerator {
                 }
             };
             
-            loop {
-                // Check for cancellation
-                if cancel_rx.try_recv().is_ok() {
-                    if let Some(ref tx) = log_sender {
-                        let _ = tx.send(format!("Stopping traffic generation for merchant {}", merchant.name)).await;
-                    }
-                    break;
-                }
-                
-                // Check transaction limit
-                if let Some(max) = merchant.traffic_config.max_transactions {
-                    if created_count >= max {
-                        if let Some(ref tx) = log_sender {
-                            let _ = tx.send(format!("Reached transaction limit ({}) for merchant {}", max, merchant.name)).await;
-                        }
-                        break;
-                    }
-                }
-                
-                // Generate amount based on probabilities
-                let amount = generate_amount(&merchant.traffic_config.amount_probabilities, &mut rng);
-                
-                // Determine if transaction should be mock based on liquidity
-                let is_mock = !merchant.is_liquid();
-                
-                // Select method - use random from available or provided method_id
-                let selected_method_id = if !available_methods.is_empty() {
-                    available_methods.choose(&mut rng)
-                        .map(|m| m.id.clone())
-                        .unwrap_or(method_id.clone())
-                } else {
-                    method_id.clone()
-                };
-                
-                // Create transaction
-                match merchant_service.create_transaction(&merchant, amount, selected_method_id.clone(), is_mock).await {
-                    Ok(transaction) => {
-                        created_count += 1;
-                        if let Some(ref tx) = log_sender {
-                            let _ = tx.send(format!("Created transaction {} for merchant {} (amount: {:.2}, method: {}, mock: {})",
-                                transaction.id, merchant.name, amount, selected_method_id, is_mock)).await;
-                                
-                            // TODO: If liquid and device connected, emit balance top-up notification
-                            if !is_mock && transaction.requisites.is_some() {
-                                let _ = tx.send(format!("Would emit balance top-up notification for transaction {}", transaction.id)).await;
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        if let Some(ref tx) = log_sender {
-                            let _ = tx.send(format!("ERROR: Failed to create transaction for merchant {}: {}", merchant.name, e)).await;
-                        }
-                    }
-                }
-                
-                // Calculate sleep duration with variance
-                let base_interval = merchant.traffic_config.interval_ms;
-                let variance = merchant.traffic_config.interval_variance;
-                let actual_interval = if variance > 0 {
-                    let min = base_interval.saturating_sub(variance);
-                    let max = base_interval + variance;
-                    rng.gen_range(min..=max)
-                } else {
-                    base_interval
-                };
-                
-                sleep(Duration::from_millis(actual_interval)).await;
-            }
+            let profile = &merchant.traffic_config.profile;
+            let run = RunContext {
+                merchant_service,
+                device_notifications,
+                merchant: merchant.clone(),
+                method_id,
+                available_methods: Arc::new(available_methods),
+                profile: profile.enabled.then(|| Arc::new(ActiveProfile { profile: profile.clone(), clock: profile.clock() })),
+                log_sender: log_sender.clone(),
+            };
             
-            // Remove from active generators
+            let created_count = match &merchant.traffic_config.pacing {
+                TrafficPacing::Sequential => run_sequential(&run, &mut rng, &mut cancel_rx).await,
+                TrafficPacing::Rate(rate) => run_at_rate(&run, rate, &mut rng, &mut cancel_rx, &progress).await,
+            };
+            
+            // Remove from active generators, unless a restart already replaced this run
             let mut generators = active_generators.write().await;
-            generators.retain(|g| g.merchant_id != merchant_id);
+            generators.retain(|g| !Arc::ptr_eq(&g.progress, &progress));
             
             if let Some(ref tx) = log_sender {
                 let _ = tx.send(format!("Traffic generation stopped for merchant {} (created {} transactions)", 
@@ -178,6 +181,31 @@ impl TrafficGenerator {
         }
     }
     
+    /// Restarts a running merchant with its new settings and the same method.
+    /// The restarted run counts `max_transactions` from zero. If its seed is the
+    /// one the current run uses, a fresh seed is picked so order ids are not repeated.
+    /// Returns false when the merchant is not running.
+    pub async fn restart_traffic(&self, mut merchant: Merchant) -> Result<bool> {
+        let running = self.active_generators
+            .read()
+            .await
+            .iter()
+            .find(|g| g.merchant_id == merchant.id)
+            .map(|g| (g.method_id.clone(), g.seed, g.quiet_mode));
+        let Some((method_id, seed, quiet_mode)) = running else {
+            return Ok(false);
+        };
+        
+        if merchant.traffic_config.seed == Some(seed) {
+            merchant.traffic_config.seed = None;
+        }
+        
+        info!("Restarting traffic for merchant {} with new settings", merchant.name);
+        self.stop_traffic(&merchant.id).await?;
+        self.start_traffic(merchant, method_id, quiet_mode).await?;
+        Ok(true)
+    }
+    
     pub async fn stop_all_traffic(&self) -> Result<()> {
         let mut generators = self.active_generators.write().await;
         
@@ -205,6 +233,26 @@ impl TrafficGenerator {
             .map(|g| (true, g.quiet_mode))
     }
     
+    /// Method the merchant's running traffic uses.
+    pub async fn running_method(&self, merchant_id: &Uuid) -> Option<String> {
+        self.active_generators
+            .read()
+            .await
+            .iter()
+            .find(|g| &g.merchant_id == merchant_id)
+            .map(|g| g.method_id.clone())
+    }
+    
+    /// Progress of a running rate-driven run; None for sequential runs.
+    pub async fn rate_progress(&self, merchant_id: &Uuid) -> Option<RateProgress> {
+        self.active_generators
+            .read()
+            .await
+            .iter()
+            .find(|g| &g.merchant_id == merchant_id)
+            .and_then(|g| g.progress.lock().clone())
+    }
+    
     pub async fn create_log_channel(&self, merchant_id: Uuid) -> mpsc::Receiver<String> {
         let (tx, rx) = mpsc::channel(1000);
         self.log_channels.write().await.insert(merchant_id, tx);
@@ -220,25 +268,604 @@ impl TrafficGenerator {
     }
 }
 
-fn generate_amount(probabilities: &std::collections::HashMap<AmountRange, f64>, rng: &mut impl Rng) -> f64 {
-    let roll = rng.gen_range(0.0..100.0);
+/// What a single request of a run needs; cloned into each request task when
+/// running at a fixed rate.
+#[derive(Clone)]
+struct RunContext {
+    merchant_service: Arc<MerchantService>,
+    device_notifications: Arc<DeviceNotificationService>,
+    merchant: Merchant,
+    method_id: String,
+    available_methods: Arc<Vec<Method>>,
+    profile: Option<Arc<ActiveProfile>>,
+    log_sender: Option<mpsc::Sender<String>>,
+}
+
+/// A time-of-day profile running on its simulated clock.
+struct ActiveProfile {
+    profile: TrafficProfile,
+    clock: SimClock,
+}
+
+impl RunContext {
+    async fn log(&self, message: String) {
+        if let Some(ref tx) = self.log_sender {
+            let _ = tx.send(message).await;
+        }
+    }
+    
+    /// How much the profile scales the base rate right now.
+    fn load_multiplier(&self) -> f64 {
+        self.profile
+            .as_ref()
+            .map(|active| active.profile.multiplier_at(active.clock.now()))
+            .unwrap_or(1.0)
+    }
+    
+    fn simulated_time(&self) -> Option<NaiveDateTime> {
+        self.profile.as_ref().map(|active| active.clock.now())
+    }
     
-    // Sort ranges by probability (ascending) for cumulative distribution
-    let mut ranges: Vec<_> = probabilities.iter().collect();
-    ranges.sort_by(|a, b| a.1.partial_cmp(b.1).unwrap());
+    /// Logs each new simulated hour, so the daily curve can be followed in the log.
+    async fn report_hour(&self, last_hour: &mut Option<NaiveDateTime>) {
+        let Some(now) = self.simulated_time() else {
+            return;
+        };
+        let hour = now.with_minute(0).and_then(|t| t.with_second(0)).and_then(|t| t.with_nanosecond(0));
+        if hour != *last_hour {
+            *last_hour = hour;
+            self.log(format!("Simulated time {} for merchant {} (load x{:.2})",
+                now.format("%a %H:%M"), self.merchant.name, self.load_multiplier())).await;
+        }
+    }
     
-    // Simple approach: use probability as weight for selection
-    // In production, you might want a more sophisticated distribution
-    for (range, probability) in &ranges {
-        if roll <= **probability {
-            return rng.gen_range(range.min as f64..=range.max as f64);
+    /// Creates one deposit or payout and schedules what follows it.
+    /// Returns whether the backend accepted it.
+    async fn send_one(&self, rng: &mut StdRng) -> bool {
+        let merchant = &self.merchant;
+        let merchant_service = &self.merchant_service;
+        let log_sender = &self.log_sender;
+        
+        // Determine if transaction should be mock based on liquidity
+        let is_mock = !merchant.is_liquid(rng);
+        
+        // Select method - use random from available or provided method_id
+        let selected_method_id = self.available_methods
+            .choose(rng)
+            .map(|m| m.id.clone())
+            .unwrap_or(self.method_id.clone());
+        
+        match merchant.traffic_config.mode {
+            TrafficMode::Deposits => {
+                let amount = merchant.traffic_config.amount_distribution.sample(rng);
+                
+                // Create transaction
+                match merchant_service.create_transaction_with_rng(merchant, amount, selected_method_id.clone(), is_mock, rng).await {
+                    Ok(transaction) => {
+                        self.log(format!("Created transaction {} for merchant {} (amount: {:.2}, method: {}, mock: {})",
+                            transaction.id, merchant.name, amount, selected_method_id, is_mock)).await;
+                        
+                        // Let the simulated buyer decide whether this order gets paid
+                        let buyer = &merchant.traffic_config.buyer;
+                        if buyer.enabled {
+                            let decision = buyer.decide(amount, rng);
+                            let band = buyer.band_label(amount);
+                            merchant_service.record_buyer_decision(merchant.id, &band, &decision);
+                            schedule_buyer(self.clone(), transaction.clone(), band, decision, is_mock);
+                        }
+                        
+                        // Attach a synthetic receipt to a share of orders
+                        if merchant.should_attach_receipt(rng) {
+                            let format = if rng.gen_bool(0.5) { ReceiptFormat::Png } else { ReceiptFormat::Pdf };
+                            schedule_receipt(
+                                merchant_service.clone(),
+                                merchant.clone(),
+                                transaction.clone(),
+                                format,
+                                StdRng::seed_from_u64(rng.gen()),
+                                log_sender.clone(),
+                            );
+                        }
+                        
+                        // Schedule merchant-side cancellation for a share of orders;
+                        // with the buyer model on, unpaid orders are cancelled there instead
+                        if !buyer.enabled && merchant.should_cancel(rng) {
+                            schedule_cancellation(
+                                merchant_service.clone(),
+                                merchant.clone(),
+                                transaction.order_id.clone(),
+                                Duration::from_millis(merchant.traffic_config.cancel_delay_ms),
+                                log_sender.clone(),
+                            );
+                        }
+                        
+                        true
+                    }
+                    Err(e) => {
+                        self.log(format!("ERROR: Failed to create transaction for merchant {}: {}", merchant.name, e)).await;
+                        false
+                    }
+                }
+            }
+            TrafficMode::Payouts => {
+                // Payouts use their own amount distribution
+                let amount = merchant.traffic_config.payout_amount_distribution.sample(rng);
+                
+                match merchant_service.create_payout_with_rng(merchant, amount, selected_method_id.clone(), is_mock, rng).await {
+                    Ok(payout) => {
+                        self.log(format!("Created payout {} for merchant {} (amount: {:.2}, method: {}, mock: {})",
+                            payout.id, merchant.name, amount, selected_method_id, is_mock)).await;
+                        
+                        track_payout(merchant_service.clone(), merchant.clone(), payout, log_sender.clone());
+                        true
+                    }
+                    Err(e) => {
+                        self.log(format!("ERROR: Failed to create payout for merchant {}: {}", merchant.name, e)).await;
+                        false
+                    }
+                }
+            }
         }
     }
+}
+
+/// One request at a time, with a randomised pause after each.
+async fn run_sequential(run: &RunContext, rng: &mut StdRng, cancel_rx: &mut mpsc::Receiver<()>) -> u64 {
+    const PAUSE_STEP: Duration = Duration::from_secs(1); // Re-check interval while the profile is at zero
+    
+    let config = &run.merchant.traffic_config;
+    let mut created_count = 0u64;
+    let mut last_hour = None;
     
-    // Fallback to first range
-    if let Some((range, _)) = ranges.first() {
-        rng.gen_range(range.min as f64..=range.max as f64)
-    } else {
-        1000.0 // Default fallback
+    loop {
+        // Check for cancellation
+        if cancel_rx.try_recv().is_ok() {
+            run.log(format!("Stopping traffic generation for merchant {}", run.merchant.name)).await;
+            break;
+        }
+        
+        // Check transaction limit
+        if let Some(max) = config.max_transactions {
+            if created_count >= max {
+                run.log(format!("Reached transaction limit ({}) for merchant {}", max, run.merchant.name)).await;
+                break;
+            }
+        }
+        
+        run.report_hour(&mut last_hour).await;
+        let multiplier = run.load_multiplier();
+        if multiplier <= 0.0 {
+            sleep(PAUSE_STEP).await;
+            continue;
+        }
+        
+        if with_retry_rng(StdRng::seed_from_u64(rng.gen()), run.send_one(rng)).await {
+            created_count += 1;
+        }
+        
+        // Calculate sleep duration with variance
+        let base_interval = config.interval_ms;
+        let variance = config.interval_variance;
+        let actual_interval = if variance > 0 {
+            let min = base_interval.saturating_sub(variance);
+            let max = base_interval + variance;
+            rng.gen_range(min..=max)
+        } else {
+            base_interval
+        };
+        
+        // A busier hour shortens the pause, a quieter one stretches it
+        sleep(Duration::from_millis(actual_interval).div_f64(multiplier)).await;
     }
+    
+    created_count
+}
+
+/// Open loop: requests are dispatched on the schedule set by the target rate,
+/// whether or not earlier ones have answered, with at most `max_concurrency`
+/// in flight. Time spent waiting for a free slot shows up as lag behind the
+/// schedule, which is how a saturated backend becomes visible.
+async fn run_at_rate(
+    run: &RunContext,
+    rate: &RateConfig,
+    rng: &mut StdRng,
+    cancel_rx: &mut mpsc::Receiver<()>,
+    progress: &Mutex<Option<RateProgress>>,
+) -> u64 {
+    const IDLE_STEP: Duration = Duration::from_millis(100); // Re-check interval while traffic is paused or the limit is pending
+    const REPORT_INTERVAL: Duration = Duration::from_secs(5);
+    
+    let slots = Arc::new(Semaphore::new(rate.max_concurrency));
+    let created = Arc::new(AtomicU64::new(0));
+    let max_transactions = run.merchant.traffic_config.max_transactions;
+    
+    let start = Instant::now();
+    let mut next = start;
+    let mut report = RateProgress::default();
+    let mut window = (Instant::now(), 0u64);
+    let mut last_hour = None;
+    
+    loop {
+        let Some(scheduled) = rate.rate_at(next - start) else {
+            run.log(format!("Rate schedule finished for merchant {}", run.merchant.name)).await;
+            break;
+        };
+        
+        run.report_hour(&mut last_hour).await;
+        report.load_multiplier = run.load_multiplier();
+        report.simulated_time = run.simulated_time();
+        let target = scheduled * report.load_multiplier;
+        report.target_tps = target;
+        report.in_flight = rate.max_concurrency - slots.available_permits();
+        report.created = created.load(Ordering::Relaxed);
+        
+        // Requests in flight may still fail, so only stop once the limit is reached
+        let pending_limit = match max_transactions {
+            Some(max) if report.created >= max => {
+                run.log(format!("Reached transaction limit ({}) for merchant {}", max, run.merchant.name)).await;
+                break;
+            }
+            Some(max) => report.created + report.in_flight as u64 >= max,
+            None => false,
+        };
+        
+        if pending_limit || report.load_multiplier <= 0.0 {
+            tokio::select! {
+                _ = sleep(IDLE_STEP) => {}
+                _ = cancel_rx.recv() => {
+                    run.log(format!("Stopping traffic generation for merchant {}", run.merchant.name)).await;
+                    break;
+                }
+            }
+            // Time spent idle is not lag
+            next = next.max(Instant::now());
+            continue;
+        }
+        
+        // Nothing is due at a zero rate, such as the start of a ramp, until a whole request has accrued
+        if target <= 0.0 {
+            next = start + rate.next_due(next - start, report.load_multiplier)
+                .unwrap_or_else(|| rate.total_duration().unwrap_or_default());
+            continue;
+        }
+        
+        let permit = tokio::select! {
+            permit = async {
+                sleep_until(next).await;
+                slots.clone().acquire_owned().await
+            } => permit.expect("request slots are never closed"),
+            _ = cancel_rx.recv() => {
+                run.log(format!("Stopping traffic generation for merchant {}", run.merchant.name)).await;
+                break;
+            }
+        };
+        
+        let lag = Instant::now().saturating_duration_since(next);
+        report.dispatched += 1;
+        report.lag_ms = lag.as_millis() as u64;
+        report.max_lag_ms = report.max_lag_ms.max(report.lag_ms);
+        if lag > RateProgress::LAG_TOLERANCE {
+            report.late += 1;
+        }
+        
+        // Each request gets its own generator so completion order does not affect what is sent
+        let mut request_rng = StdRng::seed_from_u64(rng.gen());
+        let request = run.clone();
+        let request_created = created.clone();
+        tokio::spawn(async move {
+            if with_retry_rng(StdRng::seed_from_u64(request_rng.gen()), request.send_one(&mut request_rng)).await {
+                request_created.fetch_add(1, Ordering::Relaxed);
+            }
+            drop(permit);
+        });
+        
+        // Past the last stage when nothing more is due, which ends the run above
+        next = start + rate.next_due(next - start, report.load_multiplier)
+            .unwrap_or_else(|| rate.total_duration().unwrap_or_default());
+        
+        let window_length = window.0.elapsed();
+        if window_length >= REPORT_INTERVAL {
+            report.achieved_tps = (report.dispatched - window.1) as f64 / window_length.as_secs_f64();
+            window = (Instant::now(), report.dispatched);
+            
+            let behind = if !report.is_behind() {
+                ""
+            } else if report.in_flight >= rate.max_concurrency {
+                " - FALLING BEHIND (concurrency limit reached)"
+            } else {
+                " - FALLING BEHIND"
+            };
+            run.log(format!("Rate for merchant {}: target {:.1} TPS, achieved {:.1} TPS, {} in flight, lag {} ms{}",
+                run.merchant.name, report.target_tps, report.achieved_tps, report.in_flight, report.lag_ms, behind)).await;
+        }
+        
+        *progress.lock() = Some(report.clone());
+    }
+    
+    // Let requests in flight finish so the count is final
+    let _ = slots.acquire_many(rate.max_concurrency as u32).await;
+    
+    report.in_flight = 0;
+    report.created = created.load(Ordering::Relaxed);
+    *progress.lock() = Some(report.clone());
+    
+    run.log(format!("Rate run for merchant {}: {} dispatched, {} behind schedule, max lag {} ms",
+        run.merchant.name, report.dispatched, report.late, report.max_lag_ms)).await;
+    
+    report.created
+}
+
+fn schedule_cancellation(
+    merchant_service: Arc<MerchantService>,
+    merchant: Merchant,
+    order_id: String,
+    delay: Duration,
+    log_sender: Option<mpsc::Sender<String>>,
+) {
+    tokio::spawn(async move {
+        sleep(delay).await;
+        
+        match merchant_service.cancel_transaction(&merchant, &order_id).await {
+            Ok(canceled) => {
+                if let Some(ref tx) = log_sender {
+                    let _ = tx.send(format!("Cancelled order {} for merchant {} (status: {:?})",
+                        order_id, merchant.name, canceled.status)).await;
+                }
+            }
+            Err(e) => {
+                if let Some(ref tx) = log_sender {
+                    let _ = tx.send(format!("ERROR: Failed to cancel order {} for merchant {}: {}",
+                        order_id, merchant.name, e)).await;
+                }
+            }
+        }
+    });
+}
+
+/// Carries out a buyer decision: a paying buyer's bank notifies the trader's
+/// device once the delay passes, an unpaid order is cancelled by the merchant
+/// or left open until the backend expires it.
+fn schedule_buyer(run: RunContext, transaction: Transaction, band: String, decision: BuyerDecision, is_mock: bool) {
+    match decision {
+        BuyerDecision::Pay { after } => {
+            // Mock orders are settled by the backend; only liquid ones reach a real device
+            if is_mock {
+                return;
+            }
+            
+            tokio::spawn(async move {
+                sleep(after).await;
+                
+                match run.device_notifications.send_payment_notification(&transaction).await {
+                    Ok(0) => {
+                        run.log(format!("WARNING: Buyer paid transaction {} but no connected device belongs to its trader",
+                            transaction.id)).await;
+                    }
+                    Ok(devices) => {
+                        run.merchant_service.record_buyer_notification(run.merchant.id, &band);
+                        run.log(format!("Buyer paid transaction {} ({:.2}); notified {} device(s)",
+                            transaction.id, transaction.amount, devices)).await;
+                    }
+                    Err(e) => {
+                        run.log(format!("ERROR: Failed to notify devices for transaction {}: {}",
+                            transaction.id, e)).await;
+                    }
+                }
+            });
+        }
+        BuyerDecision::Cancel { after } => {
+            schedule_cancellation(run.merchant_service, run.merchant, transaction.order_id, after, run.log_sender);
+        }
+        BuyerDecision::Abandon => {
+            if let Some(ref tx) = run.log_sender {
+                let _ = tx.try_send(format!("Buyer abandoned transaction {}; leaving it to expire", transaction.id));
+            }
+        }
+    }
+}
+
+fn schedule_receipt(
+    merchant_service: Arc<MerchantService>,
+    merchant: Merchant,
+    transaction: Transaction,
+    format: ReceiptFormat,
+    mut rng: StdRng,
+    log_sender: Option<mpsc::Sender<String>>,
+) {
+    tokio::spawn(async move {
+        let retry_rng = StdRng::seed_from_u64(rng.gen());
+        match with_retry_rng(retry_rng, merchant_service.attach_receipt_with_rng(&merchant, &transaction, format, &mut rng)).await {
+            Ok(receipt) => {
+                if let Some(ref tx) = log_sender {
+                    let _ = tx.send(format!("Uploaded receipt {} for transaction {} (merchant {})",
+                        receipt.file_name, transaction.id, merchant.name)).await;
+                }
+            }
+            Err(e) => {
+                if let Some(ref tx) = log_sender {
+                    let _ = tx.send(format!("ERROR: Failed to upload receipt for transaction {}: {}",
+                        transaction.id, e)).await;
+                }
+            }
+        }
+    });
+}
+
+/// Watches for deals that reach READY while traffic runs and disputes a share of them.
+/// Deals that were already settled when traffic started are left alone.
+fn run_dispute_automation(
+    merchant_service: Arc<MerchantService>,
+    merchant: Merchant,
+    active_generators: Arc<RwLock<Vec<GeneratorHandle>>>,
+    run: mpsc::Sender<()>, // The run's cancel channel, which identifies it
+    mut rng: StdRng,
+    log_sender: Option<mpsc::Sender<String>>,
+) {
+    const SCAN_INTERVAL: Duration = Duration::from_secs(15);
+    
+    tokio::spawn(async move {
+        let mut seen: HashSet<String> = merchant_service
+            .get_merchant_transactions(&merchant.id)
+            .into_iter()
+            .filter(|h| h.transaction.status.is_final())
+            .map(|h| h.transaction.order_id)
+            .collect();
+        let mut chosen: Vec<Transaction> = Vec::new(); // Rolled for a dispute, not opened yet
+            
+        loop {
+            sleep(SCAN_INTERVAL).await;
+            
+            // A restart starts its own automation, so stop with the run rather than the merchant
+            if !active_generators.read().await.iter().any(|g| g.cancel_tx.same_channel(&run)) {
+                break;
+            }
+            
+            for history in merchant_service.get_merchant_transactions(&merchant.id) {
+                if history.transaction.status != TransactionStatus::Ready || history.dispute_id.is_some() {
+                    continue;
+                }
+                
+                if seen.insert(history.transaction.order_id.clone()) && merchant.should_open_dispute(&mut rng) {
+                    chosen.push(history.transaction);
+                }
+            }
+            if chosen.is_empty() {
+                continue;
+            }
+            
+            // A dispute whose reply was lost, or one opened from elsewhere, is
+            // not in the local history, so ask the backend before opening another
+            let disputed: HashSet<String> = match merchant_service.list_disputes(&merchant, DisputeKind::Deal).await {
+                Ok(disputes) => disputes.into_iter().filter_map(|d| d.deal_id).collect(),
+                Err(e) => {
+                    if let Some(ref tx) = log_sender {
+                        let _ = tx.send(format!("ERROR: Failed to list disputes, retrying next scan: {}", e)).await;
+                    }
+                    continue;
+                }
+            };
+            
+            for transaction in chosen.drain(..) {
+                if disputed.contains(&transaction.id) {
+                    if let Some(ref tx) = log_sender {
+                        let _ = tx.send(format!("Deal {} already has a dispute, not opening another", transaction.id)).await;
+                    }
+                    continue;
+                }
+                
+                let dispute_rng = StdRng::seed_from_u64(rng.gen());
+                script_dispute(merchant_service.clone(), merchant.clone(), transaction, dispute_rng, log_sender.clone());
+            }
+        }
+    });
+}
+
+/// Opens a dispute with the first scripted message, posts the follow-ups, then
+/// follows the dispute until it is resolved so status transitions show up in the log.
+fn script_dispute(
+    merchant_service: Arc<MerchantService>,
+    merchant: Merchant,
+    transaction: Transaction,
+    mut rng: StdRng,
+    log_sender: Option<mpsc::Sender<String>>,
+) {
+    const MESSAGE_INTERVAL: Duration = Duration::from_secs(30);
+    const MAX_POLLS: u32 = 120; // One hour
+    
+    tokio::spawn(async move {
+        let mut messages = merchant.traffic_config.dispute_messages.iter();
+        let opening = messages.next().map(String::as_str).unwrap_or("Payment not received");
+        
+        let retry_rng = StdRng::seed_from_u64(rng.gen());
+        let dispute = match with_retry_rng(retry_rng, merchant_service.open_deal_dispute_with_rng(&merchant, &transaction, opening, &mut rng)).await {
+            Ok(dispute) => dispute,
+            Err(e) => {
+                if let Some(ref tx) = log_sender {
+                    let _ = tx.send(format!("ERROR: Failed to open dispute for deal {}: {}", transaction.id, e)).await;
+                }
+                return;
+            }
+        };
+        
+        if let Some(ref tx) = log_sender {
+            let _ = tx.send(format!("Opened dispute {} for deal {} (merchant {}, status: {:?})",
+                dispute.id, transaction.id, merchant.name, dispute.status)).await;
+        }
+        
+        let mut status = dispute.status;
+        let mut pending: Vec<&String> = messages.collect();
+        pending.reverse();
+        
+        for _ in 0..MAX_POLLS {
+            sleep(MESSAGE_INTERVAL).await;
+            
+            match merchant_service.get_dispute(&merchant, DisputeKind::Deal, &dispute.id).await {
+                Ok(current) => {
+                    if current.status != status {
+                        if let Some(ref tx) = log_sender {
+                            let _ = tx.send(format!("Dispute {} for deal {}: {:?} -> {:?}",
+                                dispute.id, transaction.id, status, current.status)).await;
+                        }
+                        status = current.status;
+                    }
+                }
+                Err(e) => {
+                    if let Some(ref tx) = log_sender {
+                        let _ = tx.send(format!("ERROR: Failed to poll dispute {}: {}", dispute.id, e)).await;
+                    }
+                    continue;
+                }
+            }
+            
+            if !status.is_open() {
+                break;
+            }
+            
+            if let Some(message) = pending.pop() {
+                if let Err(e) = merchant_service.send_dispute_message(&merchant, DisputeKind::Deal, &dispute.id, message).await {
+                    if let Some(ref tx) = log_sender {
+                        let _ = tx.send(format!("ERROR: Failed to post message in dispute {}: {}", dispute.id, e)).await;
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Polls a payout until it reaches a final status or the tracking window runs out.
+fn track_payout(
+    merchant_service: Arc<MerchantService>,
+    merchant: Merchant,
+    mut payout: Payout,
+    log_sender: Option<mpsc::Sender<String>>,
+) {
+    const POLL_INTERVAL: Duration = Duration::from_secs(10);
+    const MAX_POLLS: u32 = 360; // One hour
+    
+    tokio::spawn(async move {
+        for _ in 0..MAX_POLLS {
+            if payout.status.is_final() {
+                break;
+            }
+            
+            sleep(POLL_INTERVAL).await;
+            
+            match merchant_service.refresh_payout(&merchant, &payout).await {
+                Ok(updated) => {
+                    if updated.status != payout.status {
+                        if let Some(ref tx) = log_sender {
+                            let _ = tx.send(format!("Payout {} for merchant {}: {:?} -> {:?}",
+                                updated.id, merchant.name, payout.status, updated.status)).await;
+                        }
+                    }
+                    payout = updated;
+                }
+                Err(e) => {
+                    if let Some(ref tx) = log_sender {
+                        let _ = tx.send(format!("ERROR: Failed to poll payout {}: {}", payout.id, e)).await;
+                    }
+                }
+            }
+        }
+    });
 }
\ No newline at end of file
@@ -0,0 +1,545 @@
+use crate::device::DeviceManager;
+use crate::models::{Device, Merchant, Statistics, TrafficPacing};
+use crate::services::{LogCaptureHandle, MerchantService, RateProgress, StatisticsService, StorageService, TrafficGenerator};
+use anyhow::Result;
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEventKind},
+    execute,
+    terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    backend::{Backend, CrosstermBackend},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState},
+    Frame, Terminal,
+};
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+const REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+const TPS_WINDOW: Duration = Duration::from_secs(5); // Current TPS averages over this window
+const TOP_ERRORS: usize = 6;
+const LOG_BUFFER: usize = 256; // Captured log lines waiting for the next frame
+
+/// One merchant's line on the dashboard.
+#[derive(Debug, Clone)]
+pub struct MerchantRow {
+    pub merchant: Merchant,
+    pub running: bool,
+    pub method_id: Option<String>,
+    pub rate: Option<RateProgress>, // Rate-driven runs only
+    pub tps: f64,
+    pub statistics: Option<Statistics>,
+    pub open_transactions: usize,
+}
+
+impl MerchantRow {
+    /// Orders and payouts sent, and how many of them the backend accepted.
+    fn requests(&self) -> (u64, u64) {
+        self.statistics.as_ref().map_or((0, 0), |s| {
+            (
+                s.total_requests + s.payouts_created + s.payouts_failed,
+                s.successful_requests + s.payouts_created,
+            )
+        })
+    }
+    
+    pub fn success_rate(&self) -> Option<f64> {
+        let (sent, succeeded) = self.requests();
+        (sent > 0).then(|| succeeded as f64 / sent as f64 * 100.0)
+    }
+}
+
+/// Full-screen view of every merchant's traffic and the emulated devices,
+/// refreshed live. Traffic started here runs in quiet mode.
+pub struct Dashboard {
+    merchant_service: Arc<MerchantService>,
+    traffic_generator: Arc<TrafficGenerator>,
+    stats_service: Arc<StatisticsService>,
+    storage: Arc<StorageService>,
+    device_manager: Arc<DeviceManager>,
+    rows: Vec<MerchantRow>,
+    devices: Vec<Device>,
+    table: TableState,
+    samples: HashMap<Uuid, VecDeque<(Instant, u64)>>, // Requests sent, for the current TPS
+    message: Option<(String, bool)>, // Result of the last action; true for errors
+    logs: Option<mpsc::Receiver<String>>,
+    last_log: Option<String>, // Shown in the footer instead of over the screen
+}
+
+impl Dashboard {
+    pub fn new(
+        merchant_service: Arc<MerchantService>,
+        traffic_generator: Arc<TrafficGenerator>,
+        stats_service: Arc<StatisticsService>,
+        storage: Arc<StorageService>,
+        device_manager: Arc<DeviceManager>,
+    ) -> Self {
+        Self {
+            merchant_service,
+            traffic_generator,
+            stats_service,
+            storage,
+            device_manager,
+            rows: Vec::new(),
+            devices: Vec::new(),
+            table: TableState::default(),
+            samples: HashMap::new(),
+            message: None,
+            logs: None,
+            last_log: None,
+        }
+    }
+    
+    /// Takes over the terminal until the user quits with `q` or Esc. Logs are
+    /// captured for the footer while it is open, since lines written to stderr
+    /// would draw over the screen.
+    pub async fn run(&mut self, log_capture: &LogCaptureHandle) -> Result<()> {
+        let (tx, rx) = mpsc::channel(LOG_BUFFER);
+        let _screen = Screen::enter(log_capture, tx)?;
+        self.logs = Some(rx);
+        
+        let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+        let result = self.event_loop(&mut terminal).await;
+        
+        self.logs = None;
+        result
+    }
+    
+    async fn event_loop<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
+        let mut last_refresh: Option<Instant> = None;
+        
+        loop {
+            if last_refresh.is_none_or(|at| at.elapsed() >= REFRESH_INTERVAL) {
+                self.refresh().await;
+                last_refresh = Some(Instant::now());
+            }
+            if let Some(logs) = &mut self.logs {
+                while let Ok(line) = logs.try_recv() {
+                    self.last_log = Some(line);
+                }
+            }
+            terminal.draw(|frame| self.draw(frame))?;
+            
+            // Check for user input (non-blocking)
+            if event::poll(Duration::from_millis(100))? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press && !self.handle_key(key.code).await {
+                        break;
+                    }
+                    // Show the effect of the key right away
+                    last_refresh = None;
+                }
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Reads the current state of every merchant and device.
+    pub async fn refresh(&mut self) {
+        let selected = self.selected().map(|row| row.merchant.id);
+        let mut merchants = self.merchant_service.get_all_merchants();
+        merchants.sort_by_key(|m| m.created_at);
+        
+        let now = Instant::now();
+        let mut rows = Vec::with_capacity(merchants.len());
+        for merchant in merchants {
+            let method_id = self.traffic_generator.running_method(&merchant.id).await;
+            let mut row = MerchantRow {
+                running: method_id.is_some(),
+                method_id,
+                rate: self.traffic_generator.rate_progress(&merchant.id).await,
+                tps: 0.0,
+                statistics: self.stats_service.get_statistics(&merchant.id),
+                open_transactions: self.storage.count_open_transactions(&merchant.id),
+                merchant,
+            };
+            row.tps = self.sample_tps(row.merchant.id, row.requests().0, now);
+            rows.push(row);
+        }
+        self.samples.retain(|id, _| rows.iter().any(|row| &row.merchant.id == id));
+        self.rows = rows;
+        
+        self.devices = self.device_manager.get_all_devices();
+        self.devices.sort_by_key(|d| d.created_at);
+        
+        // Keep the same merchant selected when the list changes
+        let index = selected
+            .and_then(|id| self.rows.iter().position(|row| row.merchant.id == id))
+            .unwrap_or(0);
+        self.table.select((!self.rows.is_empty()).then_some(index));
+    }
+    
+    /// Requests per second over the last `TPS_WINDOW`.
+    fn sample_tps(&mut self, merchant_id: Uuid, sent: u64, now: Instant) -> f64 {
+        let samples = self.samples.entry(merchant_id).or_default();
+        samples.push_back((now, sent));
+        while samples.len() > 2 && samples.front().is_some_and(|(at, _)| now - *at > TPS_WINDOW) {
+            samples.pop_front();
+        }
+        
+        match (samples.front(), samples.back()) {
+            (Some((first_at, first)), Some((last_at, last))) if last_at > first_at => {
+                last.saturating_sub(*first) as f64 / (*last_at - *first_at).as_secs_f64()
+            }
+            _ => 0.0,
+        }
+    }
+    
+    pub fn selected(&self) -> Option<&MerchantRow> {
+        self.table.selected().and_then(|index| self.rows.get(index))
+    }
+    
+    pub fn rows(&self) -> &[MerchantRow] {
+        &self.rows
+    }
+    
+    /// Applies a key. Returns false when the dashboard should close.
+    pub async fn handle_key(&mut self, key: KeyCode) -> bool {
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => self.move_selection(1),
+            KeyCode::Up | KeyCode::Char('k') | KeyCode::BackTab => self.move_selection(-1),
+            KeyCode::Char('s') => {
+                if let Some(row) = self.selected().cloned() {
+                    let result = if row.running {
+                        self.stop(row.merchant).await
+                    } else {
+                        self.start(row.merchant).await
+                    };
+                    self.message = Some(match result {
+                        Ok(message) => (message, false),
+                        Err(e) => (e.to_string(), true),
+                    });
+                }
+            }
+            KeyCode::Char('x') => {
+                let running: Vec<_> = self.rows.iter().filter(|row| row.running).map(|row| row.merchant.clone()).collect();
+                let mut stopped = 0;
+                for merchant in running {
+                    if self.stop(merchant).await.is_ok() {
+                        stopped += 1;
+                    }
+                }
+                self.message = Some((format!("Stopped traffic for {} merchants", stopped), false));
+            }
+            _ => {}
+        }
+        
+        true
+    }
+    
+    fn move_selection(&mut self, step: isize) {
+        if self.rows.is_empty() {
+            return;
+        }
+        let len = self.rows.len() as isize;
+        let current = self.table.selected().unwrap_or(0) as isize;
+        self.table.select(Some((current + step).rem_euclid(len) as usize));
+    }
+    
+    /// Starts traffic with the merchant's settings and its first available method.
+    async fn start(&self, mut merchant: Merchant) -> Result<String> {
+        let methods = self.merchant_service.get_available_methods(&merchant).await?;
+        let method = methods
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("No payment methods available for {}", merchant.name))?;
+        
+        merchant.traffic_config.enabled = true;
+        self.merchant_service.update_merchant(merchant.clone()).await?;
+        self.traffic_generator.start_traffic(merchant.clone(), method.id.clone(), true).await?;
+        
+        Ok(format!("Started traffic for {} ({})", merchant.name, method.name))
+    }
+    
+    async fn stop(&self, mut merchant: Merchant) -> Result<String> {
+        self.traffic_generator.stop_traffic(&merchant.id).await?;
+        merchant.traffic_config.enabled = false;
+        self.merchant_service.update_merchant(merchant.clone()).await?;
+        
+        Ok(format!("Stopped traffic for {}", merchant.name))
+    }
+    
+    pub fn draw(&mut self, frame: &mut Frame) {
+        let [header, body, devices, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(10),
+            Constraint::Length(self.devices.len().clamp(1, 6) as u16 + 3),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [merchants, detail] = Layout::horizontal([Constraint::Percentage(55), Constraint::Percentage(45)]).areas(body);
+        
+        self.draw_header(frame, header);
+        self.draw_merchants(frame, merchants);
+        self.draw_detail(frame, detail);
+        self.draw_devices(frame, devices);
+        self.draw_footer(frame, footer);
+    }
+    
+    fn draw_header(&self, frame: &mut Frame, area: Rect) {
+        let running = self.rows.iter().filter(|row| row.running).count();
+        let tps: f64 = self.rows.iter().map(|row| row.tps).sum();
+        let connected = self.devices.iter().filter(|d| d.is_connected).count();
+        
+        let line = Line::from(vec![
+            Span::styled(" Merchant Emulator ", Style::new().add_modifier(Modifier::BOLD | Modifier::REVERSED)),
+            Span::raw(format!(
+                "  {} merchants, {} running  |  {:.1} TPS  |  {}/{} devices connected  |  {}",
+                self.rows.len(),
+                running,
+                tps,
+                connected,
+                self.devices.len(),
+                chrono::Local::now().format("%H:%M:%S"),
+            )),
+        ]);
+        frame.render_widget(Paragraph::new(line), area);
+    }
+    
+    fn draw_merchants(&mut self, frame: &mut Frame, area: Rect) {
+        let header = Row::new(["Merchant", "State", "TPS", "Success", "Sent", "Open", "Callbacks"])
+            .style(Style::new().add_modifier(Modifier::BOLD));
+        
+        let rows = self.rows.iter().map(|row| {
+            let (state, color) = if row.running {
+                if row.rate.as_ref().is_some_and(RateProgress::is_behind) {
+                    ("behind", Color::Yellow)
+                } else {
+                    ("running", Color::Green)
+                }
+            } else {
+                ("stopped", Color::DarkGray)
+            };
+            let callbacks = row.statistics.as_ref().map_or(0, |s| s.callbacks_received);
+            
+            Row::new(vec![
+                Cell::from(row.merchant.name.clone()),
+                Cell::from(state).style(Style::new().fg(color)),
+                Cell::from(format!("{:.1}", row.tps)),
+                Cell::from(row.success_rate().map_or("-".to_string(), |rate| format!("{:.1}%", rate)))
+                    .style(Style::new().fg(rate_color(row.success_rate()))),
+                Cell::from(row.requests().0.to_string()),
+                Cell::from(row.open_transactions.to_string()),
+                Cell::from(callbacks.to_string()),
+            ])
+        });
+        
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Fill(1),
+                Constraint::Length(8),
+                Constraint::Length(7),
+                Constraint::Length(8),
+                Constraint::Length(7),
+                Constraint::Length(6),
+                Constraint::Length(9),
+            ],
+        )
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(" Merchants "))
+        .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ");
+        
+        frame.render_stateful_widget(table, area, &mut self.table);
+    }
+    
+    fn draw_detail(&self, frame: &mut Frame, area: Rect) {
+        let Some(row) = self.selected() else {
+            let empty = Paragraph::new("No merchants yet. Create one from the main menu.")
+                .block(Block::default().borders(Borders::ALL).title(" Details "));
+            frame.render_widget(empty, area);
+            return;
+        };
+        
+        let label = Style::new().add_modifier(Modifier::BOLD);
+        let mut lines = Vec::new();
+        let config = &row.merchant.traffic_config;
+        
+        lines.push(Line::from(vec![
+            Span::styled("Generator: ", label),
+            Span::raw(match &row.method_id {
+                Some(method) => format!("running on {}", method),
+                None => "stopped".to_string(),
+            }),
+        ]));
+        let pacing = match &config.pacing {
+            TrafficPacing::Sequential => format!("sequential, {}ms ± {}ms", config.interval_ms, config.interval_variance),
+            TrafficPacing::Rate(rate) => format!("rate, {} stages, max {} in flight", rate.stages.len(), rate.max_concurrency),
+        };
+        lines.push(Line::from(vec![Span::styled("Pacing: ", label), Span::raw(pacing)]));
+        if let Some(max) = config.max_transactions {
+            lines.push(Line::from(vec![Span::styled("Limit: ", label), Span::raw(format!("{} orders", max))]));
+        }
+        if let Some(rate) = &row.rate {
+            lines.push(Line::from(vec![
+                Span::styled("Rate: ", label),
+                Span::raw(format!(
+                    "{:.1}/{:.1} TPS, {} in flight, lag {}ms (max {}ms)",
+                    rate.achieved_tps, rate.target_tps, rate.in_flight, rate.lag_ms, rate.max_lag_ms
+                )),
+            ]));
+            if let Some(time) = rate.simulated_time {
+                lines.push(Line::from(vec![
+                    Span::styled("Profile: ", label),
+                    Span::raw(format!("{} at x{:.2}", time.format("%a %H:%M"), rate.load_multiplier)),
+                ]));
+            }
+        }
+        
+        lines.push(Line::default());
+        match &row.statistics {
+            Some(stats) => {
+                lines.push(Line::from(vec![
+                    Span::styled("Orders: ", label),
+                    Span::raw(format!(
+                        "{} sent, {} ok, {} failed, {} cancelled, {} open",
+                        stats.total_requests, stats.successful_requests, stats.failed_requests, stats.cancelled_transactions, row.open_transactions
+                    )),
+                ]));
+                lines.push(Line::from(vec![
+                    Span::styled("Payouts: ", label),
+                    Span::raw(format!("{} created, {} failed", stats.payouts_created, stats.payouts_failed)),
+                ]));
+                lines.push(Line::from(vec![
+                    Span::styled("Callbacks: ", label),
+                    Span::raw(format!("{} received", stats.callbacks_received)),
+                ]));
+                lines.push(Line::from(vec![
+                    Span::styled("Retries: ", label),
+                    Span::raw(format!("{} attempts, {} recovered", stats.retry_attempts, stats.retries_recovered)),
+                ]));
+                
+                lines.push(Line::default());
+                lines.push(Line::styled("Errors", label));
+                let mut errors: Vec<_> = stats.error_breakdown.iter().collect();
+                errors.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+                if errors.is_empty() {
+                    lines.push(Line::styled("  none", Style::new().fg(Color::DarkGray)));
+                }
+                for (error, count) in errors.iter().take(TOP_ERRORS) {
+                    lines.push(Line::from(vec![
+                        Span::styled(format!("  {:>6}  ", count), Style::new().fg(Color::Red)),
+                        Span::raw(error.to_string()),
+                    ]));
+                }
+                if errors.len() > TOP_ERRORS {
+                    lines.push(Line::raw(format!("  ... and {} more", errors.len() - TOP_ERRORS)));
+                }
+            }
+            None => lines.push(Line::raw("No statistics yet")),
+        }
+        
+        let title = format!(" {} ", row.merchant.name);
+        frame.render_widget(Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title)), area);
+    }
+    
+    fn draw_devices(&self, frame: &mut Frame, area: Rect) {
+        let header = Row::new(["Device", "State", "Trader", "Battery", "Network", "Last active"])
+            .style(Style::new().add_modifier(Modifier::BOLD));
+        
+        let rows = self.devices.iter().map(|device| {
+            let (state, color) = if device.is_connected {
+                ("connected", Color::Green)
+            } else {
+                ("offline", Color::DarkGray)
+            };
+            Row::new(vec![
+                Cell::from(device.name.clone()),
+                Cell::from(state).style(Style::new().fg(color)),
+                Cell::from(device.trader_id.clone().unwrap_or_else(|| "-".to_string())),
+                Cell::from(format!("{}%", device.battery_level)),
+                Cell::from(device.network_info.clone()),
+                Cell::from(
+                    device
+                        .last_active_at
+                        .map_or("-".to_string(), |at| at.with_timezone(&chrono::Local).format("%H:%M:%S").to_string()),
+                ),
+            ])
+        });
+        
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Fill(1),
+                Constraint::Length(10),
+                Constraint::Fill(1),
+                Constraint::Length(8),
+                Constraint::Length(10),
+                Constraint::Length(12),
+            ],
+        )
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(" Devices "));
+        
+        frame.render_widget(table, area);
+    }
+    
+    fn draw_footer(&self, frame: &mut Frame, area: Rect) {
+        let keys = Span::styled(
+            " ↑/↓ select  s start/stop  x stop all  q quit ",
+            Style::new().add_modifier(Modifier::REVERSED),
+        );
+        let mut spans = vec![keys];
+        if let Some((message, is_error)) = &self.message {
+            let color = if *is_error { Color::Red } else { Color::Green };
+            spans.push(Span::styled(format!("  {}", message), Style::new().fg(color)));
+        }
+        if let Some(line) = &self.last_log {
+            spans.push(Span::styled(format!("  {}", line), Style::new().fg(log_color(line))));
+        }
+        frame.render_widget(Paragraph::new(Line::from(spans)), area);
+    }
+}
+
+/// Raw mode, the alternate screen and log capture, undone on drop so an
+/// error or panic inside the dashboard does not leave the terminal unusable.
+struct Screen {
+    log_capture: LogCaptureHandle,
+}
+
+impl Screen {
+    fn enter(log_capture: &LogCaptureHandle, logs: mpsc::Sender<String>) -> Result<Self> {
+        log_capture.set_channel(logs);
+        // Built first so a failure below still restores what was changed
+        let screen = Self { log_capture: log_capture.clone() };
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen)?;
+        Ok(screen)
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen, cursor::Show);
+        self.log_capture.clear_channel();
+    }
+}
+
+/// Captured lines read `<timestamp> <LEVEL> <target>: <message>`.
+fn log_color(line: &str) -> Color {
+    match line.split_whitespace().nth(1) {
+        Some("ERROR") => Color::Red,
+        Some("WARN") => Color::Yellow,
+        _ => Color::DarkGray,
+    }
+}
+
+fn rate_color(rate: Option<f64>) -> Color {
+    match rate {
+        Some(rate) if rate >= 99.0 => Color::Green,
+        Some(rate) if rate >= 90.0 => Color::Yellow,
+        Some(_) => Color::Red,
+        None => Color::DarkGray,
+    }
+}
\ No newline at end of file
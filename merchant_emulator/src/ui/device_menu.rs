@@ -1,9 +1,8 @@
-use crate::device::{DeviceManager, DeviceApiClient, ConnectDeviceRequest, NotificationRequest, DevicePingService};
-use crate::models::Config;
+use crate::device::{DeviceManager, DeviceApiClient, ConnectDeviceRequest, DevicePingService};
+use crate::services::device_notification_service::DeviceNotificationService;
 use anyhow::Result;
 use dialoguer::{theme::ColorfulTheme, Input, Select};
 use std::sync::Arc;
-use tokio::sync::RwLock;
 use chrono::Utc;
 
 pub enum DeviceMenuItem {
@@ -38,21 +37,25 @@ pub struct DeviceMenu {
     device_manager: Arc<DeviceManager>,
     api_client: Arc<DeviceApiClient>,
     ping_service: Arc<DevicePingService>,
-    config: Arc<RwLock<Config>>,
+    device_notification_service: DeviceNotificationService,
 }
 
 impl DeviceMenu {
     pub fn new(
-        config: Arc<RwLock<Config>>,
         device_manager: Arc<DeviceManager>,
         api_client: Arc<DeviceApiClient>,
         ping_service: Arc<DevicePingService>
     ) -> Self {
+        let device_notification_service = DeviceNotificationService::new(
+            device_manager.clone(),
+            api_client.clone(),
+        );
+
         Self {
             device_manager,
             api_client,
             ping_service,
-            config,
+            device_notification_service,
         }
     }
 
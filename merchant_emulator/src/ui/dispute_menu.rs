@@ -0,0 +1,267 @@
+use crate::models::{Dispute, DisputeKind, Merchant, TransactionStatus};
+use crate::services::{MerchantService, StorageService};
+use crate::ui::MainMenu;
+use anyhow::Result;
+use console::Style;
+use dialoguer::{theme::ColorfulTheme, Input, Select};
+use std::sync::Arc;
+
+pub enum DisputeMenuItem {
+    OpenDealDispute,
+    OpenPayoutDispute,
+    ViewDealDisputes,
+    ViewPayoutDisputes,
+    Back,
+}
+
+impl std::fmt::Display for DisputeMenuItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DisputeMenuItem::OpenDealDispute => write!(f, "Open Deal Dispute"),
+            DisputeMenuItem::OpenPayoutDispute => write!(f, "Open Payout Dispute"),
+            DisputeMenuItem::ViewDealDisputes => write!(f, "View Deal Disputes"),
+            DisputeMenuItem::ViewPayoutDisputes => write!(f, "View Payout Disputes"),
+            DisputeMenuItem::Back => write!(f, "Back to Merchant Menu"),
+        }
+    }
+}
+
+pub struct DisputeMenu {
+    merchant_service: Arc<MerchantService>,
+    storage: Arc<StorageService>,
+}
+
+impl DisputeMenu {
+    pub fn new(merchant_service: Arc<MerchantService>, storage: Arc<StorageService>) -> Self {
+        Self {
+            merchant_service,
+            storage,
+        }
+    }
+    
+    pub async fn run(&self, merchant: &Merchant) -> Result<()> {
+        loop {
+            let items = [
+                DisputeMenuItem::OpenDealDispute,
+                DisputeMenuItem::OpenPayoutDispute,
+                DisputeMenuItem::ViewDealDisputes,
+                DisputeMenuItem::ViewPayoutDisputes,
+                DisputeMenuItem::Back,
+            ];
+            
+            let selection = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt("Disputes")
+                .items(&items)
+                .default(0)
+                .interact()?;
+            
+            match items[selection] {
+                DisputeMenuItem::OpenDealDispute => self.open_deal_dispute(merchant).await?,
+                DisputeMenuItem::OpenPayoutDispute => self.open_payout_dispute(merchant).await?,
+                DisputeMenuItem::ViewDealDisputes => self.browse_disputes(merchant, DisputeKind::Deal).await?,
+                DisputeMenuItem::ViewPayoutDisputes => self.browse_disputes(merchant, DisputeKind::Payout).await?,
+                DisputeMenuItem::Back => break,
+            }
+        }
+        
+        Ok(())
+    }
+    
+    async fn open_deal_dispute(&self, merchant: &Merchant) -> Result<()> {
+        // The backend only accepts disputes on deals in progress or completed
+        let deals: Vec<_> = self.storage
+            .get_merchant_transactions(&merchant.id)
+            .into_iter()
+            .rev()
+            .filter(|h| h.error.is_none() && h.dispute_id.is_none())
+            .filter(|h| matches!(h.transaction.status, TransactionStatus::InProgress | TransactionStatus::Ready))
+            .take(50)
+            .collect();
+        
+        if deals.is_empty() {
+            MainMenu::show_info("No deals in progress or completed without a dispute");
+            return Ok(());
+        }
+        
+        let mut items: Vec<String> = deals
+            .iter()
+            .map(|h| format!("{} | {} | {:.2} RUB | {:?}",
+                h.transaction.order_id, h.transaction.id, h.transaction.amount, h.transaction.status))
+            .collect();
+        items.push("Back".to_string());
+        
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("Select deal to dispute")
+            .items(&items)
+            .default(0)
+            .interact()?;
+        
+        let Some(history) = deals.get(selection) else {
+            return Ok(());
+        };
+        
+        let message = Self::get_message(merchant.traffic_config.dispute_messages.first().map(String::as_str))?;
+        
+        match self.merchant_service.open_deal_dispute(merchant, &history.transaction, &message).await {
+            Ok(dispute) => MainMenu::show_success(&format!("Dispute {} opened ({:?})", dispute.id, dispute.status)),
+            Err(e) => MainMenu::show_error(&format!("Failed to open dispute: {}", e)),
+        }
+        
+        Ok(())
+    }
+    
+    async fn open_payout_dispute(&self, merchant: &Merchant) -> Result<()> {
+        let payouts: Vec<_> = self.storage
+            .get_merchant_payouts(&merchant.id)
+            .into_iter()
+            .rev()
+            .filter_map(|h| h.payout)
+            .take(50)
+            .collect();
+        
+        let mut items: Vec<String> = payouts
+            .iter()
+            .map(|p| format!("{} | {:.2} RUB | {:?}", p.id, p.amount, p.status))
+            .collect();
+        items.push("Enter payout ID manually".to_string());
+        
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("Select payout to dispute")
+            .items(&items)
+            .default(0)
+            .interact()?;
+        
+        let payout_id = match payouts.get(selection) {
+            Some(payout) => payout.id.clone(),
+            None => Input::<String>::with_theme(&ColorfulTheme::default())
+                .with_prompt("Payout ID")
+                .interact_text()?,
+        };
+        
+        let message = Self::get_message(None)?;
+        
+        match self.merchant_service.open_payout_dispute(merchant, &payout_id, &message).await {
+            Ok(dispute) => MainMenu::show_success(&format!("Dispute {} opened ({:?})", dispute.id, dispute.status)),
+            Err(e) => MainMenu::show_error(&format!("Failed to open dispute: {}", e)),
+        }
+        
+        Ok(())
+    }
+    
+    async fn browse_disputes(&self, merchant: &Merchant, kind: DisputeKind) -> Result<()> {
+        loop {
+            let disputes = match self.merchant_service.list_disputes(merchant, kind).await {
+                Ok(disputes) => disputes,
+                Err(e) => {
+                    MainMenu::show_error(&format!("Failed to load disputes: {}", e));
+                    return Ok(());
+                }
+            };
+            
+            if disputes.is_empty() {
+                MainMenu::show_info("No disputes found");
+                return Ok(());
+            }
+            
+            let mut items: Vec<String> = disputes
+                .iter()
+                .map(|d| format!("{} | {} | {:?} | {} messages",
+                    d.created_at,
+                    d.deal_id.as_deref().or(d.payout_id.as_deref()).unwrap_or("-"),
+                    d.status,
+                    d.messages.len()))
+                .collect();
+            items.push("Back".to_string());
+            
+            let selection = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt("Select dispute")
+                .items(&items)
+                .default(0)
+                .interact()?;
+            
+            let Some(summary) = disputes.get(selection) else {
+                return Ok(());
+            };
+            
+            self.dispute_details(merchant, kind, &summary.id).await?;
+        }
+    }
+    
+    async fn dispute_details(&self, merchant: &Merchant, kind: DisputeKind, dispute_id: &str) -> Result<()> {
+        loop {
+            let dispute = match self.merchant_service.get_dispute(merchant, kind, dispute_id).await {
+                Ok(dispute) => dispute,
+                Err(e) => {
+                    MainMenu::show_error(&format!("Failed to load dispute: {}", e));
+                    return Ok(());
+                }
+            };
+            
+            Self::show_dispute(&dispute);
+            
+            if !dispute.status.is_open() {
+                println!("\nPress Enter to continue...");
+                let _ = std::io::stdin().read_line(&mut String::new());
+                return Ok(());
+            }
+            
+            let items = ["Send message", "Refresh", "Back"];
+            let selection = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt("Dispute")
+                .items(&items)
+                .default(0)
+                .interact()?;
+            
+            match selection {
+                0 => {
+                    let message = Self::get_message(None)?;
+                    match self.merchant_service.send_dispute_message(merchant, kind, &dispute.id, &message).await {
+                        Ok(_) => MainMenu::show_success("Message sent"),
+                        Err(e) => MainMenu::show_error(&format!("Failed to send message: {}", e)),
+                    }
+                }
+                1 => {}
+                _ => return Ok(()),
+            }
+        }
+    }
+    
+    fn show_dispute(dispute: &Dispute) {
+        let label = Style::new().bold();
+        
+        println!("\n{}", label.apply_to(format!("Dispute {}", dispute.id)));
+        if let Some(deal_id) = &dispute.deal_id {
+            println!("  Deal: {}", deal_id);
+        }
+        if let Some(payout_id) = &dispute.payout_id {
+            println!("  Payout: {}", payout_id);
+        }
+        println!("  Status: {:?}", dispute.status);
+        println!("  Created: {}", dispute.created_at);
+        if let Some(resolved_at) = &dispute.resolved_at {
+            println!("  Resolved: {}", resolved_at);
+        }
+        if let Some(resolution) = &dispute.resolution {
+            println!("  Resolution: {}", resolution);
+        }
+        
+        println!("\n{}", label.apply_to("Messages"));
+        for message in &dispute.messages {
+            println!("  [{}] {:?}: {}", message.created_at, message.sender_type, message.message);
+            for attachment in &message.attachments {
+                println!("      📎 {} ({} bytes)", attachment.filename, attachment.size);
+            }
+        }
+    }
+    
+    fn get_message(default: Option<&str>) -> Result<String> {
+        let theme = ColorfulTheme::default();
+        let mut input = Input::<String>::with_theme(&theme).with_prompt("Message");
+        
+        if let Some(default) = default {
+            input = input.default(default.to_string());
+        }
+        
+        Ok(input.interact_text()?)
+    }
+}
@@ -11,6 +11,8 @@ pub enum MenuItem {
     ViewStatistics,
     ExportData,
     DeviceEmulator,
+    RunScenario,
+    Dashboard,
     Settings,
     Exit,
 }
@@ -23,6 +25,8 @@ impl MainMenu {
             "View Global Statistics",
             "Export Data",
             "Device Emulator",
+            "Run Scenario",
+            "Live Dashboard",
             "Settings",
             "Exit",
         ];
@@ -39,12 +43,14 @@ impl MainMenu {
             2 => MenuItem::ViewStatistics,
             3 => MenuItem::ExportData,
             4 => MenuItem::DeviceEmulator,
-            5 => MenuItem::Settings,
+            5 => MenuItem::RunScenario,
+            6 => MenuItem::Dashboard,
+            7 => MenuItem::Settings,
             _ => MenuItem::Exit,
         })
     }
     
-    pub fn get_merchant_details() -> Result<(String, String)> {
+    pub fn get_merchant_details() -> Result<(String, String, Option<String>)> {
         let name = Input::<String>::with_theme(&ColorfulTheme::default())
             .with_prompt("Merchant name")
             .interact_text()?;
@@ -53,7 +59,24 @@ impl MainMenu {
             .with_prompt("API key")
             .interact_text()?;
             
-        Ok((name, api_key))
+        let private_key = Input::<String>::with_theme(&ColorfulTheme::default())
+            .with_prompt("Private key for signature verification (leave empty to skip)")
+            .allow_empty(true)
+            .interact_text()?;
+            
+        let private_key = if private_key.trim().is_empty() {
+            None
+        } else {
+            Some(private_key.trim().to_string())
+        };
+            
+        Ok((name, api_key, private_key))
+    }
+    
+    pub fn get_scenario_path() -> Result<String> {
+        Ok(Input::<String>::with_theme(&ColorfulTheme::default())
+            .with_prompt("Scenario file (YAML)")
+            .interact_text()?)
     }
     
     pub fn confirm_action(message: &str) -> Result<bool> {
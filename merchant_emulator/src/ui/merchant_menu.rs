@@ -1,10 +1,17 @@
-use crate::models::{Merchant, Method};
+use crate::models::{CallbackBehavior, Merchant, Method, ProtocolKind, WellbitPaymentType, WellbitSettings, Receipt, ReceiptFormat, ReceivedCallback, ReconciliationReport, TransactionHistory, TransactionStatus};
 use dialoguer::{theme::ColorfulTheme, Select, Input, Confirm};
 use anyhow::Result;
 use console::Style;
 
 pub struct MerchantMenu;
 
+#[derive(Debug, Clone)]
+pub enum CallbackLogAction {
+    View(usize),
+    Export,
+    Back,
+}
+
 #[derive(Debug, Clone)]
 pub enum MerchantMenuItem {
     ConfigureTraffic,
@@ -12,9 +19,17 @@ pub enum MerchantMenuItem {
     StartTrafficQuiet,
     StopTraffic,
     ViewTransactions,
+    CancelTransaction,
+    Receipts,
+    Disputes,
+    ViewCallbackLog,
+    Reconciliation,
     ViewStatistics,
     ExportData,
     ConfigureCallback,
+    ConfigureCallbackBehavior,
+    ConfigurePrivateKey,
+    ConfigureProtocol,
     TogglePaymentType,
     SetLiquidity,
     ViewLogs,
@@ -36,13 +51,14 @@ impl MerchantMenu {
         };
         
         let header = format!(
-            "\n{} - {} ({})\nBalance: {} USDT | Liquidity: {}% | Payment Type: {:?}",
+            "\n{} - {} ({})\nBalance: {} USDT | Liquidity: {}% | Payment Type: {:?} | Protocol: {}",
             Style::new().bold().apply_to(&merchant.name),
             if merchant.is_active { "Active" } else { "Inactive" },
             traffic_status,
             merchant.balance_usdt,
             merchant.liquidity_percentage,
-            merchant.payment_type
+            merchant.payment_type,
+            merchant.protocol
         );
         
         println!("{}", header);
@@ -63,9 +79,17 @@ impl MerchantMenu {
         
         items.extend_from_slice(&[
             "View Transactions",
+            "Cancel Transaction by Order ID",
+            "Transaction Receipts",
+            "Disputes",
+            "View Callback Log",
+            "Callback Reconciliation Report",
             "View Statistics",
             "Export Data",
             "Configure Callback URL",
+            "Configure Callback Responses",
+            "Configure Private Key (Signatures)",
+            "Configure Merchant Protocol (Native/Wellbit)",
             "Toggle Payment Type (RUB/USDT)",
             "Set Liquidity Percentage",
             "Back to Main Menu",
@@ -85,26 +109,42 @@ impl MerchantMenu {
             (2, false) => MerchantMenuItem::StartTrafficQuiet,
             (n, true) if !is_quiet => match n - 3 {
                 0 => MerchantMenuItem::ViewTransactions,
-                1 => MerchantMenuItem::ViewStatistics,
-                2 => MerchantMenuItem::ExportData,
-                3 => MerchantMenuItem::ConfigureCallback,
-                4 => MerchantMenuItem::TogglePaymentType,
-                5 => MerchantMenuItem::SetLiquidity,
+                1 => MerchantMenuItem::CancelTransaction,
+                2 => MerchantMenuItem::Receipts,
+                3 => MerchantMenuItem::Disputes,
+                4 => MerchantMenuItem::ViewCallbackLog,
+                5 => MerchantMenuItem::Reconciliation,
+                6 => MerchantMenuItem::ViewStatistics,
+                7 => MerchantMenuItem::ExportData,
+                8 => MerchantMenuItem::ConfigureCallback,
+                9 => MerchantMenuItem::ConfigureCallbackBehavior,
+                10 => MerchantMenuItem::ConfigurePrivateKey,
+                11 => MerchantMenuItem::ConfigureProtocol,
+                12 => MerchantMenuItem::TogglePaymentType,
+                13 => MerchantMenuItem::SetLiquidity,
                 _ => MerchantMenuItem::Back,
             },
             (n, _) => match n - 2 {
                 0 => MerchantMenuItem::ViewTransactions,
-                1 => MerchantMenuItem::ViewStatistics,
-                2 => MerchantMenuItem::ExportData,
-                3 => MerchantMenuItem::ConfigureCallback,
-                4 => MerchantMenuItem::TogglePaymentType,
-                5 => MerchantMenuItem::SetLiquidity,
+                1 => MerchantMenuItem::CancelTransaction,
+                2 => MerchantMenuItem::Receipts,
+                3 => MerchantMenuItem::Disputes,
+                4 => MerchantMenuItem::ViewCallbackLog,
+                5 => MerchantMenuItem::Reconciliation,
+                6 => MerchantMenuItem::ViewStatistics,
+                7 => MerchantMenuItem::ExportData,
+                8 => MerchantMenuItem::ConfigureCallback,
+                9 => MerchantMenuItem::ConfigureCallbackBehavior,
+                10 => MerchantMenuItem::ConfigurePrivateKey,
+                11 => MerchantMenuItem::ConfigureProtocol,
+                12 => MerchantMenuItem::TogglePaymentType,
+                13 => MerchantMenuItem::SetLiquidity,
                 _ => MerchantMenuItem::Back,
             },
         })
     }
     
-    pub fn get_callback_url() -> Result<Option<String>> {
+    pub fn get_callback_url(default_url: &str) -> Result<Option<String>> {
         let use_callback = Confirm::with_theme(&ColorfulTheme::default())
             .with_prompt("Do you want to set a callback URL?")
             .default(false)
@@ -113,7 +153,7 @@ impl MerchantMenu {
         if use_callback {
             let url = Input::<String>::with_theme(&ColorfulTheme::default())
                 .with_prompt("Callback URL")
-                .default(format!("http://localhost:8080/callback"))
+                .default(default_url.to_string())
                 .interact_text()?;
             Ok(Some(url))
         } else {
@@ -121,6 +161,341 @@ impl MerchantMenu {
         }
     }
     
+    pub fn configure_callback_behavior(current: &CallbackBehavior) -> Result<CallbackBehavior> {
+        let status_code = Self::get_status_code("Response status code", current.status_code)?;
+        
+        let latency_ms = Input::<u64>::with_theme(&ColorfulTheme::default())
+            .with_prompt("Response latency (ms)")
+            .default(current.latency_ms)
+            .interact_text()?;
+            
+        let failure_rate = Self::get_rate("Failure rate (%)", current.failure_rate)?;
+        let failure_status_code = Self::get_status_code("Failure status code", current.failure_status_code)?;
+        let timeout_rate = Self::get_rate("Timeout rate (%)", current.timeout_rate)?;
+        
+        let timeout_ms = Input::<u64>::with_theme(&ColorfulTheme::default())
+            .with_prompt("Timeout duration (ms)")
+            .default(current.timeout_ms)
+            .interact_text()?;
+            
+        let malformed_rate = Self::get_rate("Malformed body rate (%)", current.malformed_rate)?;
+        
+        if failure_rate + timeout_rate + malformed_rate > 100.0 {
+            eprintln!("Warning: failure, timeout and malformed rates add up to more than 100%");
+        }
+        
+        Ok(CallbackBehavior {
+            status_code,
+            latency_ms,
+            failure_rate,
+            failure_status_code,
+            timeout_rate,
+            timeout_ms,
+            malformed_rate,
+        })
+    }
+    
+    fn get_status_code(prompt: &str, default: u16) -> Result<u16> {
+        Ok(Input::<u16>::with_theme(&ColorfulTheme::default())
+            .with_prompt(prompt)
+            .default(default)
+            .validate_with(|input: &u16| {
+                if (100..=599).contains(input) {
+                    Ok(())
+                } else {
+                    Err("Status code must be between 100 and 599")
+                }
+            })
+            .interact_text()?)
+    }
+    
+    fn get_rate(prompt: &str, default: f64) -> Result<f64> {
+        Ok(Input::<f64>::with_theme(&ColorfulTheme::default())
+            .with_prompt(prompt)
+            .default(default)
+            .validate_with(|input: &f64| {
+                if (0.0..=100.0).contains(input) {
+                    Ok(())
+                } else {
+                    Err("Rate must be between 0 and 100")
+                }
+            })
+            .interact_text()?)
+    }
+    
+    pub fn get_private_key() -> Result<Option<String>> {
+        let use_key = Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Verify response signatures with a private key?")
+            .default(true)
+            .interact()?;
+            
+        if use_key {
+            let key = Input::<String>::with_theme(&ColorfulTheme::default())
+                .with_prompt("Private key")
+                .interact_text()?;
+            Ok(Some(key.trim().to_string()))
+        } else {
+            Ok(None)
+        }
+    }
+    
+    pub fn configure_protocol(current: &Merchant) -> Result<(ProtocolKind, WellbitSettings)> {
+        let protocols = [ProtocolKind::Native, ProtocolKind::Wellbit];
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("Merchant protocol")
+            .items(&protocols)
+            .default(if current.protocol == ProtocolKind::Wellbit { 1 } else { 0 })
+            .interact()?;
+            
+        if protocols[selection] == ProtocolKind::Native {
+            return Ok((ProtocolKind::Native, current.wellbit.clone()));
+        }
+        
+        let settings = &current.wellbit;
+        
+        let api_key_public = Input::<String>::with_theme(&ColorfulTheme::default())
+            .with_prompt("Wellbit public key (x-api-key)")
+            .with_initial_text(settings.api_key_public.clone())
+            .interact_text()?;
+            
+        let payment_types = ["card", "sbp"];
+        let payment_type = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("Payment type")
+            .items(&payment_types)
+            .default(if settings.payment_type == WellbitPaymentType::Sbp { 1 } else { 0 })
+            .interact()?;
+            
+        let bank = Input::<String>::with_theme(&ColorfulTheme::default())
+            .with_prompt("Wellbit bank code, e.g. SBERRUB (empty for any bank)")
+            .allow_empty(true)
+            .with_initial_text(settings.bank.clone().unwrap_or_default())
+            .interact_text()?;
+            
+        let course = Input::<f64>::with_theme(&ColorfulTheme::default())
+            .with_prompt("Payment course (RUB per USDT)")
+            .default(settings.course)
+            .validate_with(|input: &f64| {
+                if *input > 0.0 {
+                    Ok(())
+                } else {
+                    Err("Course must be positive")
+                }
+            })
+            .interact_text()?;
+            
+        let lifetime_secs = Input::<u64>::with_theme(&ColorfulTheme::default())
+            .with_prompt("Payment lifetime (seconds)")
+            .default(settings.lifetime_secs)
+            .interact_text()?;
+            
+        if current.private_key.is_none() {
+            eprintln!("Warning: Wellbit requests are signed with the private key, which is not set");
+        }
+        
+        Ok((ProtocolKind::Wellbit, WellbitSettings {
+            api_key_public: api_key_public.trim().to_string(),
+            payment_type: if payment_type == 1 { WellbitPaymentType::Sbp } else { WellbitPaymentType::Card },
+            bank: Some(bank.trim().to_uppercase()).filter(|b| !b.is_empty()),
+            course,
+            lifetime_secs,
+        }))
+    }
+    
+    pub fn select_order_to_cancel(transactions: &[TransactionHistory]) -> Result<String> {
+        // Offer the most recent orders that can still be cancelled
+        let open: Vec<&TransactionHistory> = transactions
+            .iter()
+            .rev()
+            .filter(|tx| tx.error.is_none()
+                && !matches!(tx.transaction.status, TransactionStatus::Canceled | TransactionStatus::Expired))
+            .take(20)
+            .collect();
+        
+        let mut items: Vec<String> = open
+            .iter()
+            .map(|tx| format!("{} | {} RUB | {:?}",
+                tx.transaction.order_id, tx.transaction.amount, tx.transaction.status))
+            .collect();
+        items.push("Enter order ID manually".to_string());
+        
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("Select order to cancel")
+            .items(&items)
+            .default(0)
+            .interact()?;
+            
+        if selection < open.len() {
+            Ok(open[selection].transaction.order_id.clone())
+        } else {
+            Ok(Input::<String>::with_theme(&ColorfulTheme::default())
+                .with_prompt("Order ID")
+                .interact_text()?)
+        }
+    }
+    
+    pub fn select_transaction_for_receipts(transactions: &[TransactionHistory]) -> Result<Option<TransactionHistory>> {
+        // Receipts can only be attached to orders the backend accepted
+        let created: Vec<&TransactionHistory> = transactions
+            .iter()
+            .rev()
+            .filter(|tx| tx.error.is_none())
+            .take(20)
+            .collect();
+        
+        let mut items: Vec<String> = created
+            .iter()
+            .map(|tx| format!("{} | {} RUB | {:?} | {} receipt(s)",
+                tx.transaction.order_id, tx.transaction.amount, tx.transaction.status, tx.receipts.len()))
+            .collect();
+        items.push("Back".to_string());
+        
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("Select transaction")
+            .items(&items)
+            .default(0)
+            .interact()?;
+            
+        Ok(created.get(selection).map(|tx| (*tx).clone()))
+    }
+    
+    pub fn select_receipt_upload(receipts: &[Receipt]) -> Result<Option<ReceiptFormat>> {
+        if receipts.is_empty() {
+            println!("\nNo receipts uploaded for this transaction");
+        } else {
+            println!("\nReceipts:");
+            for receipt in receipts {
+                println!("  {} | {} | checked: {} | fake: {} | auto: {}",
+                    receipt.created_at, receipt.file_name, receipt.is_checked, receipt.is_fake, receipt.is_auto);
+            }
+        }
+        
+        let items = ["Upload synthetic PNG receipt", "Upload synthetic PDF receipt", "Back"];
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("Receipts")
+            .items(&items)
+            .default(2)
+            .interact()?;
+            
+        Ok(match selection {
+            0 => Some(ReceiptFormat::Png),
+            1 => Some(ReceiptFormat::Pdf),
+            _ => None,
+        })
+    }
+    
+    pub fn browse_callback_log(callbacks: &[ReceivedCallback]) -> Result<CallbackLogAction> {
+        // Most recent first
+        let mut items: Vec<String> = callbacks
+            .iter()
+            .rev()
+            .take(50)
+            .map(|c| {
+                let summary = match &c.callback {
+                    Some(callback) => format!("{} -> {}", callback.id, callback.status),
+                    None => "unparseable body".to_string(),
+                };
+                format!("{} | {} | {:?} | signature: {:?}",
+                    c.received_at.format("%H:%M:%S"), summary, c.outcome, c.signature)
+            })
+            .collect();
+        let shown = items.len();
+        items.push("Export callback log".to_string());
+        items.push("Back".to_string());
+        
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("Callback log ({} received)", callbacks.len()))
+            .items(&items)
+            .default(0)
+            .interact()?;
+            
+        Ok(if selection < shown {
+            CallbackLogAction::View(callbacks.len() - 1 - selection)
+        } else if selection == shown {
+            CallbackLogAction::Export
+        } else {
+            CallbackLogAction::Back
+        })
+    }
+    
+    pub fn show_callback_details(callback: &ReceivedCallback) {
+        let label = Style::new().bold();
+        
+        println!("\n{}", label.apply_to(format!("Callback {}", callback.id)));
+        println!("  Received: {}", callback.received_at);
+        println!("  Path: {}", callback.path);
+        println!("  Matched order: {}", callback.matched_order_id.as_deref().unwrap_or("None"));
+        println!("  Signature: {:?}", callback.signature);
+        println!("  Response: {:?}", callback.outcome);
+        if let Some(error) = &callback.parse_error {
+            println!("  Parse error: {}", error);
+        }
+        
+        println!("\n{}", label.apply_to("Headers:"));
+        for (name, value) in &callback.headers {
+            println!("  {}: {}", name, value);
+        }
+        
+        println!("\n{}", label.apply_to("Body:"));
+        match serde_json::from_str::<serde_json::Value>(&callback.raw_body) {
+            Ok(value) => println!("{}", serde_json::to_string_pretty(&value).unwrap_or_default()),
+            Err(_) => println!("{}", callback.raw_body),
+        }
+        
+        println!("\nPress Enter to continue...");
+        let _ = std::io::stdin().read_line(&mut String::new());
+    }
+    
+    pub fn show_reconciliation_report(report: &ReconciliationReport) -> Result<bool> {
+        let label = Style::new().bold();
+        let warn = Style::new().yellow();
+        
+        println!("\n{}", label.apply_to("Callback Reconciliation"));
+        println!("  Transactions: {}", report.total_transactions);
+        println!("  Callbacks received: {}", report.total_callbacks);
+        
+        println!("\n{}", label.apply_to(format!("Transactions without callback: {}", report.missing_callbacks.len())));
+        for missing in report.missing_callbacks.iter().take(10) {
+            println!("  {} | {:?} | created {}", missing.order_id, missing.status, missing.created_at.format("%H:%M:%S"));
+        }
+        println!("  Still in flight: {}", report.pending_callbacks.len());
+        
+        println!("\n{}", label.apply_to(format!("Duplicate callbacks: {}", report.duplicate_callbacks.len())));
+        for duplicate in report.duplicate_callbacks.iter().take(10) {
+            println!("  {}", warn.apply_to(format!("{} | {} x{}", duplicate.order_id, duplicate.status, duplicate.count)));
+        }
+        
+        println!("\n{}", label.apply_to(format!("Callbacks for unknown orders: {}", report.unknown_order_callbacks.len())));
+        for unknown in report.unknown_order_callbacks.iter().take(10) {
+            println!("  {}", warn.apply_to(format!("{} | {} | {}", unknown.id, unknown.status, unknown.received_at.format("%H:%M:%S"))));
+        }
+        
+        println!("\n{}", label.apply_to(format!("Unattributed callbacks (any merchant): {}", report.unattributed_callbacks.len())));
+        for unknown in report.unattributed_callbacks.iter().take(10) {
+            println!("  {}", warn.apply_to(format!("{} | {} | {}", unknown.id, unknown.status, unknown.received_at.format("%H:%M:%S"))));
+        }
+        
+        println!("\n{}", label.apply_to(format!("Out-of-order status changes: {}", report.out_of_order.len())));
+        for change in report.out_of_order.iter().take(10) {
+            println!("  {}", warn.apply_to(format!("{} | {} after {}", change.order_id, change.status, change.previous_status)));
+        }
+        
+        println!("\n{}", label.apply_to("Callback delay from create response:"));
+        match &report.delay {
+            Some(delay) => {
+                println!("  Samples: {}", delay.samples);
+                println!("  Min: {} ms | p50: {} ms | p90: {} ms | p95: {} ms | p99: {} ms | Max: {} ms",
+                    delay.min_ms, delay.p50_ms, delay.p90_ms, delay.p95_ms, delay.p99_ms, delay.max_ms);
+            }
+            None => println!("  No matched callbacks"),
+        }
+        println!();
+        
+        Ok(Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Export report to JSON?")
+            .default(false)
+            .interact()?)
+    }
+    
     pub fn get_liquidity_percentage() -> Result<f64> {
         loop {
             let input = Input::<String>::with_theme(&ColorfulTheme::default())
@@ -152,26 +527,6 @@ impl MerchantMenu {
         }
     }
     
-    pub fn select_method_id() -> Result<String> {
-        // In a real implementation, this would fetch available methods from the API
-        // For now, we'll use a hardcoded list
-        let methods = vec![
-            ("1", "Bank Card (RUB)"),
-            ("2", "USDT TRC-20"),
-            ("3", "SBP (Fast Payment System)"),
-        ];
-        
-        let items: Vec<&str> = methods.iter().map(|(_, name)| *name).collect();
-        
-        let selection = Select::with_theme(&ColorfulTheme::default())
-            .with_prompt("Select payment method")
-            .items(&items)
-            .default(0)
-            .interact()?;
-            
-        Ok(methods[selection].0.to_string())
-    }
-    
     pub fn select_method_from_list(methods: &[Method]) -> Result<String> {
         let items: Vec<String> = methods
             .iter()
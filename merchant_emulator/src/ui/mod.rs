@@ -2,10 +2,14 @@ pub mod menu;
 pub mod merchant_menu;
 pub mod traffic_menu;
 pub mod device_menu;
+pub mod dispute_menu;
 pub mod log_viewer;
+pub mod dashboard;
 
 pub use menu::{MainMenu, MenuItem};
-pub use merchant_menu::{MerchantMenu, MerchantMenuItem};
+pub use merchant_menu::{CallbackLogAction, MerchantMenu, MerchantMenuItem};
 pub use traffic_menu::TrafficMenu;
 pub use device_menu::DeviceMenu;
-pub use log_viewer::LogViewer;
\ No newline at end of file
+pub use dispute_menu::DisputeMenu;
+pub use log_viewer::LogViewer;
+pub use dashboard::Dashboard;
\ No newline at end of file
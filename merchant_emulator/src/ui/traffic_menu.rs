@@ -1,7 +1,9 @@
-use crate::models::{TrafficConfig, AmountRange};
+use crate::models::{
+    AmountDistribution, AmountModel, AmountRounding, BurstWindow, BuyerBehavior, ConversionBand, DayFilter, RateConfig, RateStage,
+    TrafficConfig, TrafficMode, TrafficPacing, TrafficProfile, WeightedRange,
+};
 use dialoguer::{theme::ColorfulTheme, Select, Input, Confirm};
 use anyhow::Result;
-use std::collections::HashMap;
 
 pub struct TrafficMenu;
 
@@ -11,23 +13,74 @@ impl TrafficMenu {
         
         loop {
             println!("\nCurrent Traffic Configuration:");
-            println!("  Interval: {} ms (±{} ms)", config.interval_ms, config.interval_variance);
+            println!("  Mode: {:?}", config.mode);
+            match &config.pacing {
+                TrafficPacing::Sequential => {
+                    println!("  Pacing: one at a time, {} ms (±{} ms) apart", config.interval_ms, config.interval_variance);
+                }
+                TrafficPacing::Rate(rate) => {
+                    println!("  Pacing: fixed rate");
+                    for line in rate.describe() {
+                        println!("    {}", line);
+                    }
+                }
+            }
             println!("  Max transactions: {:?}", config.max_transactions);
             println!("  Created so far: {}", config.created_count);
-            println!("\n  Amount Probabilities:");
+            if config.profile.enabled {
+                println!("  Time-of-day profile:");
+                for line in config.profile.describe() {
+                    println!("    {}", line);
+                }
+            } else {
+                println!("  Time-of-day profile: off");
+            }
+            match config.seed {
+                Some(seed) => println!("  Seed: {}", seed),
+                None => println!("  Seed: random each run"),
+            }
+            println!("  Cancellations: {}% after {} ms", config.cancel_percentage, config.cancel_delay_ms);
+            println!("  Receipts: {}% of orders", config.receipt_percentage);
+            println!("  Disputes: {}% of completed deals ({} scripted messages)",
+                config.dispute_percentage, config.dispute_messages.len());
+            println!("\n  Amount Distribution:");
+            for line in config.amount_distribution.describe() {
+                println!("    {}", line);
+            }
             
-            let mut ranges: Vec<_> = config.amount_probabilities.iter().collect();
-            ranges.sort_by_key(|(range, _)| range.min);
+            println!("\n  Payout Amount Distribution:");
+            for line in config.payout_amount_distribution.describe() {
+                println!("    {}", line);
+            }
             
-            for (range, prob) in &ranges {
-                println!("    {}-{}: {}%", range.min, range.max, prob);
+            let buyer = &config.buyer;
+            if buyer.enabled {
+                println!("\n  Buyer Behaviour: pays after {}-{} ms; {}% of unpaid orders cancelled after {} ms",
+                    buyer.pay_delay_min_ms, buyer.pay_delay_max_ms, buyer.abandon_cancel_percentage, buyer.abandon_cancel_delay_ms);
+                for band in &buyer.bands {
+                    println!("    {}-{} RUB: {}% pay", band.min, band.max, band.pay_percentage);
+                }
+                println!("    Other amounts: {}% pay", buyer.other_pay_percentage);
+            } else {
+                println!("\n  Buyer Behaviour: off");
             }
             
             let items = vec![
                 "Set interval (ms)",
                 "Set interval variance (ms)",
                 "Set max transactions",
-                "Configure amount probabilities",
+                "Configure amount distribution",
+                "Set cancellation share (%)",
+                "Set cancellation delay (ms)",
+                "Set receipt share (%)",
+                "Set dispute share (%)",
+                "Edit dispute messages",
+                "Toggle mode (deposits/payouts)",
+                "Configure payout amount distribution",
+                "Configure buyer behaviour",
+                "Set seed",
+                "Configure pacing (sequential/rate)",
+                "Configure time-of-day profile",
                 "Reset to defaults",
                 "Save and exit",
             ];
@@ -59,13 +112,49 @@ impl TrafficMenu {
                     }
                 }
                 3 => {
-                    config.amount_probabilities = Self::configure_probabilities()?;
+                    config.amount_distribution = Self::configure_probabilities(&config.amount_distribution)?;
                 }
                 4 => {
+                    config.cancel_percentage = Self::get_probability("Share of orders to cancel (0-100%)", config.cancel_percentage)?;
+                }
+                5 => {
+                    config.cancel_delay_ms = Self::get_positive_number("Delay before cancellation (ms)", config.cancel_delay_ms as f64)? as u64;
+                }
+                6 => {
+                    config.receipt_percentage = Self::get_probability("Share of orders with a receipt (0-100%)", config.receipt_percentage)?;
+                }
+                7 => {
+                    config.dispute_percentage = Self::get_probability("Share of completed deals to dispute (0-100%)", config.dispute_percentage)?;
+                }
+                8 => {
+                    config.dispute_messages = Self::configure_dispute_messages(&config.dispute_messages)?;
+                }
+                9 => {
+                    config.mode = match config.mode {
+                        TrafficMode::Deposits => TrafficMode::Payouts,
+                        TrafficMode::Payouts => TrafficMode::Deposits,
+                    };
+                }
+                10 => {
+                    config.payout_amount_distribution = Self::configure_probabilities(&config.payout_amount_distribution)?;
+                }
+                11 => {
+                    config.buyer = Self::configure_buyer(&config.buyer)?;
+                }
+                12 => {
+                    config.seed = Self::configure_seed(config.seed)?;
+                }
+                13 => {
+                    config.pacing = Self::configure_pacing(&config.pacing)?;
+                }
+                14 => {
+                    config.profile = Self::configure_profile(&config.profile)?;
+                }
+                15 => {
                     config = TrafficConfig::default();
                     println!("Reset to default configuration");
                 }
-                5 => {
+                16 => {
                     return Ok(config);
                 }
                 _ => {}
@@ -89,38 +178,372 @@ impl TrafficMenu {
         }
     }
     
-    fn configure_probabilities() -> Result<HashMap<AmountRange, f64>> {
-        let mut probabilities = HashMap::new();
-        
-        println!("\nConfigure amount range probabilities");
-        println!("Enter probability for each range (0-100%)");
-        
-        let ranges = vec![
-            AmountRange { min: 1000, max: 3000 },
-            AmountRange { min: 3000, max: 5000 },
-            AmountRange { min: 5000, max: 10000 },
-            AmountRange { min: 10000, max: 20000 },
-            AmountRange { min: 20000, max: 50000 },
-            AmountRange { min: 50000, max: 100000 },
-        ];
+    /// The first message opens the dispute, the rest are posted one by one afterwards.
+    fn configure_dispute_messages(current: &[String]) -> Result<Vec<String>> {
+        println!("\nCurrent dispute script:");
+        for (i, message) in current.iter().enumerate() {
+            println!("  {}. {}", i + 1, message);
+        }
+        
+        println!("\nEnter messages one by one, empty line to finish");
+        
+        let mut messages = Vec::new();
+        loop {
+            let message = Input::<String>::with_theme(&ColorfulTheme::default())
+                .with_prompt(format!("Message {}", messages.len() + 1))
+                .allow_empty(true)
+                .interact_text()?;
+                
+            if message.trim().is_empty() {
+                break;
+            }
+            messages.push(message.trim().to_string());
+        }
         
-        for range in ranges {
-            let prompt = format!("{}-{} RUB", range.min, range.max);
-            let default = match range.min {
-                1000 => 64.0,
-                3000 => 69.0,
-                5000 => 73.0,
-                10000 => 82.0,
-                20000 => 88.0,
-                50000 => 92.0,
-                _ => 50.0,
+        if messages.is_empty() {
+            println!("No messages entered, keeping the current script");
+            return Ok(current.to_vec());
+        }
+        
+        Ok(messages)
+    }
+    
+    /// Prompts for a distribution until it passes validation.
+    fn configure_probabilities(current: &AmountDistribution) -> Result<AmountDistribution> {
+        loop {
+            let modes = vec![
+                "Weighted ranges",
+                "Log-normal around a median",
+                "Fixed list of amounts",
+            ];
+            let default_mode = match current.model {
+                AmountModel::Weighted { .. } => 0,
+                AmountModel::LogNormal { .. } => 1,
+                AmountModel::Fixed { .. } => 2,
             };
             
-            let prob = Self::get_probability(&prompt, default)?;
-            probabilities.insert(range, prob);
+            let mode = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt("Amount distribution")
+                .items(&modes)
+                .default(default_mode)
+                .interact()?;
+                
+            let model = match mode {
+                0 => Self::configure_weighted_ranges(current)?,
+                1 => Self::configure_log_normal(current)?,
+                _ => Self::configure_fixed_amounts(current)?,
+            };
+            
+            let distribution = AmountDistribution {
+                model,
+                rounding: Self::configure_rounding(current.rounding)?,
+            };
+            
+            match distribution.validate() {
+                Ok(()) => return Ok(distribution),
+                Err(e) => eprintln!("Invalid distribution: {}", e),
+            }
+        }
+    }
+    
+    fn configure_weighted_ranges(current: &AmountDistribution) -> Result<AmountModel> {
+        let ranges = match &current.model {
+            AmountModel::Weighted { ranges } => ranges.clone(),
+            _ => match AmountDistribution::default_deposits().model {
+                AmountModel::Weighted { ranges } => ranges,
+                _ => Vec::new(),
+            },
+        };
+        let default = ranges
+            .iter()
+            .map(|r| format!("{}-{}:{}", r.min, r.max, r.weight))
+            .collect::<Vec<_>>()
+            .join(", ");
+        
+        println!("\nEnter ranges as min-max:weight, separated by commas");
+        println!("Weights are relative, e.g. 1000-3000:3, 3000-5000:1 makes the first range three times as likely");
+        
+        loop {
+            let input = Input::<String>::with_theme(&ColorfulTheme::default())
+                .with_prompt("Ranges (RUB)")
+                .default(default.clone())
+                .interact_text()?;
+                
+            match parse_weighted_ranges(&input) {
+                Some(ranges) => return Ok(AmountModel::Weighted { ranges }),
+                None => eprintln!("Could not parse ranges, expected e.g. 1000-3000:30, 3000-5000:25"),
+            }
+        }
+    }
+    
+    fn configure_log_normal(current: &AmountDistribution) -> Result<AmountModel> {
+        let (median, sigma, min, max) = match current.model {
+            AmountModel::LogNormal { median, sigma, min, max } => (median, sigma, min, max),
+            _ => (5000.0, 0.8, 1000.0, 100000.0),
+        };
+        
+        Ok(AmountModel::LogNormal {
+            median: Self::get_positive_number("Median amount (RUB)", median)?,
+            sigma: Self::get_positive_number("Spread (sigma of ln amount, e.g. 0.8)", sigma)?,
+            min: Self::get_positive_number("Minimum amount (RUB)", min)?,
+            max: Self::get_positive_number("Maximum amount (RUB)", max)?,
+        })
+    }
+    
+    fn configure_fixed_amounts(current: &AmountDistribution) -> Result<AmountModel> {
+        let default = match &current.model {
+            AmountModel::Fixed { amounts } => amounts.iter().map(|a| a.to_string()).collect::<Vec<_>>().join(", "),
+            _ => "1000, 2000, 5000, 10000".to_string(),
+        };
+        
+        loop {
+            let input = Input::<String>::with_theme(&ColorfulTheme::default())
+                .with_prompt("Amounts (RUB), separated by commas")
+                .default(default.clone())
+                .interact_text()?;
+                
+            let amounts: Option<Vec<f64>> = input.split(',').map(|a| a.trim().parse().ok()).collect();
+            match amounts {
+                Some(amounts) => return Ok(AmountModel::Fixed { amounts }),
+                None => eprintln!("Could not parse amounts, expected e.g. 1000, 2000, 5000"),
+            }
         }
+    }
+    
+    fn configure_rounding(current: AmountRounding) -> Result<AmountRounding> {
+        let options = vec!["Kopecks (two decimals)", "Whole rubles", "Multiples of a step"];
+        let default = match current {
+            AmountRounding::Kopecks => 0,
+            AmountRounding::Whole => 1,
+            AmountRounding::Multiple(_) => 2,
+        };
         
-        Ok(probabilities)
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("Round amounts to")
+            .items(&options)
+            .default(default)
+            .interact()?;
+            
+        Ok(match selection {
+            0 => AmountRounding::Kopecks,
+            1 => AmountRounding::Whole,
+            _ => {
+                let step = match current {
+                    AmountRounding::Multiple(step) => step,
+                    _ => 100,
+                };
+                AmountRounding::Multiple(Self::get_positive_number("Step (RUB)", step as f64)? as u64)
+            }
+        })
+    }
+    
+    fn configure_buyer(current: &BuyerBehavior) -> Result<BuyerBehavior> {
+        let mut buyer = current.clone();
+        
+        buyer.enabled = Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Simulate buyers paying deposits?")
+            .default(buyer.enabled)
+            .interact()?;
+        if !buyer.enabled {
+            return Ok(buyer);
+        }
+        
+        loop {
+            let default = buyer.bands
+                .iter()
+                .map(|b| format!("{}-{}:{}", b.min, b.max, b.pay_percentage))
+                .collect::<Vec<_>>()
+                .join(", ");
+            
+            println!("\nEnter bands as min-max:pay%, separated by commas");
+            let input = Input::<String>::with_theme(&ColorfulTheme::default())
+                .with_prompt("Pay chance by amount (RUB)")
+                .default(default)
+                .interact_text()?;
+                
+            match parse_range_pairs(&input) {
+                Some(pairs) => {
+                    buyer.bands = pairs
+                        .into_iter()
+                        .map(|(min, max, pay_percentage)| ConversionBand { min, max, pay_percentage })
+                        .collect();
+                }
+                None => {
+                    eprintln!("Could not parse bands, expected e.g. 1000-3000:64, 3000-5000:69");
+                    continue;
+                }
+            }
+            
+            buyer.other_pay_percentage = Self::get_probability("Pay chance for other amounts (0-100%)", buyer.other_pay_percentage)?;
+            buyer.pay_delay_min_ms = Self::get_positive_number("Minimum delay before paying (ms)", buyer.pay_delay_min_ms as f64)? as u64;
+            buyer.pay_delay_max_ms = Self::get_positive_number("Maximum delay before paying (ms)", buyer.pay_delay_max_ms as f64)? as u64;
+            buyer.abandon_cancel_percentage = Self::get_probability(
+                "Share of unpaid orders the merchant cancels; the rest expire (0-100%)",
+                buyer.abandon_cancel_percentage,
+            )?;
+            buyer.abandon_cancel_delay_ms = Self::get_positive_number("Delay before cancelling unpaid orders (ms)", buyer.abandon_cancel_delay_ms as f64)? as u64;
+            
+            match buyer.validate() {
+                Ok(()) => return Ok(buyer),
+                Err(e) => eprintln!("Invalid buyer behaviour: {}", e),
+            }
+        }
+    }
+    
+    fn configure_pacing(current: &TrafficPacing) -> Result<TrafficPacing> {
+        let options = vec![
+            "Sequential - one request at a time with the interval in between",
+            "Fixed rate - requests sent on schedule (TPS), up to a concurrency limit",
+        ];
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("Pacing")
+            .items(&options)
+            .default(matches!(current, TrafficPacing::Rate(_)) as usize)
+            .interact()?;
+        if selection == 0 {
+            return Ok(TrafficPacing::Sequential);
+        }
+        
+        let mut rate = match current {
+            TrafficPacing::Rate(rate) => rate.clone(),
+            TrafficPacing::Sequential => RateConfig::default(),
+        };
+        
+        loop {
+            rate.target_tps = Self::get_positive_number("Target rate (TPS)", rate.target_tps)?;
+            rate.max_concurrency = Self::get_positive_number("Max requests in flight", rate.max_concurrency as f64)? as usize;
+            
+            println!("\nOptional stages as seconds:tps, separated by commas, e.g. 60:50, 300:50, 60:0");
+            println!("Each stage ramps linearly from the previous rate (0 at the start); the run ends after the last one");
+            let default = rate.stages
+                .iter()
+                .map(|s| format!("{}:{}", s.duration_secs, s.target_tps))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let input = Input::<String>::with_theme(&ColorfulTheme::default())
+                .with_prompt("Stages (empty for a steady rate)")
+                .default(default)
+                .allow_empty(true)
+                .interact_text()?;
+                
+            match parse_stages(&input) {
+                Some(stages) => rate.stages = stages,
+                None => {
+                    eprintln!("Could not parse stages, expected e.g. 60:50, 300:50");
+                    continue;
+                }
+            }
+            
+            match rate.validate() {
+                Ok(()) => return Ok(TrafficPacing::Rate(rate)),
+                Err(e) => eprintln!("Invalid pacing: {}", e),
+            }
+        }
+    }
+    
+    fn configure_profile(current: &TrafficProfile) -> Result<TrafficProfile> {
+        let mut profile = current.clone();
+        
+        profile.enabled = Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Shape traffic by time of day?")
+            .default(profile.enabled)
+            .interact()?;
+        if !profile.enabled {
+            return Ok(profile);
+        }
+        
+        println!("\nHourly multipliers from 00:00 to 23:00, separated by spaces or commas; 1 is the base rate");
+        loop {
+            profile.weekday_hours = Self::get_curve("Weekday curve", &profile.weekday_hours)?;
+            profile.weekend_hours = Self::get_curve("Weekend curve", &profile.weekend_hours)?;
+            
+            println!("\nBursts as HH:MM-HH:MM:multiplier, optionally @weekdays or @weekends, separated by commas");
+            let default = profile.bursts
+                .iter()
+                .map(|b| {
+                    let days = match b.days {
+                        DayFilter::All => "",
+                        DayFilter::Weekdays => "@weekdays",
+                        DayFilter::Weekends => "@weekends",
+                    };
+                    format!("{}-{}:{}{}", b.start.format("%H:%M"), b.end.format("%H:%M"), b.multiplier, days)
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            let input = Input::<String>::with_theme(&ColorfulTheme::default())
+                .with_prompt("Bursts (empty for none)")
+                .default(default)
+                .allow_empty(true)
+                .interact_text()?;
+            match parse_bursts(&input) {
+                Some(bursts) => profile.bursts = bursts,
+                None => {
+                    eprintln!("Could not parse bursts, expected e.g. 12:00-12:30:3, 20:00-21:00:2@weekends");
+                    continue;
+                }
+            }
+            
+            profile.clock_speed = Self::get_positive_number("Clock speed (1 = real time, 1440 = a day per minute)", profile.clock_speed)?;
+            
+            let start = Input::<String>::with_theme(&ColorfulTheme::default())
+                .with_prompt("Simulated start (YYYY-MM-DD HH:MM, empty for now)")
+                .default(profile.start_at.map(|s| s.format("%Y-%m-%d %H:%M").to_string()).unwrap_or_default())
+                .allow_empty(true)
+                .interact_text()?;
+            if start.trim().is_empty() {
+                profile.start_at = None;
+            } else {
+                match chrono::NaiveDateTime::parse_from_str(start.trim(), "%Y-%m-%d %H:%M") {
+                    Ok(start) => profile.start_at = Some(start),
+                    Err(_) => {
+                        eprintln!("Could not parse the start, expected e.g. 2024-06-03 08:00");
+                        continue;
+                    }
+                }
+            }
+            
+            match profile.validate() {
+                Ok(()) => return Ok(profile),
+                Err(e) => eprintln!("Invalid profile: {}", e),
+            }
+        }
+    }
+    
+    fn get_curve(prompt: &str, current: &[f64]) -> Result<Vec<f64>> {
+        let default = current.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" ");
+        
+        loop {
+            let input = Input::<String>::with_theme(&ColorfulTheme::default())
+                .with_prompt(prompt)
+                .default(default.clone())
+                .interact_text()?;
+                
+            let values: Option<Vec<f64>> = input
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|v| !v.is_empty())
+                .map(|v| v.parse().ok())
+                .collect();
+            match values {
+                Some(values) if values.len() == 24 => return Ok(values),
+                _ => eprintln!("Please enter 24 numbers"),
+            }
+        }
+    }
+    
+    fn configure_seed(current: Option<u64>) -> Result<Option<u64>> {
+        let fixed = Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Use a fixed seed? Runs with the same seed and server responses replay identically")
+            .default(current.is_some())
+            .interact()?;
+        if !fixed {
+            return Ok(None);
+        }
+        
+        let seed = Input::<u64>::with_theme(&ColorfulTheme::default())
+            .with_prompt("Seed")
+            .default(current.unwrap_or_else(rand::random))
+            .interact_text()?;
+        Ok(Some(seed))
     }
     
     fn get_probability(prompt: &str, default: f64) -> Result<f64> {
@@ -138,4 +561,61 @@ impl TrafficMenu {
             }
         }
     }
+}
+
+/// Parses `min-max:weight` pairs separated by commas.
+fn parse_weighted_ranges(input: &str) -> Option<Vec<WeightedRange>> {
+    let pairs = parse_range_pairs(input)?;
+    Some(pairs.into_iter().map(|(min, max, weight)| WeightedRange { min, max, weight }).collect())
+}
+
+/// Parses `seconds:tps` stages separated by commas; empty input means no stages.
+fn parse_stages(input: &str) -> Option<Vec<RateStage>> {
+    input
+        .split(',')
+        .filter(|part| !part.trim().is_empty())
+        .map(|part| {
+            let (duration, tps) = part.trim().split_once(':')?;
+            Some(RateStage {
+                duration_secs: duration.trim().parse().ok()?,
+                target_tps: tps.trim().parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Parses `HH:MM-HH:MM:multiplier[@weekdays|@weekends]` windows separated by commas.
+fn parse_bursts(input: &str) -> Option<Vec<BurstWindow>> {
+    input
+        .split(',')
+        .filter(|part| !part.trim().is_empty())
+        .map(|part| {
+            let (window, days) = match part.trim().split_once('@') {
+                Some((window, "weekdays")) => (window, DayFilter::Weekdays),
+                Some((window, "weekends")) => (window, DayFilter::Weekends),
+                Some(_) => return None,
+                None => (part.trim(), DayFilter::All),
+            };
+            let (times, multiplier) = window.rsplit_once(':')?;
+            let (start, end) = times.split_once('-')?;
+            Some(BurstWindow {
+                start: chrono::NaiveTime::parse_from_str(start.trim(), "%H:%M").ok()?,
+                end: chrono::NaiveTime::parse_from_str(end.trim(), "%H:%M").ok()?,
+                multiplier: multiplier.trim().parse().ok()?,
+                days,
+            })
+        })
+        .collect()
+}
+
+/// Parses `min-max:value` pairs separated by commas.
+fn parse_range_pairs(input: &str) -> Option<Vec<(u64, u64, f64)>> {
+    input
+        .split(',')
+        .map(|part| {
+            let (range, value) = part.trim().split_once(':')?;
+            let (min, max) = range.trim().split_once('-')?;
+            Some((min.trim().parse().ok()?, max.trim().parse().ok()?, value.trim().parse().ok()?))
+        })
+        .collect()
 }
\ No newline at end of file
@@ -0,0 +1,124 @@
+use merchant_emulator::models::{AmountDistribution, AmountModel, AmountRounding, TrafficConfig, WeightedRange};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fn ranges(weights: &[(u64, u64, f64)]) -> AmountDistribution {
+    AmountDistribution::weighted(
+        weights
+            .iter()
+            .map(|&(min, max, weight)| WeightedRange { min, max, weight })
+            .collect(),
+    )
+}
+
+#[test]
+fn weighted_ranges_are_picked_in_proportion_to_their_weights() {
+    // The old sampler put almost everything in the lowest-weighted range
+    let distribution = ranges(&[(1000, 2000, 64.0), (2000, 3000, 92.0), (3000, 4000, 0.0)]);
+    let mut rng = StdRng::seed_from_u64(7);
+    
+    let samples: Vec<f64> = (0..20_000).map(|_| distribution.sample(&mut rng)).collect();
+    let low = samples.iter().filter(|a| **a < 2000.0).count() as f64 / samples.len() as f64;
+    
+    assert!((low - 64.0 / 156.0).abs() < 0.02, "low range share was {}", low);
+    assert!(samples.iter().all(|a| (1000.0..=3000.0).contains(a)));
+}
+
+#[test]
+fn rounding_to_a_step_stays_inside_the_range() {
+    let mut distribution = ranges(&[(1050, 1390, 1.0)]);
+    distribution.rounding = AmountRounding::Multiple(100);
+    let mut rng = StdRng::seed_from_u64(1);
+    
+    for _ in 0..1000 {
+        let amount = distribution.sample(&mut rng);
+        assert_eq!(amount % 100.0, 0.0);
+        assert!((1100.0..=1300.0).contains(&amount), "amount {}", amount);
+    }
+}
+
+#[test]
+fn log_normal_amounts_cluster_around_the_median() {
+    let distribution = AmountDistribution {
+        model: AmountModel::LogNormal { median: 5000.0, sigma: 0.5, min: 1000.0, max: 50000.0 },
+        rounding: AmountRounding::Whole,
+    };
+    distribution.validate().unwrap();
+    let mut rng = StdRng::seed_from_u64(3);
+    
+    let mut samples: Vec<f64> = (0..10_001).map(|_| distribution.sample(&mut rng)).collect();
+    samples.sort_by(f64::total_cmp);
+    
+    let median = samples[samples.len() / 2];
+    assert!((4500.0..=5500.0).contains(&median), "median was {}", median);
+    assert!(samples.iter().all(|a| a.fract() == 0.0 && (1000.0..=50000.0).contains(a)));
+}
+
+#[test]
+fn fixed_amounts_are_only_the_listed_ones() {
+    let distribution = AmountDistribution {
+        model: AmountModel::Fixed { amounts: vec![1000.0, 2500.0] },
+        rounding: AmountRounding::default(),
+    };
+    let mut rng = StdRng::seed_from_u64(5);
+    
+    assert!((0..100).all(|_| [1000.0, 2500.0].contains(&distribution.sample(&mut rng))));
+}
+
+#[test]
+fn invalid_distributions_are_rejected() {
+    assert!(ranges(&[]).validate().is_err());
+    assert!(ranges(&[(3000, 1000, 1.0)]).validate().is_err());
+    assert!(ranges(&[(1000, 3000, 0.0)]).validate().is_err());
+    assert!(ranges(&[(1000, 3000, -1.0)]).validate().is_err());
+    
+    let mut zero_step = ranges(&[(1000, 3000, 1.0)]);
+    zero_step.rounding = AmountRounding::Multiple(0);
+    assert!(zero_step.validate().is_err());
+    
+    let median_outside = AmountDistribution {
+        model: AmountModel::LogNormal { median: 500.0, sigma: 0.5, min: 1000.0, max: 5000.0 },
+        rounding: AmountRounding::default(),
+    };
+    assert!(median_outside.validate().is_err());
+    
+    let fixed_zero = AmountDistribution {
+        model: AmountModel::Fixed { amounts: vec![0.0] },
+        rounding: AmountRounding::default(),
+    };
+    assert!(fixed_zero.validate().is_err());
+    
+    assert!(AmountDistribution::default_deposits().validate().is_ok());
+    assert!(AmountDistribution::default_payouts().validate().is_ok());
+}
+
+#[test]
+fn saved_probability_maps_load_as_weights() {
+    let mut config = serde_json::to_value(TrafficConfig::default()).unwrap();
+    config.as_object_mut().unwrap().remove("amount_distribution");
+    config.as_object_mut().unwrap().remove("payout_amount_distribution");
+    config["amount_probabilities"] = serde_json::json!({ "1000-3000": 64.0, "3000-5000": 69.0 });
+    config["payout_amount_probabilities"] = serde_json::json!({ "5000-20000": 100.0 });
+    
+    let loaded: TrafficConfig = serde_json::from_value(config).unwrap();
+    
+    assert_eq!(
+        loaded.amount_distribution.model,
+        AmountModel::Weighted {
+            ranges: vec![
+                WeightedRange { min: 1000, max: 3000, weight: 64.0 },
+                WeightedRange { min: 3000, max: 5000, weight: 69.0 },
+            ]
+        }
+    );
+    assert_eq!(loaded.amount_distribution.rounding, AmountRounding::Kopecks);
+    assert_eq!(
+        loaded.payout_amount_distribution.model,
+        AmountModel::Weighted { ranges: vec![WeightedRange { min: 5000, max: 20000, weight: 100.0 }] }
+    );
+    
+    // And the current format round-trips
+    let saved = serde_json::to_string(&loaded).unwrap();
+    let reloaded: TrafficConfig = serde_json::from_str(&saved).unwrap();
+    assert_eq!(reloaded.amount_distribution, loaded.amount_distribution);
+}
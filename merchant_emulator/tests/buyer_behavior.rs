@@ -0,0 +1,125 @@
+mod common;
+
+use common::{wait_for, Harness, METHOD_ID};
+use merchant_emulator::models::{BuyerBehavior, BuyerDecision, ConversionBand};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fn buyer(pay_percentage: f64, abandon_cancel_percentage: f64) -> BuyerBehavior {
+    BuyerBehavior {
+        enabled: true,
+        bands: vec![ConversionBand { min: 1000, max: 100000, pay_percentage }],
+        other_pay_percentage: pay_percentage,
+        pay_delay_min_ms: 1,
+        pay_delay_max_ms: 5,
+        abandon_cancel_percentage,
+        abandon_cancel_delay_ms: 10,
+    }
+}
+
+#[test]
+fn each_band_converts_at_its_own_rate() {
+    let behavior = BuyerBehavior::default();
+    let mut rng = StdRng::seed_from_u64(3);
+    
+    for (amount, expected) in [(2000.0, 0.64), (75000.0, 0.92)] {
+        let paid = (0..20_000)
+            .filter(|_| matches!(behavior.decide(amount, &mut rng), BuyerDecision::Pay { .. }))
+            .count() as f64 / 20_000.0;
+        assert!((paid - expected).abs() < 0.02, "{} RUB paid {}", amount, paid);
+    }
+    
+    assert_eq!(behavior.band_label(2000.0), "1000-3000");
+    assert_eq!(behavior.band_label(500.0), "other");
+    assert_eq!(behavior.pay_percentage(500.0), behavior.other_pay_percentage);
+}
+
+#[test]
+fn invalid_behaviour_is_rejected() {
+    let mut behavior = buyer(120.0, 0.0);
+    assert!(behavior.validate().is_err());
+    
+    behavior = buyer(50.0, 0.0);
+    behavior.pay_delay_min_ms = 10;
+    behavior.pay_delay_max_ms = 1;
+    assert!(behavior.validate().is_err());
+    
+    assert!(BuyerBehavior::default().validate().is_ok());
+}
+
+#[tokio::test]
+async fn paying_buyers_notify_the_traders_device() {
+    let harness = Harness::start().await;
+    harness.connect_device().await;
+    let mut merchant = harness
+        .merchant_with("Buyer", |traffic| {
+            traffic.max_transactions = Some(3);
+            traffic.buyer = buyer(100.0, 0.0);
+        })
+        .await;
+    // Liquid orders reach the device instead of being settled by the backend
+    merchant.liquidity_percentage = 100.0;
+    let generator = harness.traffic_generator();
+    
+    generator.start_traffic(merchant.clone(), METHOD_ID.to_string(), true).await.unwrap();
+    
+    let state = harness.backend.state().clone();
+    let notified = wait_for(|| state.devices.read().iter().map(|d| d.notifications.len()).sum::<usize>() == 3).await;
+    assert!(notified, "device did not receive a notification per order");
+    
+    let notifications = state.devices.read()[0].notifications.clone();
+    assert!(notifications.iter().all(|n| n["category"] == "transaction"));
+    
+    let stats = harness.stats.clone();
+    assert!(wait_for(|| {
+        let conversion = &stats.get_statistics(&merchant.id).unwrap().conversion_breakdown["1000-100000"];
+        conversion.notified == 3
+    }).await);
+    
+    let conversion = harness.stats.get_statistics(&merchant.id).unwrap().conversion_breakdown["1000-100000"].clone();
+    assert_eq!((conversion.orders, conversion.paid, conversion.cancelled, conversion.abandoned), (3, 3, 0, 0));
+}
+
+#[tokio::test]
+async fn unpaid_orders_are_cancelled_or_left_to_expire() {
+    let harness = Harness::start().await;
+    harness.connect_device().await;
+    let mut merchant = harness
+        .merchant_with("Buyer", |traffic| {
+            traffic.max_transactions = Some(3);
+            traffic.buyer = buyer(0.0, 100.0);
+        })
+        .await;
+    merchant.liquidity_percentage = 100.0;
+    let generator = harness.traffic_generator();
+    
+    generator.start_traffic(merchant.clone(), METHOD_ID.to_string(), true).await.unwrap();
+    
+    let state = harness.backend.state().clone();
+    let cancelled = wait_for(|| {
+        let transactions = state.transactions.read();
+        transactions.len() == 3 && transactions.iter().all(|t| t.status == "CANCELED")
+    }).await;
+    assert!(cancelled, "unpaid orders were not cancelled");
+    assert!(state.devices.read().iter().all(|d| d.notifications.is_empty()));
+    
+    // Abandoned orders are left open for the backend to expire
+    let harness = Harness::start().await;
+    let mut merchant = harness
+        .merchant_with("Buyer", |traffic| {
+            traffic.max_transactions = Some(3);
+            traffic.buyer = buyer(0.0, 0.0);
+        })
+        .await;
+    merchant.liquidity_percentage = 100.0;
+    let generator = harness.traffic_generator();
+    
+    generator.start_traffic(merchant.clone(), METHOD_ID.to_string(), true).await.unwrap();
+    
+    let stats = harness.stats.clone();
+    assert!(wait_for(|| {
+        stats.get_statistics(&merchant.id).unwrap().conversion_breakdown.get("1000-100000").map(|c| c.abandoned) == Some(3)
+    }).await);
+    assert!(harness.backend.state().transactions.read().iter().all(|t| t.status == "IN_PROGRESS"));
+    assert_eq!(harness.stats.get_statistics(&merchant.id).unwrap().cancelled_transactions, 0);
+}
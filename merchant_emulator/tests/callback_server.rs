@@ -0,0 +1,293 @@
+mod common;
+
+use common::{wait_for, wait_for_async, Harness, API_KEY, METHOD_ID};
+use merchant_emulator::api::signature::{self, SIGNATURE_HEADER};
+use merchant_emulator::services::ReconciliationService;
+use merchant_emulator::models::{CallbackOutcome, CallbackSignature, Merchant, Transaction, TransactionStatus};
+
+const PRIVATE_KEY: &str = "mock-private-key";
+
+/// A merchant whose private key the mock also holds, with one open deposit
+/// that calls back to `callback_url`.
+async fn signed_merchant(harness: &Harness, callback_url: &str) -> (Merchant, Transaction) {
+    harness.backend.state().merchants.write()[0].private_key = Some(PRIVATE_KEY.to_string());
+    let mut merchant = harness
+        .merchant_service
+        .create_merchant("Signed".to_string(), API_KEY.to_string(), Some(PRIVATE_KEY.to_string()))
+        .await
+        .unwrap();
+    merchant.callback_url = Some(format!("{}/callback/{}", callback_url, merchant.id));
+    harness.merchant_service.update_merchant(merchant.clone()).await.unwrap();
+    
+    let transaction = harness
+        .merchant_service
+        .create_transaction(&merchant, 3000.0, METHOD_ID.to_string(), false)
+        .await
+        .unwrap();
+    (merchant, transaction)
+}
+
+/// Posts a callback for `order_id` with an optional signature and waits for it to be logged.
+async fn post_callback(harness: &Harness, callback_url: &str, merchant: &Merchant, order_id: &str, token: Option<String>) -> CallbackSignature {
+    let body = serde_json::json!({ "id": order_id, "amount": 3000, "status": "READY" }).to_string();
+    let mut request = reqwest::Client::new()
+        .post(format!("{}/callback/{}", callback_url, merchant.id))
+        .header("Content-Type", "application/json")
+        .body(body);
+    if let Some(token) = token {
+        request = request.header(SIGNATURE_HEADER, token);
+    }
+    assert_eq!(request.send().await.unwrap().status(), 200);
+    
+    assert!(wait_for(|| !harness.storage.get_merchant_callbacks(&merchant.id).is_empty()).await);
+    harness.storage.get_merchant_callbacks(&merchant.id)[0].signature
+}
+
+#[tokio::test]
+async fn backend_callback_updates_the_merchants_transaction() {
+    let harness = Harness::start().await;
+    let callback_url = harness.start_callback_server().await;
+    
+    let mut merchant = harness.merchant().await;
+    merchant.callback_url = Some(format!("{}/callback/{}", callback_url, merchant.id));
+    harness.merchant_service.update_merchant(merchant.clone()).await.unwrap();
+    
+    let transaction = harness
+        .merchant_service
+        .create_transaction(&merchant, 3000.0, METHOD_ID.to_string(), false)
+        .await
+        .unwrap();
+    
+    harness.backend.state().set_transaction_status(&transaction.id, "READY").expect("deposit exists");
+    
+    let settled = wait_for(|| {
+        harness
+            .storage
+            .find_transaction(&transaction.order_id)
+            .is_some_and(|h| h.transaction.status == TransactionStatus::Ready)
+    })
+    .await;
+    assert!(settled, "callback did not settle the transaction");
+    
+    let history = harness.storage.find_transaction(&transaction.order_id).unwrap();
+    assert!(history.transaction.callback_sent);
+    
+    let callbacks = harness.storage.get_merchant_callbacks(&merchant.id);
+    assert_eq!(callbacks.len(), 1);
+    assert_eq!(callbacks[0].matched_order_id.as_deref(), Some(transaction.order_id.as_str()));
+    assert_eq!(callbacks[0].outcome, CallbackOutcome::Respond(200));
+    assert_eq!(callbacks[0].signature, CallbackSignature::NotChecked);
+    
+    let stats = harness.stats.get_statistics(&merchant.id).unwrap();
+    assert_eq!(stats.callbacks_received, 1);
+    assert_eq!(stats.status_breakdown.get("READY"), Some(&1));
+    
+    let sent = harness.backend.state().callbacks.read().clone();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].response_status, Some(200));
+}
+
+#[tokio::test]
+async fn generic_callback_is_routed_by_order_id() {
+    let harness = Harness::start().await;
+    let callback_url = harness.start_callback_server().await;
+    let merchant = harness.merchant().await;
+    
+    let transaction = harness
+        .merchant_service
+        .create_transaction(&merchant, 3000.0, METHOD_ID.to_string(), false)
+        .await
+        .unwrap();
+    
+    let response = reqwest::Client::new()
+        .post(format!("{}/callback", callback_url))
+        .json(&serde_json::json!({ "id": transaction.order_id, "status": "CANCELED" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    
+    let routed = wait_for(|| {
+        harness
+            .storage
+            .find_transaction(&transaction.order_id)
+            .is_some_and(|h| h.transaction.status == TransactionStatus::Canceled)
+    })
+    .await;
+    assert!(routed, "callback was not routed to the transaction");
+    
+    let callbacks = harness.storage.get_merchant_callbacks(&merchant.id);
+    assert_eq!(callbacks.len(), 1);
+    assert_eq!(callbacks[0].path, "/callback");
+    assert_eq!(callbacks[0].matched_order_id.as_deref(), Some(transaction.order_id.as_str()));
+}
+
+#[tokio::test]
+async fn unknown_merchant_and_malformed_bodies_are_rejected() {
+    let harness = Harness::start().await;
+    let callback_url = harness.start_callback_server().await;
+    let client = reqwest::Client::new();
+    let unknown = uuid::Uuid::new_v4();
+    
+    let response = client
+        .post(format!("{}/callback/{}", callback_url, unknown))
+        .json(&serde_json::json!({ "id": "order-1", "status": "READY" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+    
+    let response = client
+        .post(format!("{}/callback", callback_url))
+        .header("Content-Type", "application/json")
+        .body("{\"id\": ")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+    
+    // The unknown merchant's callback is still logged, without a matched order
+    let logged = wait_for(|| !harness.storage.get_merchant_callbacks(&unknown).is_empty()).await;
+    assert!(logged);
+    let callbacks = harness.storage.get_merchant_callbacks(&unknown);
+    assert!(callbacks[0].matched_order_id.is_none());
+    assert_eq!(callbacks[0].outcome, CallbackOutcome::Respond(404));
+}
+
+#[tokio::test]
+async fn configured_failures_are_returned_to_the_backend() {
+    let harness = Harness::start().await;
+    let callback_url = harness.start_callback_server().await;
+    
+    let mut merchant = harness.merchant().await;
+    merchant.callback_url = Some(format!("{}/callback/{}", callback_url, merchant.id));
+    merchant.callback_behavior.failure_rate = 100.0;
+    merchant.callback_behavior.failure_status_code = 503;
+    harness.merchant_service.update_merchant(merchant.clone()).await.unwrap();
+    
+    let transaction = harness
+        .merchant_service
+        .create_transaction(&merchant, 3000.0, METHOD_ID.to_string(), false)
+        .await
+        .unwrap();
+    harness.backend.state().set_transaction_status(&transaction.id, "READY").unwrap();
+    
+    let state = harness.backend.state().clone();
+    let answered = wait_for(|| state.callbacks.read().iter().any(|c| c.response_status.is_some())).await;
+    assert!(answered);
+    assert_eq!(state.callbacks.read()[0].response_status, Some(503));
+    
+    // The callback is still applied; the failure only changes the response
+    let applied = wait_for(|| {
+        harness
+            .storage
+            .find_transaction(&transaction.order_id)
+            .is_some_and(|h| h.transaction.status == TransactionStatus::Ready)
+    })
+    .await;
+    assert!(applied);
+    let callbacks = harness.storage.get_merchant_callbacks(&merchant.id);
+    assert_eq!(callbacks[0].outcome, CallbackOutcome::Fail(503));
+}
+
+#[tokio::test]
+async fn callbacks_signed_by_the_backend_are_valid() {
+    let harness = Harness::start().await;
+    let callback_url = harness.start_callback_server().await;
+    let (merchant, transaction) = signed_merchant(&harness, &callback_url).await;
+    harness.backend.state().set_transaction_status(&transaction.id, "READY").unwrap();
+    
+    assert!(wait_for(|| !harness.storage.get_merchant_callbacks(&merchant.id).is_empty()).await);
+    let callbacks = harness.storage.get_merchant_callbacks(&merchant.id);
+    assert!(callbacks[0].headers.contains_key(SIGNATURE_HEADER));
+    assert_eq!(callbacks[0].signature, CallbackSignature::Valid);
+}
+
+#[tokio::test]
+async fn callbacks_signed_with_another_key_are_invalid() {
+    let harness = Harness::start().await;
+    let callback_url = harness.start_callback_server().await;
+    let (merchant, transaction) = signed_merchant(&harness, &callback_url).await;
+    
+    let body = serde_json::json!({ "id": transaction.order_id, "amount": 3000, "status": "READY" });
+    let token = signature::sign_value("another-key", &body);
+    
+    let checked = post_callback(&harness, &callback_url, &merchant, &transaction.order_id, Some(token)).await;
+    assert_eq!(checked, CallbackSignature::Invalid);
+}
+
+#[tokio::test]
+async fn unsigned_callbacks_are_missing_their_signature() {
+    let harness = Harness::start().await;
+    let callback_url = harness.start_callback_server().await;
+    let (merchant, transaction) = signed_merchant(&harness, &callback_url).await;
+    
+    let checked = post_callback(&harness, &callback_url, &merchant, &transaction.order_id, None).await;
+    assert_eq!(checked, CallbackSignature::Missing);
+}
+
+#[tokio::test]
+async fn a_burst_of_callbacks_is_persisted_together() {
+    let harness = Harness::start().await;
+    let callback_url = harness.start_callback_server().await;
+    let merchant = harness.merchant().await;
+    
+    let mut order_ids = Vec::new();
+    for _ in 0..3 {
+        let transaction = harness
+            .merchant_service
+            .create_transaction(&merchant, 3000.0, METHOD_ID.to_string(), false)
+            .await
+            .unwrap();
+        order_ids.push(transaction.order_id);
+    }
+    
+    let client = reqwest::Client::new();
+    for order_id in &order_ids {
+        let response = client
+            .post(format!("{}/callback", callback_url))
+            .json(&serde_json::json!({ "id": order_id, "status": "CANCELED" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+    }
+    
+    // Saves are deferred, so a restarted emulator sees the burst once it has been written
+    let persisted = wait_for_async(|| async {
+        let reopened = harness.reopen_storage().await;
+        reopened.get_merchant_callbacks(&merchant.id).len() == order_ids.len()
+            && order_ids.iter().all(|order_id| {
+                reopened
+                    .find_transaction(order_id)
+                    .is_some_and(|h| h.transaction.status == TransactionStatus::Canceled)
+            })
+    })
+    .await;
+    assert!(persisted, "callbacks were not written to disk");
+    
+    let reopened = harness.reopen_storage().await;
+    assert_eq!(reopened.get_statistics(&merchant.id).unwrap().callbacks_received, 3);
+}
+
+#[tokio::test]
+async fn generic_callbacks_for_unknown_orders_are_unattributed() {
+    let harness = Harness::start().await;
+    let callback_url = harness.start_callback_server().await;
+    let merchant = harness.merchant().await;
+    
+    let response = reqwest::Client::new()
+        .post(format!("{}/callback", callback_url))
+        .json(&serde_json::json!({ "id": "nobody-ordered-this", "status": "READY" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    
+    let reconciliation = ReconciliationService::new(harness.storage.clone());
+    assert!(wait_for(|| !reconciliation.report(&merchant.id).unattributed_callbacks.is_empty()).await);
+    
+    let report = reconciliation.report(&merchant.id);
+    assert_eq!(report.unattributed_callbacks[0].id, "nobody-ordered-this");
+    assert!(report.unknown_order_callbacks.is_empty());
+}
@@ -0,0 +1,149 @@
+mod common;
+
+use clap::Parser;
+use common::{Harness, API_KEY, DEVICE_CODE, TRADER_ID};
+use merchant_emulator::cli::{self, Cli, Command, DeviceCommand, MerchantCommand, TrafficCommand};
+use merchant_emulator::models::Config;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn parse(args: &[&str]) -> Command {
+    let cli = Cli::try_parse_from(std::iter::once("merchant-emulator").chain(args.iter().copied())).expect("parse");
+    cli.command.expect("command")
+}
+
+fn create_merchant(name: &str) -> Command {
+    parse(&["merchant", "create", "--name", name, "--api-key", API_KEY])
+}
+
+#[test]
+fn global_flags_override_the_config() {
+    let cli = Cli::try_parse_from([
+        "merchant-emulator",
+        "traffic",
+        "status",
+        "--api-url",
+        "http://backend:3000/",
+        "--data-dir",
+        "/tmp/emulator",
+        "--callback-port",
+        "9090",
+    ])
+    .unwrap();
+    let mut config = Config::default();
+    cli.apply(&mut config);
+    
+    assert_eq!(config.api_base_url, "http://backend:3000");
+    assert_eq!(config.data_dir, std::path::PathBuf::from("/tmp/emulator"));
+    assert_eq!(config.callback_server_port, 9090);
+    assert!(matches!(cli.command, Some(Command::Traffic(TrafficCommand::Status { merchant: None }))));
+    
+    // No subcommand keeps the interactive menu
+    assert!(Cli::try_parse_from(["merchant-emulator"]).unwrap().command.is_none());
+    assert!(Cli::try_parse_from(["merchant-emulator", "device", "notify", "phone"]).is_err());
+    assert_eq!(cli.default_log_level(), "warn");
+    
+    let daemon = Cli::try_parse_from(["merchant-emulator", "daemon", "--listen", "0.0.0.0:9000"]).unwrap();
+    assert_eq!(daemon.default_log_level(), "info");
+    assert!(matches!(daemon.command, Some(Command::Daemon { listen, token: None, allow_unauthenticated: false }) if listen == "0.0.0.0:9000"));
+}
+
+#[tokio::test]
+async fn merchants_are_created_and_listed_without_secrets() {
+    let harness = Harness::start().await;
+    let app = harness.app().await;
+    
+    let created = cli::run(&app, create_merchant("shop")).await.unwrap();
+    assert_eq!(created["name"], "shop");
+    assert!(created.get("api_key").is_none());
+    
+    // A later invocation sees the merchant from disk
+    let app = harness.app().await;
+    let listed = cli::run(&app, Command::Merchant(MerchantCommand::List)).await.unwrap();
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+    assert_eq!(listed[0]["id"], created["id"]);
+    
+    let unknown = cli::run(&app, parse(&["traffic", "status", "nobody"])).await;
+    assert!(unknown.unwrap_err().to_string().contains("not found"));
+    assert_eq!(cli::print_result(Err(anyhow::anyhow!("boom"))), 1);
+}
+
+#[tokio::test]
+async fn devices_connect_and_notify() {
+    let harness = Harness::start().await;
+    let app = harness.app().await;
+    
+    let device = cli::run(&app, Command::Device(DeviceCommand::Create { name: "phone".to_string() })).await.unwrap();
+    assert_eq!(device["is_connected"], false);
+    
+    let connected = cli::run(&app, parse(&["device", "connect", "phone", "--code", DEVICE_CODE, "--trader-id", TRADER_ID]))
+        .await
+        .unwrap();
+    assert_eq!(connected["id"], device["id"]);
+    assert_eq!(connected["is_connected"], true);
+    assert_eq!(connected["trader_id"], TRADER_ID);
+    
+    let app = harness.app().await;
+    cli::run(&app, parse(&["device", "notify", "phone", "--amount", "1500"])).await.unwrap();
+    let notified: usize = harness.backend.state().devices.read().iter().map(|d| d.notifications.len()).sum();
+    assert_eq!(notified, 1);
+}
+
+#[tokio::test]
+async fn traffic_start_runs_in_the_foreground_until_done() {
+    let harness = Harness::start().await;
+    let app = harness.app().await;
+    let merchant = cli::run(&app, create_merchant("shop")).await.unwrap();
+    
+    let result = cli::run(&app, parse(&["traffic", "start", "shop", "--max-transactions", "3", "--interval-ms", "50", "--seed", "5"])).await.unwrap();
+    
+    assert_eq!(result["stopped_by"], "finished");
+    assert_eq!(result["method_id"], "method-c2c");
+    assert_eq!(result["statistics"]["successful_requests"], 3);
+    assert_eq!(harness.backend.state().transactions.read().len(), 3);
+    
+    // Nothing is left running; status falls back to the stored statistics
+    let app = harness.app().await;
+    let status = cli::run(&app, parse(&["traffic", "status", "shop"])).await.unwrap();
+    assert_eq!(status["merchant_id"], merchant["id"]);
+    assert_eq!(status["running"], false);
+    assert_eq!(status["statistics"]["successful_requests"], 3);
+    assert_eq!(cli::run(&app, parse(&["traffic", "status"])).await.unwrap(), Value::Array(vec![]));
+    
+    let export = cli::run(&app, parse(&["export", "shop"])).await.unwrap();
+    for file in ["history", "statistics", "callbacks", "reconciliation"] {
+        let path = export[file].as_str().unwrap();
+        assert!(std::path::Path::new(path).exists(), "{} missing", file);
+    }
+}
+
+#[tokio::test]
+async fn traffic_stop_ends_a_run_started_elsewhere() {
+    let harness = Harness::start().await;
+    let app = Arc::new(harness.app().await);
+    cli::run(&app, create_merchant("shop")).await.unwrap();
+    
+    let runner = app.clone();
+    let run = tokio::spawn(async move { cli::run(&runner, parse(&["traffic", "start", "shop", "--interval-ms", "50"])).await });
+    
+    // Another invocation sees the run once it has written its status
+    let other = harness.app().await;
+    let mut status = Value::Null;
+    for _ in 0..40 {
+        status = cli::run(&other, parse(&["traffic", "status"])).await.unwrap();
+        if status.as_array().is_some_and(|runs| !runs.is_empty()) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(status[0]["merchant"], "shop");
+    assert_eq!(status[0]["pid"], std::process::id());
+    
+    let stopped = cli::run(&other, parse(&["traffic", "stop", "shop"])).await.unwrap();
+    assert_eq!(stopped["stopped"], true);
+    
+    let result = run.await.unwrap().unwrap();
+    assert_eq!(result["stopped_by"], "stop_request");
+    assert!(cli::run(&other, parse(&["traffic", "stop", "shop"])).await.unwrap_err().to_string().contains("not running"));
+}
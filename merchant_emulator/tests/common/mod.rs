@@ -0,0 +1,230 @@
+// Shared setup for the integration tests: a mock backend on a free port and
+// the emulator services wired to it the same way main.rs wires them.
+#![allow(dead_code)] // Each test binary uses a different part of the harness
+
+use merchant_emulator::api::{ApiClient, CallbackServer};
+use merchant_emulator::device::{DeviceApiClient, DeviceManager};
+use merchant_emulator::app::App;
+use merchant_emulator::models::{Config, Merchant, RetryConfig, TrafficConfig};
+use merchant_emulator::services::{
+    CatalogService, DeviceNotificationService, MerchantService, Protocols, ScenarioRunner, StatisticsService, StatusTracker,
+    StorageService, TrafficGenerator,
+};
+use mock_backend::{MockBackend, Script, Seed};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+pub const API_KEY: &str = "mock-merchant-key";
+pub const METHOD_ID: &str = "method-c2c";
+pub const DEVICE_CODE: &str = "mock-device-code";
+pub const TRADER_ID: &str = "mock-trader";
+
+/// Scratch data directory, removed when dropped.
+pub struct DataDir(PathBuf);
+
+impl DataDir {
+    pub fn new() -> Self {
+        let path = std::env::temp_dir().join(format!("merchant-emulator-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&path).expect("create test data dir");
+        Self(path)
+    }
+    
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for DataDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+pub struct Harness {
+    pub backend: MockBackend,
+    pub data_dir: DataDir,
+    pub storage: Arc<StorageService>,
+    pub stats: Arc<StatisticsService>,
+    pub merchant_service: Arc<MerchantService>,
+    pub device_manager: Arc<DeviceManager>,
+    pub device_notifications: Arc<DeviceNotificationService>,
+}
+
+impl Harness {
+    pub async fn start() -> Self {
+        Self::start_with(Script::default()).await
+    }
+    
+    pub async fn start_with(script: Script) -> Self {
+        let backend = MockBackend::start("127.0.0.1:0", Seed::default(), script)
+            .await
+            .expect("start mock backend");
+        let data_dir = DataDir::new();
+        
+        let storage = Arc::new(StorageService::new(data_dir.path().to_path_buf()).await.expect("open storage"));
+        let stats = Arc::new(StatisticsService::new());
+        let merchant_service = services(&backend, storage.clone(), stats.clone());
+        
+        let device_manager = Arc::new(DeviceManager::new(data_dir.path().to_path_buf()));
+        let device_notifications = Arc::new(DeviceNotificationService::new(
+            device_manager.clone(),
+            Arc::new(DeviceApiClient::new(backend.base_url())),
+        ));
+        
+        Self { backend, data_dir, storage, stats, merchant_service, device_manager, device_notifications }
+    }
+    
+    pub fn traffic_generator(&self) -> TrafficGenerator {
+        TrafficGenerator::new(self.merchant_service.clone(), self.device_notifications.clone())
+    }
+    
+    /// A scenario runner over the harness services, connecting devices through the mock.
+    pub fn scenario_runner(&self) -> ScenarioRunner {
+        ScenarioRunner::new(
+            self.merchant_service.clone(),
+            Arc::new(self.traffic_generator()),
+            self.storage.clone(),
+            self.device_manager.clone(),
+            Arc::new(DeviceApiClient::new(self.backend.base_url())),
+        )
+    }
+    
+    /// Adds an emulated device connected as the seeded mock device and linked
+    /// to the trader the mock assigns deposits to.
+    pub async fn connect_device(&self) -> String {
+        let device = self.device_manager.create_device("Test Device".to_string()).await.expect("create device");
+        self.device_manager
+            .connect_device(&device.id, DEVICE_CODE.to_string(), DEVICE_CODE.to_string())
+            .await
+            .expect("connect device");
+        self.device_manager.link_device_to_trader(&device.id, TRADER_ID).await.expect("link device");
+        device.id
+    }
+    
+    /// Creates the seeded mock merchant through the service, as the menu does.
+    pub async fn merchant(&self) -> Merchant {
+        self.merchant_service
+            .create_merchant("Test Merchant".to_string(), API_KEY.to_string(), None)
+            .await
+            .expect("create merchant")
+    }
+    
+    /// Creates a merchant on the seeded mock account sending a request every
+    /// 10ms, with `traffic` applied on top, and saves it as the menu would.
+    pub async fn merchant_with(&self, name: &str, traffic: impl FnOnce(&mut TrafficConfig)) -> Merchant {
+        let mut merchant = self.merchant_service
+            .create_merchant(name.to_string(), API_KEY.to_string(), None)
+            .await
+            .expect("create merchant");
+        merchant.traffic_config.interval_ms = 10;
+        merchant.traffic_config.interval_variance = 0;
+        traffic(&mut merchant.traffic_config);
+        
+        self.merchant_service.update_merchant(merchant.clone()).await.expect("save merchant");
+        merchant
+    }
+    
+    /// Config for a whole `App` over the harness backend and data directory,
+    /// with its own free callback port.
+    pub fn config(&self) -> Config {
+        Config {
+            api_base_url: self.backend.base_url(),
+            callback_server_port: free_port(),
+            data_dir: self.data_dir.path().to_path_buf(),
+            export_dir: self.data_dir.path().join("exports"),
+            ..Config::default()
+        }
+    }
+    
+    /// An `App` sharing the harness data directory, as a separate CLI invocation would.
+    pub async fn app(&self) -> App {
+        App::new(self.config()).await.expect("start app")
+    }
+    
+    /// Reopens storage from disk, as a restarted emulator would.
+    pub async fn reopen_storage(&self) -> StorageService {
+        StorageService::new(self.data_dir.path().to_path_buf()).await.expect("reopen storage")
+    }
+    
+    /// Starts a callback server on a free port and routes what it receives
+    /// through the merchant service. Returns the server's base URL.
+    pub async fn start_callback_server(&self) -> String {
+        let port = free_port();
+        let mut server = CallbackServer::new(port, self.storage.clone());
+        
+        let mut receiver = server.take_receiver().expect("callback receiver");
+        let merchant_service = self.merchant_service.clone();
+        tokio::spawn(async move {
+            while let Some(callback) = receiver.recv().await {
+                merchant_service.route_callback(callback).await.expect("route callback");
+            }
+        });
+        tokio::spawn(async move {
+            let _ = server.start().await;
+        });
+        
+        let url = format!("http://127.0.0.1:{}", port);
+        let ready = wait_for(|| std::net::TcpStream::connect(("127.0.0.1", port)).is_ok()).await;
+        assert!(ready, "callback server did not start");
+        url
+    }
+}
+
+fn services(backend: &MockBackend, storage: Arc<StorageService>, stats: Arc<StatisticsService>) -> Arc<MerchantService> {
+    // Keep retries short so failure tests do not wait on real backoff
+    let mut retry = RetryConfig::default();
+    for policy in [&mut retry.idempotent, &mut retry.non_idempotent] {
+        policy.base_delay_ms = 10;
+        policy.max_delay_ms = 50;
+    }
+    
+    let api_client = Arc::new(ApiClient::new(backend.base_url(), retry, stats.clone()).expect("api client"));
+    let catalog = Arc::new(CatalogService::new(api_client.clone(), stats.clone(), Duration::from_secs(300)));
+    let protocols = Arc::new(Protocols::new(api_client.clone()));
+    let status_tracker = Arc::new(StatusTracker::new(
+        protocols.clone(),
+        storage.clone(),
+        stats.clone(),
+        catalog.clone(),
+    ));
+    
+    Arc::new(MerchantService::new(
+        api_client,
+        storage,
+        stats,
+        status_tracker,
+        catalog,
+        protocols,
+    ))
+}
+
+pub fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .map(|addr| addr.port())
+        .expect("free port")
+}
+
+/// Polls `condition` for up to five seconds.
+pub async fn wait_for(mut condition: impl FnMut() -> bool) -> bool {
+    wait_for_async(|| std::future::ready(condition())).await
+}
+
+/// `wait_for` with a condition that has to await.
+pub async fn wait_for_async<F: Future<Output = bool>>(mut condition: impl FnMut() -> F) -> bool {
+    for _ in 0..200 {
+        if condition().await {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(25)).await;
+    }
+    condition().await
+}
+
+/// Waits up to five seconds for the merchant's traffic to stop.
+pub async fn wait_until_stopped(generator: &TrafficGenerator, merchant: &Merchant) -> bool {
+    wait_for_async(move || async move { !generator.is_running(&merchant.id).await }).await
+}
@@ -0,0 +1,207 @@
+mod common;
+
+use common::{free_port, wait_for, wait_for_async, Harness, API_KEY};
+use merchant_emulator::api::{ControlServer, ControlState};
+use parking_lot::Mutex;
+use reqwest::{Method, StatusCode};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn control_state(harness: &Harness, token: Option<&str>) -> ControlState {
+    ControlState {
+        merchant_service: harness.merchant_service.clone(),
+        traffic_generator: Arc::new(harness.traffic_generator()),
+        stats_service: harness.stats.clone(),
+        device_manager: harness.device_manager.clone(),
+        token: token.map(str::to_string),
+    }
+}
+
+/// A control API over the harness services.
+struct Control {
+    client: reqwest::Client,
+    url: String,
+    token: Option<&'static str>,
+}
+
+impl Control {
+    async fn start(harness: &Harness, token: Option<&'static str>) -> Self {
+        let port = free_port();
+        let server = ControlServer::new(format!("127.0.0.1:{}", port), control_state(harness, token));
+        tokio::spawn(async move {
+            let _ = server.start().await;
+        });
+        
+        let ready = wait_for(|| std::net::TcpStream::connect(("127.0.0.1", port)).is_ok()).await;
+        assert!(ready, "control API did not start");
+        Self { client: reqwest::Client::new(), url: format!("http://127.0.0.1:{}", port), token }
+    }
+    
+    async fn call(&self, method: Method, path: &str, body: Option<Value>) -> (StatusCode, Value) {
+        let mut request = self.client.request(method, format!("{}{}", self.url, path));
+        if let Some(token) = self.token {
+            request = request.bearer_auth(token);
+        }
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        let response = request.send().await.expect("control request");
+        let status = response.status();
+        (status, response.json().await.unwrap_or(Value::Null))
+    }
+    
+    async fn get(&self, path: &str) -> Value {
+        let (status, body) = self.call(Method::GET, path, None).await;
+        assert_eq!(status, StatusCode::OK, "GET {}: {}", path, body);
+        body
+    }
+    
+    async fn create_merchant(&self) -> String {
+        let (status, body) = self
+            .call(Method::POST, "/merchants", Some(json!({ "name": "shop", "api_key": API_KEY })))
+            .await;
+        assert_eq!(status, StatusCode::CREATED, "{}", body);
+        body["id"].as_str().unwrap().to_string()
+    }
+    
+    async fn wait_until_stopped(&self, merchant_id: &str) -> Value {
+        let path = format!("/merchants/{}", merchant_id);
+        let status = Mutex::new(Value::Null);
+        let (control, path, last) = (self, &path, &status);
+        
+        let stopped = wait_for_async(move || async move {
+            let status = control.get(path).await;
+            let stopped = status["running"] == false;
+            *last.lock() = status;
+            stopped
+        })
+        .await;
+        assert!(stopped, "traffic for {} did not stop", merchant_id);
+        status.into_inner()
+    }
+}
+
+#[tokio::test]
+async fn requests_need_the_token_when_one_is_set() {
+    let harness = Harness::start().await;
+    let control = Control::start(&harness, Some("secret")).await;
+    
+    let health = control.get("/health").await;
+    assert_eq!(health["status"], "ok");
+    assert_eq!(health["merchants"], 0);
+    
+    let guessing = Control { token: Some("secres"), ..control };
+    let (status, _) = guessing.call(Method::GET, "/merchants", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    
+    let anonymous = Control { token: None, ..guessing };
+    let (status, body) = anonymous.call(Method::GET, "/merchants", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert!(body["error"].as_str().unwrap().contains("token"));
+}
+
+#[tokio::test]
+async fn other_interfaces_need_a_token_unless_explicitly_allowed() {
+    let harness = Harness::start().await;
+    let addr = format!("0.0.0.0:{}", free_port());
+    
+    let refused = ControlServer::new(addr.clone(), control_state(&harness, None)).start().await;
+    assert!(refused.unwrap_err().to_string().contains("without a token"));
+    
+    let server = ControlServer::new(addr, control_state(&harness, None)).allow_unauthenticated(true);
+    let serving = tokio::time::timeout(Duration::from_millis(200), server.start()).await;
+    assert!(serving.is_err(), "control API stopped: {:?}", serving);
+}
+
+#[tokio::test]
+async fn traffic_is_started_and_runs_to_its_limit() {
+    let harness = Harness::start().await;
+    let control = Control::start(&harness, None).await;
+    let merchant_id = control.create_merchant().await;
+    
+    let listed = control.get("/merchants").await;
+    assert_eq!(listed[0]["id"], merchant_id.as_str());
+    assert_eq!(listed[0]["running"], false);
+    assert!(listed[0].get("api_key").is_none());
+    
+    let (status, update) = control
+        .call(Method::PATCH, &format!("/merchants/{}/traffic/config", merchant_id), Some(json!({ "interval_ms": 20, "interval_variance": 0 })))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", update);
+    assert_eq!(update["restarted"], false);
+    // Fields the patch leaves out keep their values
+    assert_eq!(update["traffic_config"]["cancel_delay_ms"], control.get(&format!("/merchants/{}/traffic/config", merchant_id)).await["cancel_delay_ms"]);
+    
+    let (status, started) = control
+        .call(Method::POST, &format!("/merchants/{}/traffic/start", merchant_id), Some(json!({ "max_transactions": 3 })))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", started);
+    assert_eq!(started["method_id"], "method-c2c");
+    
+    let finished = control.wait_until_stopped(&merchant_id).await;
+    assert_eq!(finished["statistics"]["successful_requests"], 3);
+    assert_eq!(control.get(&format!("/merchants/{}/statistics", merchant_id)).await["successful_requests"], 3);
+    assert_eq!(control.get("/statistics").await[&merchant_id]["successful_requests"], 3);
+    
+    let (status, _) = control.call(Method::POST, &format!("/merchants/{}/traffic/stop", merchant_id), None).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn config_changes_restart_running_traffic() {
+    let harness = Harness::start().await;
+    let control = Control::start(&harness, None).await;
+    let merchant_id = control.create_merchant().await;
+    let config = format!("/merchants/{}/traffic/config", merchant_id);
+    
+    control.call(Method::PATCH, &config, Some(json!({ "interval_ms": 50, "interval_variance": 0 }))).await;
+    let (status, _) = control
+        .call(Method::POST, &format!("/merchants/{}/traffic/start", merchant_id), Some(json!({ "seed": 9 })))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    
+    let (status, update) = control.call(Method::PATCH, &config, Some(json!({ "interval_ms": 10 }))).await;
+    assert_eq!(status, StatusCode::OK, "{}", update);
+    assert_eq!(update["restarted"], true);
+    assert_eq!(update["traffic_config"]["seed"], 9);
+    
+    let running = control.get(&format!("/merchants/{}", merchant_id)).await;
+    assert_eq!(running["running"], true);
+    assert_eq!(running["traffic_config"]["interval_ms"], 10);
+    
+    // Invalid settings are rejected and the run keeps going
+    let (status, body) = control.call(Method::PATCH, &config, Some(json!({ "cancel_percentage": 150 }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].as_str().unwrap().contains("Cancel percentage"));
+    
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let (status, stopped) = control.call(Method::POST, &format!("/merchants/{}/traffic/stop", merchant_id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(stopped["running"], false);
+    assert_eq!(stopped["traffic_config"]["enabled"], false);
+    
+    // The restarted run picked a new seed, so no order id was sent twice
+    let statistics = &stopped["statistics"];
+    assert_eq!(statistics["failed_requests"], 0);
+    let sent = statistics["successful_requests"].as_u64().unwrap();
+    assert!(sent > 5, "only {} orders", sent);
+    assert!(wait_for(|| harness.backend.state().transactions.read().len() as u64 >= sent).await);
+}
+
+#[tokio::test]
+async fn unknown_merchants_and_devices() {
+    let harness = Harness::start().await;
+    harness.connect_device().await;
+    let control = Control::start(&harness, None).await;
+    
+    let (status, body) = control.call(Method::GET, &format!("/merchants/{}", uuid::Uuid::new_v4()), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(body["error"].as_str().unwrap().contains("not found"));
+    
+    let devices = control.get("/devices").await;
+    assert_eq!(devices.as_array().unwrap().len(), 1);
+    assert_eq!(devices[0]["is_connected"], true);
+    assert_eq!(control.get("/health").await["devices_connected"], 1);
+}
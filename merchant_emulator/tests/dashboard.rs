@@ -0,0 +1,122 @@
+mod common;
+
+use common::Harness;
+use crossterm::event::KeyCode;
+use merchant_emulator::ui::Dashboard;
+use ratatui::{backend::TestBackend, Terminal};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn dashboard(harness: &Harness) -> Dashboard {
+    Dashboard::new(
+        harness.merchant_service.clone(),
+        Arc::new(harness.traffic_generator()),
+        harness.stats.clone(),
+        harness.storage.clone(),
+        harness.device_manager.clone(),
+    )
+}
+
+/// Draws the dashboard and returns the screen as text.
+fn render(dashboard: &mut Dashboard) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(140, 40)).unwrap();
+    terminal.draw(|frame| dashboard.draw(frame)).unwrap();
+    let buffer = terminal.backend().buffer();
+    (0..buffer.area.height)
+        .map(|y| (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[tokio::test]
+async fn shows_merchants_and_devices() {
+    let harness = Harness::start().await;
+    for name in ["alpha", "beta"] {
+        harness.merchant_with(name, |traffic| traffic.interval_ms = 20).await;
+    }
+    harness.connect_device().await;
+    let mut dashboard = dashboard(&harness);
+    
+    let empty = render(&mut dashboard);
+    assert!(empty.contains("No merchants yet"));
+    
+    dashboard.refresh().await;
+    let screen = render(&mut dashboard);
+    assert!(screen.contains("2 merchants, 0 running"), "{}", screen);
+    assert!(screen.contains("alpha") && screen.contains("beta"));
+    assert!(screen.contains("Test Device") && screen.contains("connected"));
+    assert!(screen.contains("sequential, 20ms"));
+    assert!(screen.contains("1/1 devices connected"));
+}
+
+#[tokio::test]
+async fn keys_switch_merchants_and_toggle_traffic() {
+    let harness = Harness::start().await;
+    for name in ["alpha", "beta"] {
+        harness.merchant_with(name, |traffic| traffic.interval_ms = 20).await;
+    }
+    let mut dashboard = dashboard(&harness);
+    dashboard.refresh().await;
+    
+    assert_eq!(dashboard.selected().unwrap().merchant.name, "alpha");
+    assert!(dashboard.handle_key(KeyCode::Down).await);
+    assert_eq!(dashboard.selected().unwrap().merchant.name, "beta");
+    dashboard.handle_key(KeyCode::Down).await;
+    assert_eq!(dashboard.selected().unwrap().merchant.name, "alpha");
+    dashboard.handle_key(KeyCode::Up).await;
+    
+    // Start beta, then watch its rate and success figures come in
+    dashboard.handle_key(KeyCode::Char('s')).await;
+    dashboard.refresh().await;
+    let beta = dashboard.selected().unwrap();
+    assert_eq!(beta.merchant.name, "beta");
+    assert!(beta.running);
+    assert!(beta.merchant.traffic_config.enabled);
+    assert!(render(&mut dashboard).contains("Started traffic for beta"));
+    
+    tokio::time::sleep(Duration::from_millis(600)).await;
+    dashboard.refresh().await;
+    let beta = dashboard.selected().unwrap();
+    assert!(beta.tps > 5.0, "tps {}", beta.tps);
+    assert_eq!(beta.success_rate(), Some(100.0));
+    assert!(!dashboard.rows()[0].running);
+    
+    dashboard.handle_key(KeyCode::Char('s')).await;
+    dashboard.refresh().await;
+    assert!(!dashboard.selected().unwrap().running);
+    assert!(render(&mut dashboard).contains("Stopped traffic for beta"));
+    
+    assert!(!dashboard.handle_key(KeyCode::Char('q')).await);
+}
+
+#[tokio::test]
+async fn stop_all_stops_every_running_merchant() {
+    let harness = Harness::start().await;
+    for name in ["alpha", "beta"] {
+        harness.merchant_with(name, |traffic| traffic.interval_ms = 20).await;
+    }
+    let mut dashboard = dashboard(&harness);
+    dashboard.refresh().await;
+    
+    dashboard.handle_key(KeyCode::Char('s')).await;
+    dashboard.handle_key(KeyCode::Tab).await;
+    dashboard.handle_key(KeyCode::Char('s')).await;
+    dashboard.refresh().await;
+    assert!(dashboard.rows().iter().all(|row| row.running));
+    
+    dashboard.handle_key(KeyCode::Char('x')).await;
+    dashboard.refresh().await;
+    assert!(dashboard.rows().iter().all(|row| !row.running));
+    assert!(render(&mut dashboard).contains("Stopped traffic for 2 merchants"));
+    
+    // Nothing more is sent once requests already in flight have answered
+    let sent = |dashboard: &Dashboard| -> u64 {
+        dashboard.rows().iter().map(|row| row.statistics.as_ref().unwrap().total_requests).sum()
+    };
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    dashboard.refresh().await;
+    let before = sent(&dashboard);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    dashboard.refresh().await;
+    assert_eq!(sent(&dashboard), before);
+}
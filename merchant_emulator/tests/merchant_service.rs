@@ -0,0 +1,162 @@
+mod common;
+
+use common::{Harness, METHOD_ID};
+use merchant_emulator::models::TransactionStatus;
+use mock_backend::{Endpoint, EndpointBehavior, Script};
+use std::collections::HashMap;
+
+#[tokio::test]
+async fn create_merchant_verifies_key_and_reads_balance() {
+    let harness = Harness::start().await;
+    
+    let merchant = harness.merchant().await;
+    
+    assert_eq!(merchant.balance_usdt, 1000.0);
+    assert!(harness.storage.get_merchant(&merchant.id).is_some());
+    assert!(harness.stats.get_statistics(&merchant.id).is_some());
+}
+
+#[tokio::test]
+async fn create_merchant_rejects_unknown_key() {
+    let harness = Harness::start().await;
+    
+    let result = harness
+        .merchant_service
+        .create_merchant("Unknown".to_string(), "wrong-key".to_string(), None)
+        .await;
+    
+    assert!(result.is_err());
+    assert!(harness.storage.get_all_merchants().is_empty());
+}
+
+#[tokio::test]
+async fn create_transaction_records_history_and_statistics() {
+    let harness = Harness::start().await;
+    let merchant = harness.merchant().await;
+    
+    let transaction = harness
+        .merchant_service
+        .create_transaction(&merchant, 1500.0, METHOD_ID.to_string(), false)
+        .await
+        .expect("create transaction");
+    
+    assert!(!transaction.id.is_empty());
+    assert_eq!(transaction.amount, 1500.0);
+    assert!(transaction.requisites.is_some());
+    
+    let created = harness.backend.state().transactions.read().clone();
+    assert_eq!(created.len(), 1);
+    assert_eq!(created[0].order_id, transaction.order_id);
+    
+    let history = harness.merchant_service.get_merchant_transactions(&merchant.id);
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].response_status, 201);
+    assert!(history[0].error.is_none());
+    
+    let stats = harness.stats.get_statistics(&merchant.id).unwrap();
+    assert_eq!(stats.total_requests, 1);
+    assert_eq!(stats.successful_requests, 1);
+    assert_eq!(stats.failed_requests, 0);
+    assert_eq!(stats.total_amount, 1500.0);
+    assert_eq!(stats.status_breakdown.get("InProgress"), Some(&1));
+}
+
+#[tokio::test]
+async fn create_transaction_failure_is_recorded_by_category() {
+    let mut script = Script::default();
+    script.deposits.no_requisite_rate = 100.0;
+    let harness = Harness::start_with(script).await;
+    let merchant = harness.merchant().await;
+    
+    let result = harness
+        .merchant_service
+        .create_transaction(&merchant, 1500.0, METHOD_ID.to_string(), false)
+        .await;
+    
+    assert!(result.is_err());
+    
+    let history = harness.merchant_service.get_merchant_transactions(&merchant.id);
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].response_status, 409);
+    assert!(history[0].error.is_some());
+    
+    let stats = harness.stats.get_statistics(&merchant.id).unwrap();
+    assert_eq!(stats.total_requests, 1);
+    assert_eq!(stats.successful_requests, 0);
+    assert_eq!(stats.failed_requests, 1);
+    assert_eq!(stats.total_amount, 0.0);
+    assert_eq!(stats.error_breakdown.get("No Requisites"), Some(&1));
+}
+
+#[tokio::test]
+async fn create_transaction_rejects_unknown_method() {
+    let harness = Harness::start().await;
+    let merchant = harness.merchant().await;
+    
+    let result = harness
+        .merchant_service
+        .create_transaction(&merchant, 1500.0, "missing-method".to_string(), false)
+        .await;
+    
+    assert!(result.is_err());
+    assert!(harness.backend.state().transactions.read().is_empty());
+    
+    let stats = harness.stats.get_statistics(&merchant.id).unwrap();
+    assert_eq!(stats.failed_requests, 1);
+    assert_eq!(stats.error_breakdown.get("Rejected"), Some(&1));
+}
+
+#[tokio::test]
+async fn server_errors_are_retried_before_succeeding() {
+    let behavior = EndpointBehavior {
+        fail_next: 2,
+        failure_status: 503,
+        ..EndpointBehavior::default()
+    };
+    let script = Script {
+        endpoints: HashMap::from([(Endpoint::TransactionCreate, behavior)]),
+        ..Script::default()
+    };
+    let harness = Harness::start_with(script).await;
+    let merchant = harness.merchant().await;
+    
+    harness
+        .merchant_service
+        .create_transaction(&merchant, 1500.0, METHOD_ID.to_string(), false)
+        .await
+        .expect("create transaction after retries");
+    
+    assert_eq!(harness.backend.state().request_count(Endpoint::TransactionCreate), 3);
+    
+    let stats = harness.stats.get_statistics(&merchant.id).unwrap();
+    assert_eq!(stats.successful_requests, 1);
+    assert_eq!(stats.failed_requests, 0);
+    assert_eq!(stats.retry_attempts, 2);
+    assert_eq!(stats.retries_recovered, 1);
+}
+
+#[tokio::test]
+async fn cancel_transaction_updates_history_and_statistics() {
+    let harness = Harness::start().await;
+    let merchant = harness.merchant().await;
+    
+    let transaction = harness
+        .merchant_service
+        .create_transaction(&merchant, 1500.0, METHOD_ID.to_string(), false)
+        .await
+        .expect("create transaction");
+    
+    harness
+        .merchant_service
+        .cancel_transaction(&merchant, &transaction.order_id)
+        .await
+        .expect("cancel transaction");
+    
+    let history = harness.storage.find_transaction(&transaction.order_id).unwrap();
+    assert!(history.canceled_at.is_some());
+    assert_eq!(history.transaction.status, TransactionStatus::Canceled);
+    
+    let stats = harness.stats.get_statistics(&merchant.id).unwrap();
+    assert_eq!(stats.cancelled_transactions, 1);
+    assert_eq!(stats.failed_cancellations, 0);
+}
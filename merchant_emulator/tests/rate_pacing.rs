@@ -0,0 +1,145 @@
+mod common;
+
+use common::{wait_for_async, Harness, METHOD_ID};
+use merchant_emulator::models::{Merchant, RateConfig, RateStage, TrafficPacing};
+use merchant_emulator::services::{RateProgress, TrafficGenerator};
+use mock_backend::{Endpoint, EndpointBehavior, Script};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::time::Duration;
+
+fn stages(stages: &[(u64, f64)]) -> RateConfig {
+    RateConfig {
+        target_tps: 0.0,
+        max_concurrency: 10,
+        stages: stages
+            .iter()
+            .map(|&(duration_secs, target_tps)| RateStage { duration_secs, target_tps })
+            .collect(),
+    }
+}
+
+/// Waits for the run to end, returning the last progress seen while it ran.
+async fn follow_run(generator: &TrafficGenerator, merchant: &Merchant) -> Option<RateProgress> {
+    let last = Mutex::new(None);
+    let seen = &last;
+    let finished = wait_for_async(move || async move {
+        if let Some(progress) = generator.rate_progress(&merchant.id).await {
+            *seen.lock() = Some(progress);
+        }
+        !generator.is_running(&merchant.id).await
+    })
+    .await;
+    
+    assert!(finished, "rate run did not finish");
+    last.into_inner()
+}
+
+#[test]
+fn stages_ramp_linearly_and_end_the_run() {
+    let rate = stages(&[(10, 100.0), (10, 100.0), (10, 0.0)]);
+    
+    assert_eq!(rate.rate_at(Duration::ZERO), Some(0.0));
+    assert_eq!(rate.rate_at(Duration::from_secs(5)), Some(50.0));
+    assert_eq!(rate.rate_at(Duration::from_secs(15)), Some(100.0));
+    assert_eq!(rate.rate_at(Duration::from_secs(25)), Some(50.0));
+    assert_eq!(rate.rate_at(Duration::from_secs(30)), None);
+    assert_eq!(rate.total_duration(), Some(Duration::from_secs(30)));
+    
+    let steady = RateConfig::default();
+    assert_eq!(steady.rate_at(Duration::from_secs(3600)), Some(steady.target_tps));
+}
+
+#[test]
+fn invalid_rates_are_rejected() {
+    assert!(RateConfig { max_concurrency: 0, ..RateConfig::default() }.validate().is_err());
+    assert!(RateConfig { target_tps: 0.0, ..RateConfig::default() }.validate().is_err());
+    assert!(stages(&[(0, 10.0)]).validate().is_err());
+    assert!(stages(&[(5, 0.0)]).validate().is_err());
+    assert!(stages(&[(5, 10.0), (5, 0.0)]).validate().is_ok());
+}
+
+#[tokio::test]
+async fn requests_overlap_up_to_the_concurrency_limit() {
+    // Each create takes 200ms, so a sequential run would manage five per second
+    let script = Script {
+        endpoints: HashMap::from([(Endpoint::TransactionCreate, EndpointBehavior { latency_ms: 200, ..EndpointBehavior::default() })]),
+        ..Script::default()
+    };
+    let harness = Harness::start_with(script).await;
+    let rate = RateConfig { target_tps: 40.0, max_concurrency: 20, stages: Vec::new() };
+    let merchant = harness
+        .merchant_with("Rate", |traffic| {
+            traffic.pacing = TrafficPacing::Rate(rate);
+            traffic.max_transactions = Some(20);
+        })
+        .await;
+    let generator = harness.traffic_generator();
+    
+    let started = std::time::Instant::now();
+    generator.start_traffic(merchant.clone(), METHOD_ID.to_string(), true).await.unwrap();
+    let progress = follow_run(&generator, &merchant).await.expect("rate progress");
+    
+    assert!(started.elapsed() < Duration::from_secs(2), "took {:?}", started.elapsed());
+    assert!(progress.in_flight > 1, "requests did not overlap");
+    assert_eq!(harness.backend.state().transactions.read().len(), 20);
+    assert_eq!(harness.stats.get_statistics(&merchant.id).unwrap().successful_requests, 20);
+}
+
+#[tokio::test]
+async fn a_saturated_backend_shows_up_as_lag() {
+    let script = Script {
+        endpoints: HashMap::from([(Endpoint::TransactionCreate, EndpointBehavior { latency_ms: 200, ..EndpointBehavior::default() })]),
+        ..Script::default()
+    };
+    let harness = Harness::start_with(script).await;
+    // Two slots at 200ms each cap throughput at 10 TPS, well under the target
+    let rate = RateConfig { target_tps: 50.0, max_concurrency: 2, stages: vec![RateStage { duration_secs: 1, target_tps: 50.0 }] };
+    let merchant = harness.merchant_with("Rate", |traffic| traffic.pacing = TrafficPacing::Rate(rate)).await;
+    let generator = harness.traffic_generator();
+    
+    let started = std::time::Instant::now();
+    generator.start_traffic(merchant.clone(), METHOD_ID.to_string(), true).await.unwrap();
+    let progress = follow_run(&generator, &merchant).await.expect("rate progress");
+    
+    assert!(progress.late > 0);
+    assert!(progress.max_lag_ms > 100, "max lag {} ms", progress.max_lag_ms);
+    assert!(progress.in_flight <= 2);
+    
+    // Every slot of the one-second ramp is still sent, just late, so the run overruns the stage
+    let created = harness.backend.state().transactions.read().len();
+    assert!((20..=26).contains(&created), "created {}", created);
+    assert!(started.elapsed() > Duration::from_millis(1500), "took {:?}", started.elapsed());
+}
+
+#[test]
+fn ramps_from_zero_accrue_their_first_request() {
+    // Over a ten minute ramp to 10 TPS the rate reaches t/60, so one request has accrued after about 11s
+    let ramp = stages(&[(600, 10.0)]);
+    let first = ramp.next_due(Duration::ZERO, 1.0).unwrap();
+    assert!((10.5..11.5).contains(&first.as_secs_f64()), "first due at {:?}", first);
+    
+    let second = ramp.next_due(first, 1.0).unwrap();
+    assert!(second - first < Duration::from_secs(5), "second due at {:?}", second);
+    
+    // A higher load multiplier brings it forward; nothing is due once the stages end first
+    let doubled = ramp.next_due(Duration::ZERO, 2.0).unwrap();
+    assert!((7.5..8.0).contains(&doubled.as_secs_f64()), "doubled due at {:?}", doubled);
+    assert_eq!(ramp.next_due(Duration::from_millis(599_950), 1.0), None);
+}
+
+#[tokio::test]
+async fn a_long_ramp_from_zero_sends_its_whole_schedule() {
+    // 0 -> 10 TPS over 3s sends 15 requests; stepping by the starting rate sent one
+    let harness = Harness::start().await;
+    let merchant = harness.merchant_with("Rate", |traffic| traffic.pacing = TrafficPacing::Rate(stages(&[(3, 10.0)]))).await;
+    let generator = harness.traffic_generator();
+    
+    let started = std::time::Instant::now();
+    generator.start_traffic(merchant.clone(), METHOD_ID.to_string(), true).await.unwrap();
+    follow_run(&generator, &merchant).await;
+    
+    let created = harness.backend.state().transactions.read().len();
+    assert!((13..=16).contains(&created), "created {}", created);
+    assert!(started.elapsed() >= Duration::from_millis(2500), "took {:?}", started.elapsed());
+}
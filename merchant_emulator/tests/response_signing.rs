@@ -0,0 +1,158 @@
+mod common;
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::routing::{get, post};
+use axum::Router;
+use common::{Harness, API_KEY, METHOD_ID};
+use merchant_emulator::api::signature::{self, SIGNATURE_HEADER};
+use merchant_emulator::api::{ApiClient, ApiError};
+use merchant_emulator::models::{Merchant, RetryConfig, TransactionRequest};
+use mock_backend::{Endpoint, EndpointBehavior, Script};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const PRIVATE_KEY: &str = "mock-private-key";
+
+/// A merchant holding `PRIVATE_KEY`, built without the connect check `create_merchant` makes.
+fn signing_merchant() -> Merchant {
+    Merchant::new("Signed".to_string(), API_KEY.to_string(), Some(PRIVATE_KEY.to_string()))
+}
+
+fn client(harness: &Harness, base_url: String) -> ApiClient {
+    ApiClient::new(base_url, RetryConfig::default(), harness.stats.clone()).unwrap()
+}
+
+type Captured = Arc<Mutex<Vec<(Option<String>, String)>>>;
+
+const PAYMENT: &str = r#"{"payment_id":7,"payment_status":"new"}"#;
+
+/// A backend that records signed request bodies, answers balance requests
+/// with a body that does not match its signature, signs Wellbit lookups and
+/// leaves Wellbit status updates unsigned.
+async fn local_backend() -> (String, Captured) {
+    async fn capture(State(captured): State<Captured>, headers: HeaderMap, body: Bytes) -> &'static str {
+        let signature = headers.get(SIGNATURE_HEADER).map(|v| v.to_str().unwrap().to_string());
+        captured.lock().push((signature, String::from_utf8(body.to_vec()).unwrap()));
+        "{}"
+    }
+    async fn balance() -> ([(&'static str, String); 1], &'static str) {
+        ([(SIGNATURE_HEADER, signature::sign(PRIVATE_KEY, r#"{"balance":5}"#))], r#"{"balance":1000}"#)
+    }
+    async fn signed_payment() -> ([(&'static str, String); 1], &'static str) {
+        ([(SIGNATURE_HEADER, signature::sign(PRIVATE_KEY, PAYMENT))], PAYMENT)
+    }
+    async fn unsigned_payment() -> &'static str {
+        PAYMENT
+    }
+    
+    let captured = Captured::default();
+    let app = Router::new()
+        .route("/api/merchant/transactions/create", post(capture))
+        .route("/api/merchant/balance", get(balance))
+        .route("/api/wellbit/payment/get", post(signed_payment))
+        .route("/api/wellbit/payment/status", post(unsigned_payment))
+        .with_state(captured.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (url, captured)
+}
+
+#[tokio::test]
+async fn signed_responses_are_accepted() {
+    let harness = Harness::start().await;
+    let (url, _) = local_backend().await;
+    let merchant = signing_merchant();
+    
+    let payment = client(&harness, url).wellbit_get_payment(&merchant.credentials(), 7).await.unwrap();
+    
+    assert_eq!(payment.payment_id, 7);
+}
+
+#[tokio::test]
+async fn requests_are_signed_over_their_canonical_body() {
+    let harness = Harness::start().await;
+    let (url, captured) = local_backend().await;
+    let merchant = signing_merchant();
+    
+    let request = TransactionRequest {
+        amount: 1500.0,
+        order_id: "signed-order".to_string(),
+        method_id: METHOD_ID.to_string(),
+        rate: Some(95.0),
+        expired_at: "2026-01-01T00:00:00Z".to_string(),
+        user_ip: None,
+        user_id: None,
+        transaction_type: None,
+        callback_uri: None,
+        success_uri: None,
+        fail_uri: None,
+        is_mock: Some(false),
+    };
+    // The reply is not a transaction, which does not matter here
+    let _ = client(&harness, url).create_transaction(&merchant.credentials(), request).await;
+    
+    let (signature, body) = captured.lock().pop().expect("request captured");
+    let signature = signature.expect("request signed");
+    assert!(signature::verify(PRIVATE_KEY, &body, &signature), "{} not signed by {}", body, signature);
+}
+
+#[tokio::test]
+async fn tampered_responses_are_rejected() {
+    let harness = Harness::start().await;
+    let (url, _) = local_backend().await;
+    let merchant = signing_merchant();
+    
+    let result = client(&harness, url.clone()).get_balance(&merchant.credentials()).await;
+    assert!(matches!(result, Err(ApiError::SignatureMismatch)), "{:?}", result);
+    
+    // Signed with a key other than the merchant's
+    let merchant = Merchant::new("Signed".to_string(), API_KEY.to_string(), Some("another-key".to_string()));
+    let result = client(&harness, url).wellbit_get_payment(&merchant.credentials(), 7).await;
+    assert!(matches!(result, Err(ApiError::SignatureMismatch)), "{:?}", result);
+}
+
+#[tokio::test]
+async fn keyed_native_merchants_work_unsigned() {
+    let harness = Harness::start().await;
+    harness.backend.state().merchants.write()[0].private_key = Some(PRIVATE_KEY.to_string());
+    
+    // The backend signs native routes only in the Wellbit response format
+    let merchant = harness.merchant_service
+        .create_merchant("Signed".to_string(), API_KEY.to_string(), Some(PRIVATE_KEY.to_string()))
+        .await
+        .unwrap();
+    let transaction = harness.merchant_service
+        .create_transaction(&merchant, 1500.0, METHOD_ID.to_string(), false)
+        .await
+        .unwrap();
+    
+    assert_eq!(transaction.amount, 1500.0);
+    let stats = harness.stats.get_statistics(&merchant.id).unwrap();
+    assert_eq!(stats.successful_requests, 1);
+    assert!(stats.error_breakdown.is_empty());
+}
+
+#[tokio::test]
+async fn missing_wellbit_signatures_are_mismatches() {
+    let harness = Harness::start().await;
+    let (url, _) = local_backend().await;
+    let merchant = signing_merchant();
+    
+    let result = client(&harness, url).wellbit_update_status(&merchant.credentials(), 7, "cancel").await;
+    assert!(matches!(result, Err(ApiError::SignatureMismatch)), "{:?}", result);
+    
+    // Unsigned error responses are still reported as what they are
+    harness.backend.set_script(Script {
+        endpoints: HashMap::from([(
+            Endpoint::MerchantBalance,
+            EndpointBehavior { fail_next: 10, failure_status: 400, ..EndpointBehavior::default() },
+        )]),
+        ..Script::default()
+    });
+    let result = client(&harness, harness.backend.base_url()).get_balance(&merchant.credentials()).await;
+    assert!(matches!(result, Err(ApiError::Validation { .. })), "{:?}", result);
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use common::{free_port, Harness, API_KEY};
+use merchant_emulator::api::{ApiClient, ApiError};
+use merchant_emulator::models::{Merchant, RetryConfig};
+use mock_backend::{Endpoint, EndpointBehavior, Script};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Short backoff, as in the harness, with the given `Retry-After` cap.
+fn retry_config(max_retry_after_ms: u64) -> RetryConfig {
+    let mut retry = RetryConfig::default();
+    for policy in [&mut retry.idempotent, &mut retry.non_idempotent] {
+        policy.base_delay_ms = 10;
+        policy.max_delay_ms = 50;
+        policy.max_retry_after_ms = max_retry_after_ms;
+    }
+    retry
+}
+
+/// A merchant known to the harness statistics, so retries are counted.
+fn merchant(harness: &Harness) -> Merchant {
+    let merchant = Merchant::new("Retries".to_string(), API_KEY.to_string(), None);
+    harness.stats.initialize_merchant(merchant.id);
+    merchant
+}
+
+fn throttle(harness: &Harness, status: u16, retry_after_secs: u64) {
+    harness.backend.set_script(Script {
+        endpoints: HashMap::from([(
+            Endpoint::MerchantBalance,
+            EndpointBehavior {
+                fail_next: 1,
+                failure_status: status,
+                retry_after_secs: Some(retry_after_secs),
+                ..EndpointBehavior::default()
+            },
+        )]),
+        ..Script::default()
+    });
+}
+
+/// Accepts connections and closes them without answering, counting each one.
+async fn dropping_backend() -> (String, Arc<AtomicUsize>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let accepted = Arc::new(AtomicUsize::new(0));
+    
+    let counter = accepted.clone();
+    tokio::spawn(async move {
+        while let Ok((socket, _)) = listener.accept().await {
+            counter.fetch_add(1, Ordering::SeqCst);
+            drop(socket);
+        }
+    });
+    (url, accepted)
+}
+
+#[tokio::test]
+async fn rate_limits_wait_out_retry_after() {
+    let harness = Harness::start().await;
+    let merchant = merchant(&harness);
+    let client = ApiClient::new(harness.backend.base_url(), retry_config(30_000), harness.stats.clone()).unwrap();
+    
+    for status in [429, 503] {
+        throttle(&harness, status, 1);
+        let started = Instant::now();
+        
+        let balance = client.get_balance(&merchant.credentials()).await.unwrap();
+        
+        assert_eq!(balance, 1000.0);
+        // The backoff alone would have retried within 60ms
+        assert!(started.elapsed() >= Duration::from_secs(1), "{} retried after {:?}", status, started.elapsed());
+    }
+    
+    assert_eq!(harness.backend.state().request_count(Endpoint::MerchantBalance), 4);
+    let stats = harness.stats.get_statistics(&merchant.id).unwrap();
+    assert_eq!(stats.retry_attempts, 2);
+    assert_eq!(stats.retries_recovered, 2);
+    assert_eq!(stats.retry_breakdown.get("Balance: Rate Limited"), Some(&1));
+    assert_eq!(stats.retry_breakdown.get("Balance: Server Error"), Some(&1));
+}
+
+#[tokio::test]
+async fn retry_after_beyond_the_cap_gives_up() {
+    let harness = Harness::start().await;
+    let merchant = merchant(&harness);
+    let client = ApiClient::new(harness.backend.base_url(), retry_config(500), harness.stats.clone()).unwrap();
+    
+    throttle(&harness, 429, 5);
+    let started = Instant::now();
+    
+    let result = client.get_balance(&merchant.credentials()).await;
+    
+    assert!(matches!(result, Err(ApiError::RateLimited { .. })), "{:?}", result);
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert_eq!(harness.backend.state().request_count(Endpoint::MerchantBalance), 1);
+    assert_eq!(harness.stats.get_statistics(&merchant.id).unwrap().retry_attempts, 0);
+}
+
+#[tokio::test]
+async fn dropped_connections_are_retried_only_for_idempotent_calls() {
+    let harness = Harness::start().await;
+    let merchant = merchant(&harness);
+    let (url, accepted) = dropping_backend().await;
+    let retry = retry_config(30_000);
+    let client = ApiClient::new(url, retry.clone(), harness.stats.clone()).unwrap();
+    
+    // The backend may have cancelled the order before the connection dropped
+    let result = client.cancel_transaction(&merchant.credentials(), "order-1").await;
+    assert!(matches!(result, Err(ApiError::Transport(_))), "{:?}", result);
+    assert_eq!(accepted.swap(0, Ordering::SeqCst), 1);
+    
+    let result = client.get_balance(&merchant.credentials()).await;
+    assert!(matches!(result, Err(ApiError::Transport(_))), "{:?}", result);
+    assert_eq!(accepted.load(Ordering::SeqCst), 1 + retry.idempotent.max_retries as usize);
+}
+
+#[tokio::test]
+async fn refused_connections_are_retried_for_every_call() {
+    let harness = Harness::start().await;
+    let merchant = merchant(&harness);
+    // Nothing listens on a port that was just released
+    let url = format!("http://127.0.0.1:{}", free_port());
+    let retry = retry_config(30_000);
+    let client = ApiClient::new(url, retry.clone(), harness.stats.clone()).unwrap();
+    
+    let result = client.cancel_transaction(&merchant.credentials(), "order-1").await;
+    
+    assert!(matches!(result, Err(ApiError::Transport(_))), "{:?}", result);
+    let stats = harness.stats.get_statistics(&merchant.id).unwrap();
+    assert_eq!(stats.retry_attempts, retry.non_idempotent.max_retries as u64);
+    assert_eq!(stats.retry_breakdown.get("CancelTransaction: Transport"), Some(&(retry.non_idempotent.max_retries as u64)));
+}
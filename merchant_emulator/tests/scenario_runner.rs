@@ -0,0 +1,155 @@
+mod common;
+
+use common::{Harness, API_KEY, DEVICE_CODE, TRADER_ID};
+use merchant_emulator::models::{AmountModel, Scenario, ScenarioResult, TrafficPacing};
+use merchant_emulator::services::ScenarioRunner;
+use std::path::Path;
+
+fn scenario(merchants: &str) -> String {
+    format!(
+        "name: smoke test\nseed: 7\nsettle_secs: 1\ndevices:\n  - name: Phone\n    device_code: {}\n    trader_id: {}\nmerchants:\n{}",
+        DEVICE_CODE, TRADER_ID, merchants
+    )
+}
+
+fn deposits(name: &str, method: &str, max_transactions: u64) -> String {
+    format!(
+        "  - name: {}\n    api_key: {}\n    method: {}\n    liquidity_percentage: 100\n    traffic:\n      interval_ms: 10\n      interval_variance: 0\n      max_transactions: {}\n      buyer:\n        enabled: true\n        other_pay_percentage: 100\n        bands: []\n        pay_delay_min_ms: 1\n        pay_delay_max_ms: 5\n",
+        name, API_KEY, method, max_transactions
+    )
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> T {
+    serde_json::from_str(&std::fs::read_to_string(path).expect("read bundle file")).expect("parse bundle file")
+}
+
+#[tokio::test]
+async fn the_example_scenario_is_valid() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios/example.yaml");
+    let scenario = ScenarioRunner::load(&path).await.unwrap();
+    
+    assert_eq!(scenario.merchants.len(), 2);
+    let c2c = &scenario.merchants[0].traffic;
+    assert!(matches!(&c2c.pacing, TrafficPacing::Rate(rate) if rate.stages.len() == 3));
+    assert!(matches!(c2c.amount_distribution.model, AmountModel::LogNormal { .. }));
+    // Settings a file leaves out keep their defaults
+    assert!(c2c.buyer.enabled);
+    assert_eq!(c2c.buyer.bands.len(), 6);
+    assert_eq!(scenario.merchants[1].traffic.interval_ms, 2000);
+    assert!(scenario.merchants[1].traffic.profile.enabled);
+}
+
+#[test]
+fn invalid_scenarios_are_rejected() {
+    assert!(Scenario::from_yaml(&scenario(&deposits("a", "method-c2c", 5))).is_ok());
+    
+    let twice = format!("{}{}", deposits("a", "method-c2c", 5), deposits("a", "method-sbp", 5));
+    assert!(Scenario::from_yaml(&scenario(&twice)).unwrap_err().contains("listed twice"));
+    
+    // Without max_transactions, stages or a duration the run would never end
+    let unbounded = format!("  - name: a\n    api_key: {}\n", API_KEY);
+    assert!(Scenario::from_yaml(&scenario(&unbounded)).unwrap_err().contains("never stops"));
+    assert!(Scenario::from_yaml(&format!("duration_secs: 5\n{}", scenario(&unbounded))).is_ok());
+    
+    let bad_cancel = deposits("a", "method-c2c", 5).replace("      buyer:", "      cancel_percentage: 150\n      buyer:");
+    assert!(Scenario::from_yaml(&scenario(&bad_cancel)).unwrap_err().contains("Cancel percentage"));
+    
+    let typo = deposits("a", "method-c2c", 5).replace("liquidity_percentage", "liquidity");
+    assert!(Scenario::from_yaml(&scenario(&typo)).is_err());
+    
+    let traffic_typo = deposits("a", "method-c2c", 5).replace("      buyer:", "      cancel_percentge: 10\n      buyer:");
+    assert!(Scenario::from_yaml(&scenario(&traffic_typo)).unwrap_err().contains("cancel_percentge"));
+}
+
+#[tokio::test]
+async fn a_run_writes_a_results_bundle() {
+    let harness = Harness::start().await;
+    let merchants = format!("{}{}", deposits("c2c", "method-c2c", 4), deposits("sbp", "method-sbp", 3));
+    let scenario = Scenario::from_yaml(&scenario(&merchants)).unwrap();
+    let runner = harness.scenario_runner();
+    
+    let result = runner.run(&scenario, harness.data_dir.path()).await.unwrap();
+    
+    assert!(!result.stopped_early);
+    assert_eq!(result.devices_connected, 1);
+    assert_eq!(harness.backend.state().transactions.read().len(), 7);
+    // Every buyer paid, so the connected device got a notification per order
+    let notified: usize = harness.backend.state().devices.read().iter().map(|d| d.notifications.len()).sum();
+    assert_eq!(notified, 7);
+    
+    let bundle = &result.bundle_dir;
+    assert!(bundle.starts_with(harness.data_dir.path().join("scenarios")));
+    let summary: ScenarioResult = read_json(&bundle.join("summary.json"));
+    assert_eq!(summary.merchants.len(), 2);
+    for (merchant, expected) in summary.merchants.iter().zip([4, 3]) {
+        assert_eq!(merchant.transactions, expected);
+        assert_eq!(merchant.statistics.successful_requests, expected as u64);
+        let transactions: Vec<serde_json::Value> = read_json(&bundle.join(&merchant.name).join("transactions.json"));
+        assert_eq!(transactions.len(), expected);
+        for file in ["payouts.json", "callbacks.json", "reconciliation.json", "statistics.json"] {
+            assert!(bundle.join(&merchant.name).join(file).exists(), "{} missing", file);
+        }
+    }
+    
+    // The copied scenario pins each merchant's seed, so it replays the same run
+    let resolved = Scenario::from_yaml(&std::fs::read_to_string(bundle.join("scenario.yaml")).unwrap()).unwrap();
+    for (merchant, result) in resolved.merchants.iter().zip(&summary.merchants) {
+        assert_eq!(merchant.traffic.seed, Some(result.seed));
+    }
+}
+
+#[tokio::test]
+async fn reruns_reuse_merchants() {
+    let harness = Harness::start().await;
+    // Unseeded, so the second run's order ids do not collide with the first's
+    let yaml = scenario(&deposits("c2c", "method-c2c", 3)).replace("seed: 7\n", "");
+    let scenario = Scenario::from_yaml(&yaml).unwrap();
+    let runner = harness.scenario_runner();
+    
+    let first = runner.run(&scenario, harness.data_dir.path()).await.unwrap();
+    let second = runner.run(&scenario, harness.data_dir.path()).await.unwrap();
+    
+    assert_eq!(harness.storage.get_all_merchants().len(), 1);
+    assert_eq!(first.merchants[0].merchant_id, second.merchants[0].merchant_id);
+    assert_ne!(first.merchants[0].seed, second.merchants[0].seed);
+    // Statistics and the bundle cover only the latest run
+    assert_eq!(second.merchants[0].transactions, 3);
+    assert_eq!(second.merchants[0].statistics.total_requests, 3);
+}
+
+#[tokio::test]
+async fn a_seeded_scenario_replays_on_a_fresh_backend() {
+    let scenario = Scenario::from_yaml(&scenario(&deposits("c2c", "method-c2c", 3))).unwrap();
+    let mut runs = Vec::new();
+    for _ in 0..2 {
+        let harness = Harness::start().await;
+        let result = harness.scenario_runner().run(&scenario, harness.data_dir.path()).await.unwrap();
+        let transactions: Vec<serde_json::Value> = read_json(&result.bundle_dir.join("c2c").join("transactions.json"));
+        runs.push(
+            transactions
+                .iter()
+                .map(|t| (t["transaction"]["order_id"].clone(), t["transaction"]["amount"].clone()))
+                .collect::<Vec<_>>(),
+        );
+    }
+    
+    assert_eq!(runs[0].len(), 3);
+    assert_eq!(runs[0], runs[1]);
+}
+
+#[tokio::test]
+async fn the_duration_stops_open_ended_merchants() {
+    let harness = Harness::start().await;
+    let open_ended = format!("  - name: open\n    api_key: {}\n    traffic:\n      interval_ms: 50\n      interval_variance: 0\n", API_KEY);
+    let scenario = Scenario::from_yaml(&format!("duration_secs: 1\n{}", scenario(&open_ended)).replace("settle_secs: 1", "settle_secs: 0")).unwrap();
+    let runner = harness.scenario_runner();
+    
+    let started = std::time::Instant::now();
+    let result = runner.run(&scenario, harness.data_dir.path()).await.unwrap();
+    
+    assert!(result.stopped_early);
+    assert!(started.elapsed() < std::time::Duration::from_secs(3), "took {:?}", started.elapsed());
+    assert!(result.merchants[0].transactions > 5);
+    // No method in the file, so the first one the backend offers is used
+    assert_eq!(result.merchants[0].method_id, "method-c2c");
+}
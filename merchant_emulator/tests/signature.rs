@@ -0,0 +1,45 @@
+use merchant_emulator::api::signature::{canonical_json, sign_value, verify};
+use merchant_emulator::models::{WellbitPaymentRequest, WellbitPaymentType};
+use serde_json::json;
+
+const PRIVATE_KEY: &str = "wellbit-private-key";
+
+fn payment() -> WellbitPaymentRequest {
+    WellbitPaymentRequest {
+        payment_id: 1700000001,
+        payment_amount: 5000.0,
+        payment_amount_usdt: 52.63,
+        payment_amount_profit: 0.0,
+        payment_amount_profit_usdt: 0.0,
+        payment_fee_percent_profit: 0.0,
+        payment_type: WellbitPaymentType::Card,
+        payment_bank: Some("SBERRUB".to_string()),
+        payment_course: 95.0,
+        payment_lifetime: 1800,
+        payment_status: "new".to_string(),
+    }
+}
+
+#[test]
+fn wellbit_payments_are_signed_like_the_backend() {
+    let value = serde_json::to_value(payment()).unwrap();
+    
+    // Taken from the backend: canonicalJson(body) and createHmac('sha256', key) in node
+    assert_eq!(
+        canonical_json(&value),
+        r#"{"payment_amount":5000,"payment_amount_profit":0,"payment_amount_profit_usdt":0,"payment_amount_usdt":52.63,"payment_bank":"SBERRUB","payment_course":95,"payment_fee_percent_profit":0,"payment_id":1700000001,"payment_lifetime":1800,"payment_status":"new","payment_type":"card"}"#
+    );
+    assert_eq!(sign_value(PRIVATE_KEY, &value), "fce2273f9e1d4b5b38e4ac1dfb88d9c2523821b8d7b9a662beadb9263cb7a0c9");
+}
+
+#[test]
+fn numbers_are_printed_as_javascript_prints_them() {
+    let value = json!({ "b": [1.0, 2.5, -0.0], "a": { "amount": 100.0, "fee": 0.35 } });
+    
+    assert_eq!(canonical_json(&value), r#"{"a":{"amount":100,"fee":0.35},"b":[1,2.5,0]}"#);
+    
+    // A body the backend sent with integers verifies against the same key either way
+    let signature = sign_value(PRIVATE_KEY, &value);
+    assert!(verify(PRIVATE_KEY, r#"{"b":[1,2.5,0],"a":{"amount":100,"fee":0.35}}"#, &signature));
+    assert!(!verify(PRIVATE_KEY, r#"{"b":[1,2.5,0],"a":{"amount":101,"fee":0.35}}"#, &signature));
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use common::{DataDir, Harness, METHOD_ID};
+use merchant_emulator::models::{Merchant, ProtocolKind, Statistics, TransactionStatus};
+use merchant_emulator::services::{StatisticsService, StorageService};
+
+#[tokio::test]
+async fn merchants_survive_a_restart() {
+    let data_dir = DataDir::new();
+    let storage = StorageService::new(data_dir.path().to_path_buf()).await.unwrap();
+    
+    let mut merchant = Merchant::new("Persisted".to_string(), "key".to_string(), Some("secret".to_string()));
+    merchant.traffic_config.max_transactions = Some(42);
+    merchant.protocol = ProtocolKind::Wellbit;
+    merchant.liquidity_percentage = 35.0;
+    storage.add_merchant(merchant.clone()).unwrap();
+    storage.save_merchants().await.unwrap();
+    
+    let reopened = StorageService::new(data_dir.path().to_path_buf()).await.unwrap();
+    let loaded = reopened.get_merchant(&merchant.id).expect("merchant reloaded");
+    
+    assert_eq!(loaded.name, "Persisted");
+    assert_eq!(loaded.private_key.as_deref(), Some("secret"));
+    assert_eq!(loaded.traffic_config.max_transactions, Some(42));
+    assert_eq!(loaded.traffic_config.amount_distribution, merchant.traffic_config.amount_distribution);
+    assert_eq!(loaded.protocol, ProtocolKind::Wellbit);
+    assert_eq!(loaded.liquidity_percentage, 35.0);
+}
+
+#[tokio::test]
+async fn transactions_and_statistics_survive_a_restart() {
+    let harness = Harness::start().await;
+    let merchant = harness.merchant().await;
+    
+    let transaction = harness
+        .merchant_service
+        .create_transaction(&merchant, 2500.0, METHOD_ID.to_string(), false)
+        .await
+        .unwrap();
+    harness.merchant_service.persist_statistics().await.unwrap();
+    
+    let reopened = harness.reopen_storage().await;
+    
+    let history = reopened.find_transaction(&transaction.order_id).expect("transaction reloaded");
+    assert_eq!(history.merchant_id, merchant.id);
+    assert_eq!(history.transaction.id, transaction.id);
+    assert_eq!(history.transaction.status, TransactionStatus::InProgress);
+    assert_eq!(history.protocol, ProtocolKind::Native);
+    assert_eq!(reopened.get_merchant_transactions(&merchant.id).len(), 1);
+    
+    // Statistics are restored into a fresh service the way main.rs does it
+    let stats = StatisticsService::new();
+    stats.load(reopened.get_all_statistics());
+    let restored = stats.get_statistics(&merchant.id).expect("statistics reloaded");
+    assert_eq!(restored.successful_requests, 1);
+    assert_eq!(restored.total_amount, 2500.0);
+}
+
+#[tokio::test]
+async fn only_open_transactions_are_resumed() {
+    let harness = Harness::start().await;
+    let merchant = harness.merchant().await;
+    
+    let mut orders = Vec::new();
+    for _ in 0..3 {
+        let transaction = harness
+            .merchant_service
+            .create_transaction(&merchant, 2500.0, METHOD_ID.to_string(), false)
+            .await
+            .unwrap();
+        orders.push(transaction.order_id);
+    }
+    harness.merchant_service.cancel_transaction(&merchant, &orders[0]).await.unwrap();
+    
+    // A restarted emulator picks up the two deposits still in progress, once
+    let app = harness.app().await;
+    let merchants = app.storage.get_all_merchants();
+    assert_eq!(app.status_tracker.resume(&merchants), 2);
+    assert_eq!(app.status_tracker.tracked_count(), 2);
+    assert_eq!(app.status_tracker.resume(&merchants), 0);
+}
+
+#[tokio::test]
+async fn statistics_written_in_the_old_format_still_load() {
+    let data_dir = DataDir::new();
+    let merchant_id = uuid::Uuid::new_v4();
+    
+    // Files from older versions lack the fields added since, which default to zero
+    let legacy = serde_json::json!({
+        merchant_id.to_string(): {
+            "merchant_id": merchant_id,
+            "total_requests": 3,
+            "successful_requests": 2,
+            "failed_requests": 1,
+            "total_amount": 4000.0,
+            "callbacks_received": 1,
+            "liquid_transactions": 2,
+            "non_liquid_transactions": 0,
+            "error_breakdown": { "Server Error": 1 },
+            "status_breakdown": { "Ready": 2 },
+        }
+    });
+    std::fs::write(data_dir.path().join("statistics.json"), legacy.to_string()).unwrap();
+    
+    let storage = StorageService::new(data_dir.path().to_path_buf()).await.unwrap();
+    let stats: Statistics = storage.get_statistics(&merchant_id).expect("statistics loaded");
+    
+    assert_eq!(stats.total_requests, 3);
+    assert_eq!(stats.error_breakdown.get("Server Error"), Some(&1));
+    assert_eq!(stats.cancelled_transactions, 0);
+    assert_eq!(stats.retry_attempts, 0);
+}
@@ -0,0 +1,182 @@
+mod common;
+
+use common::{wait_for, wait_until_stopped, Harness, METHOD_ID};
+use merchant_emulator::models::BuyerBehavior;
+use mock_backend::{DepositScript, Endpoint, EndpointBehavior, Script};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test]
+async fn traffic_stops_at_max_transactions() {
+    let harness = Harness::start().await;
+    let merchant = harness.merchant_with("Traffic", |traffic| traffic.max_transactions = Some(3)).await;
+    let generator = harness.traffic_generator();
+    
+    generator.start_traffic(merchant.clone(), METHOD_ID.to_string(), true).await.unwrap();
+    assert!(generator.is_running(&merchant.id).await);
+    
+    assert!(wait_until_stopped(&generator, &merchant).await, "traffic did not stop at the limit");
+    
+    assert_eq!(harness.backend.state().transactions.read().len(), 3);
+    assert_eq!(harness.merchant_service.get_merchant_transactions(&merchant.id).len(), 3);
+    
+    let stats = harness.stats.get_statistics(&merchant.id).unwrap();
+    assert_eq!(stats.successful_requests, 3);
+    assert!(stats.total_amount >= 3000.0);
+}
+
+#[tokio::test]
+async fn failed_creations_do_not_count_towards_the_limit() {
+    let behavior = EndpointBehavior {
+        fail_next: 2,
+        failure_status: 400,
+        ..EndpointBehavior::default()
+    };
+    let script = Script {
+        endpoints: HashMap::from([(Endpoint::TransactionCreate, behavior)]),
+        ..Script::default()
+    };
+    let harness = Harness::start_with(script).await;
+    let merchant = harness.merchant_with("Traffic", |traffic| traffic.max_transactions = Some(2)).await;
+    let generator = harness.traffic_generator();
+    
+    generator.start_traffic(merchant.clone(), METHOD_ID.to_string(), true).await.unwrap();
+    assert!(wait_until_stopped(&generator, &merchant).await);
+    
+    assert_eq!(harness.backend.state().request_count(Endpoint::TransactionCreate), 4);
+    assert_eq!(harness.backend.state().transactions.read().len(), 2);
+    
+    let stats = harness.stats.get_statistics(&merchant.id).unwrap();
+    assert_eq!(stats.successful_requests, 2);
+    assert_eq!(stats.failed_requests, 2);
+}
+
+#[tokio::test]
+async fn traffic_runs_until_stopped_without_a_limit() {
+    let harness = Harness::start().await;
+    let merchant = harness.merchant_with("Traffic", |_| {}).await;
+    let generator = Arc::new(harness.traffic_generator());
+    
+    generator.start_traffic(merchant.clone(), METHOD_ID.to_string(), true).await.unwrap();
+    assert!(generator.start_traffic(merchant.clone(), METHOD_ID.to_string(), true).await.is_err());
+    
+    let state = harness.backend.state().clone();
+    assert!(wait_for(|| state.transactions.read().len() >= 3).await);
+    
+    generator.stop_traffic(&merchant.id).await.unwrap();
+    assert!(!generator.is_running(&merchant.id).await);
+    
+    // At most the request in flight when stopping completes afterwards
+    let stopped_at = state.transactions.read().len();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(state.transactions.read().len() <= stopped_at + 1);
+}
+
+#[tokio::test]
+async fn traffic_uses_the_merchants_methods() {
+    let harness = Harness::start().await;
+    let merchant = harness.merchant_with("Traffic", |traffic| traffic.max_transactions = Some(10)).await;
+    let generator = harness.traffic_generator();
+    
+    generator.start_traffic(merchant.clone(), "not-a-method".to_string(), true).await.unwrap();
+    assert!(wait_until_stopped(&generator, &merchant).await);
+    
+    // The fallback method id is only used when the method list cannot be fetched
+    let transactions = harness.backend.state().transactions.read().clone();
+    assert_eq!(transactions.len(), 10);
+    assert!(transactions.iter().all(|t| t.method.id == "method-c2c" || t.method.id == "method-sbp"));
+}
+
+/// What a seeded run sent: deposit requests, receipt uploads and the bank
+/// notifications paying buyers triggered.
+#[derive(Debug, PartialEq)]
+struct Replay {
+    transactions: Vec<(String, f64, String, bool, Option<String>)>,
+    receipts: Vec<(String, Option<String>)>, // File name, and the file for PNGs; PDFs carry the upload time
+    notifications: Vec<String>,
+}
+
+/// Runs seeded traffic against a fresh backend and returns what was sent.
+async fn seeded_run(seed: u64) -> Replay {
+    // One bank type keeps the notification text down to what the emulator chose
+    let script = Script {
+        deposits: DepositScript { bank_types: vec!["TBANK".to_string()], ..DepositScript::default() },
+        ..Script::default()
+    };
+    let harness = Harness::start_with(script).await;
+    harness.connect_device().await;
+    let mut merchant = harness
+        .merchant_with("Traffic", |traffic| {
+            traffic.max_transactions = Some(8);
+            traffic.interval_variance = 5;
+            traffic.receipt_percentage = 50.0;
+            traffic.buyer = BuyerBehavior {
+                enabled: true,
+                other_pay_percentage: 100.0,
+                bands: Vec::new(),
+                pay_delay_min_ms: 1,
+                pay_delay_max_ms: 5,
+                ..BuyerBehavior::default()
+            };
+            traffic.seed = Some(seed);
+        })
+        .await;
+    merchant.liquidity_percentage = 50.0;
+    let generator = harness.traffic_generator();
+    
+    generator.start_traffic(merchant.clone(), METHOD_ID.to_string(), true).await.unwrap();
+    assert!(wait_until_stopped(&generator, &merchant).await);
+    
+    let mut history = harness.merchant_service.get_merchant_transactions(&merchant.id);
+    history.sort_by_key(|h| h.request_time);
+    
+    // Every liquid order is paid, and each payment reaches the one device
+    let liquid = history.iter().filter(|h| !h.request_body.is_mock.unwrap()).count();
+    let state = harness.backend.state().clone();
+    assert!(wait_for(|| state.devices.read()[0].notifications.len() == liquid).await);
+    // Uploads started during the run have all been answered
+    let stats = harness.stats.clone();
+    assert!(wait_for(|| stats.get_statistics(&merchant.id).unwrap().receipts_uploaded as usize == state.receipts.read().len()).await);
+    
+    let transactions = history
+        .into_iter()
+        .map(|h| {
+            let request = h.request_body;
+            (request.order_id, request.amount, request.method_id, request.is_mock.unwrap(), request.user_id)
+        })
+        .collect();
+    let mut receipts: Vec<_> = state
+        .receipts
+        .read()
+        .iter()
+        .map(|r| (r.file_name.clone(), r.file_name.ends_with(".png").then(|| r.file_data.clone())))
+        .collect();
+    receipts.sort();
+    let mut notifications: Vec<_> = state.devices.read()[0]
+        .notifications
+        .iter()
+        .map(|n| n["content"].as_str().unwrap().to_string())
+        .collect();
+    notifications.sort();
+    
+    Replay { transactions, receipts, notifications }
+}
+
+#[tokio::test]
+async fn seeded_runs_replay_identically() {
+    let first = seeded_run(42).await;
+    let second = seeded_run(42).await;
+    
+    assert_eq!(first.transactions.len(), 8);
+    assert_eq!(first, second);
+    
+    // Liquidity and methods are drawn too, not only amounts
+    assert!(first.transactions.iter().any(|t| t.3) && first.transactions.iter().any(|t| !t.3));
+    // Receipt images and buyer payments come from the same seed
+    assert!(first.receipts.iter().any(|r| r.1.is_some()), "{:?}", first.receipts);
+    assert!(!first.notifications.is_empty());
+    
+    let other = seeded_run(43).await;
+    assert_ne!(first, other);
+}
@@ -0,0 +1,157 @@
+mod common;
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use common::{wait_until_stopped, Harness, METHOD_ID};
+use merchant_emulator::models::{BurstWindow, DayFilter, RateConfig, TrafficPacing, TrafficProfile};
+use std::time::Duration;
+
+// 2024-06-03 is a Monday
+fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 6, day).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+}
+
+fn time(hour: u32, minute: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+}
+
+fn flat(weekday: f64, weekend: f64) -> TrafficProfile {
+    TrafficProfile {
+        enabled: true,
+        weekday_hours: vec![weekday; 24],
+        weekend_hours: vec![weekend; 24],
+        bursts: Vec::new(),
+        clock_speed: 1.0,
+        start_at: Some(at(3, 12, 0)),
+    }
+}
+
+#[test]
+fn multipliers_follow_the_hourly_curve() {
+    let mut profile = flat(1.0, 1.0);
+    profile.weekday_hours[8] = 1.0;
+    profile.weekday_hours[9] = 2.0;
+    
+    assert_eq!(profile.multiplier_at(at(3, 8, 0)), 1.0);
+    assert_eq!(profile.multiplier_at(at(3, 8, 30)), 1.5);
+    assert_eq!(profile.multiplier_at(at(3, 9, 0)), 2.0);
+    
+    // Saturday uses the weekend curve, and Friday's last hour ramps towards it
+    let profile = flat(1.0, 3.0);
+    assert_eq!(profile.multiplier_at(at(8, 12, 0)), 3.0);
+    assert_eq!(profile.multiplier_at(at(7, 23, 30)), 2.0);
+    assert_eq!(profile.multiplier_at(at(7, 12, 0)), 1.0);
+}
+
+#[test]
+fn bursts_multiply_the_curve_and_wrap_midnight() {
+    let mut profile = flat(1.0, 1.0);
+    profile.bursts = vec![
+        BurstWindow { start: time(12, 0), end: time(12, 30), multiplier: 3.0, days: DayFilter::All },
+        BurstWindow { start: time(23, 0), end: time(1, 0), multiplier: 2.0, days: DayFilter::Weekends },
+    ];
+    
+    assert_eq!(profile.multiplier_at(at(3, 12, 15)), 3.0);
+    assert_eq!(profile.multiplier_at(at(3, 12, 30)), 1.0);
+    
+    // Saturday night's burst runs into Sunday morning; Friday night has none
+    assert_eq!(profile.multiplier_at(at(8, 23, 30)), 2.0);
+    assert_eq!(profile.multiplier_at(at(9, 0, 30)), 2.0);
+    assert_eq!(profile.multiplier_at(at(7, 23, 30)), 1.0);
+    assert_eq!(profile.multiplier_at(at(8, 0, 30)), 1.0);
+}
+
+#[test]
+fn invalid_profiles_are_rejected() {
+    assert!(TrafficProfile::default().validate().is_ok());
+    assert!(flat(0.0, 0.0).validate().is_err());
+    
+    let mut profile = flat(1.0, 1.0);
+    profile.weekend_hours.pop();
+    assert!(profile.validate().is_err());
+    
+    let mut profile = flat(1.0, 1.0);
+    profile.clock_speed = 0.0;
+    assert!(profile.validate().is_err());
+    
+    let mut profile = flat(1.0, 1.0);
+    profile.bursts.push(BurstWindow { start: time(1, 0), end: time(2, 0), multiplier: -1.0, days: DayFilter::All });
+    assert!(profile.validate().is_err());
+}
+
+#[test]
+fn the_clock_runs_at_the_configured_speed() {
+    let mut profile = flat(1.0, 1.0);
+    profile.clock_speed = 3600.0;
+    let clock = profile.clock();
+    
+    std::thread::sleep(Duration::from_millis(500));
+    let simulated = clock.now() - at(3, 12, 0);
+    assert!((30..=40).contains(&simulated.num_minutes()), "simulated {} minutes", simulated.num_minutes());
+}
+
+#[tokio::test]
+async fn busy_hours_shorten_the_interval() {
+    let harness = Harness::start().await;
+    // Four times the base load turns 200ms pauses into 50ms ones
+    let merchant = harness
+        .merchant_with("Profiled", |traffic| {
+            traffic.interval_ms = 200;
+            traffic.profile = flat(4.0, 4.0);
+            traffic.max_transactions = Some(6);
+        })
+        .await;
+    let generator = harness.traffic_generator();
+    
+    let started = std::time::Instant::now();
+    generator.start_traffic(merchant.clone(), METHOD_ID.to_string(), true).await.unwrap();
+    assert!(wait_until_stopped(&generator, &merchant).await, "traffic did not stop");
+    
+    assert_eq!(harness.backend.state().transactions.read().len(), 6);
+    assert!(started.elapsed() < Duration::from_millis(700), "took {:?}", started.elapsed());
+}
+
+#[tokio::test]
+async fn quiet_hours_pause_traffic() {
+    let harness = Harness::start().await;
+    // Monday noon with nothing scheduled on weekdays
+    let merchant = harness
+        .merchant_with("Profiled", |traffic| {
+            traffic.interval_ms = 200;
+            traffic.profile = flat(0.0, 1.0);
+        })
+        .await;
+    let generator = harness.traffic_generator();
+    
+    generator.start_traffic(merchant.clone(), METHOD_ID.to_string(), true).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    assert!(generator.is_running(&merchant.id).await);
+    generator.stop_traffic(&merchant.id).await.unwrap();
+    
+    assert!(harness.backend.state().transactions.read().is_empty());
+}
+
+#[tokio::test]
+async fn rate_targets_scale_with_the_profile() {
+    let harness = Harness::start().await;
+    let merchant = harness
+        .merchant_with("Profiled", |traffic| {
+            traffic.profile = flat(0.5, 0.5);
+            traffic.max_transactions = Some(10);
+            traffic.pacing = TrafficPacing::Rate(RateConfig { target_tps: 40.0, max_concurrency: 10, stages: Vec::new() });
+        })
+        .await;
+    let generator = harness.traffic_generator();
+    
+    generator.start_traffic(merchant.clone(), METHOD_ID.to_string(), true).await.unwrap();
+    let mut progress = None;
+    while generator.is_running(&merchant.id).await {
+        progress = generator.rate_progress(&merchant.id).await.or(progress);
+        tokio::time::sleep(Duration::from_millis(25)).await;
+    }
+    
+    let progress = progress.expect("rate progress");
+    assert_eq!(progress.load_multiplier, 0.5);
+    assert_eq!(progress.target_tps, 20.0);
+    assert!(progress.simulated_time.is_some_and(|t| t >= at(3, 12, 0)));
+    assert_eq!(harness.backend.state().transactions.read().len(), 10);
+}
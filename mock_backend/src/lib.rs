@@ -0,0 +1,91 @@
+//! In-memory stand-in for the backend's merchant and device APIs, used to run
+//! the emulators and their tests offline.
+
+pub mod routes;
+pub mod script;
+pub mod state;
+
+pub use script::{DepositScript, Endpoint, EndpointBehavior, Script};
+pub use state::{MockDevice, MockMerchant, MockMethod, MockReceipt, MockState, MockTransaction, Seed};
+
+use anyhow::{Context, Result};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use tracing::info;
+
+/// A running mock backend. The server stops when this is dropped.
+pub struct MockBackend {
+    state: Arc<MockState>,
+    addr: SocketAddr,
+    server: JoinHandle<()>,
+    expiry: JoinHandle<()>,
+}
+
+impl MockBackend {
+    /// Binds `addr` (use port 0 for a free port) and starts serving.
+    pub async fn start(addr: &str, seed: Seed, script: Script) -> Result<Self> {
+        let listener = TcpListener::bind(addr)
+            .await
+            .with_context(|| format!("Failed to bind mock backend to {}", addr))?;
+        let addr = listener.local_addr()?;
+        
+        let state = Arc::new(MockState::new(seed, script));
+        let app = routes::router(state.clone());
+        
+        let server = tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                tracing::error!("Mock backend stopped: {}", e);
+            }
+        });
+        
+        let expiry_state = state.clone();
+        let expiry = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(1));
+            loop {
+                interval.tick().await;
+                expiry_state.expire_overdue();
+            }
+        });
+        
+        info!("Mock backend listening on {}", addr);
+        
+        Ok(Self { state, addr, server, expiry })
+    }
+    
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+    
+    /// Base URL for the merchant emulator, e.g. `http://127.0.0.1:3000`.
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+    
+    /// Base URL for the device emulator, which appends `/device/...` itself.
+    pub fn device_base_url(&self) -> String {
+        format!("http://{}/api", self.addr)
+    }
+    
+    pub fn state(&self) -> &Arc<MockState> {
+        &self.state
+    }
+    
+    pub fn set_script(&self, script: Script) {
+        *self.state.script.write() = script;
+    }
+    
+    /// Runs until the server task ends.
+    pub async fn wait(mut self) -> Result<()> {
+        (&mut self.server).await.context("Mock backend task failed")
+    }
+}
+
+impl Drop for MockBackend {
+    fn drop(&mut self) {
+        self.server.abort();
+        self.expiry.abort();
+    }
+}
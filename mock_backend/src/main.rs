@@ -0,0 +1,71 @@
+use anyhow::{Context, Result};
+use clap::Parser;
+use mock_backend::{MockBackend, Script, Seed};
+use serde::de::DeserializeOwned;
+use std::path::{Path, PathBuf};
+use tracing::info;
+use tracing_subscriber::{fmt, prelude::*, EnvFilter};
+
+/// In-memory stand-in for the backend's merchant and device APIs.
+#[derive(Debug, Parser)]
+#[command(name = "mock-backend", version)]
+struct Args {
+    #[arg(long, default_value = "0.0.0.0")]
+    host: String,
+    
+    #[arg(long, default_value_t = 3000)]
+    port: u16,
+    
+    /// JSON file with the initial script (latency, failures, deposit outcomes)
+    #[arg(long)]
+    script: Option<PathBuf>,
+    
+    /// JSON file with the merchants and devices to start with
+    #[arg(long)]
+    seed: Option<PathBuf>,
+}
+
+fn load<T: DeserializeOwned + Default>(path: Option<&Path>) -> Result<T> {
+    let Some(path) = path else {
+        return Ok(T::default());
+    };
+    
+    let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Initialize logging
+    tracing_subscriber::registry()
+        .with(fmt::layer())
+        .with(
+            EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| EnvFilter::new("info"))
+        )
+        .init();
+    
+    let args = Args::parse();
+    let seed: Seed = load(args.seed.as_deref())?;
+    let script: Script = load(args.script.as_deref())?;
+    
+    for merchant in &seed.merchants {
+        info!("Merchant {} ({}) with API key {}", merchant.name, merchant.id, merchant.api_key);
+    }
+    for device in &seed.devices {
+        info!("Device {} ({}) with code {}", device.name, device.id, device.code);
+    }
+    
+    let backend = MockBackend::start(&format!("{}:{}", args.host, args.port), seed, script).await?;
+    info!("Merchant emulator base URL: {}", backend.base_url());
+    info!("Device emulator base URL: {}", backend.device_base_url());
+    
+    tokio::select! {
+        result = tokio::signal::ctrl_c() => {
+            result?;
+            info!("Shutting down");
+            Ok(())
+        }
+        result = backend.wait() => result,
+    }
+}
@@ -0,0 +1,100 @@
+use crate::script::Script;
+use crate::state::{MockDevice, MockMerchant, MockState};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Json, Response};
+use axum::routing::{get, post};
+use axum::Router;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use tracing::info;
+
+/// Endpoints for scripting and inspecting the mock. They are not part of the
+/// backend API and are never intercepted.
+pub fn routes() -> Router<Arc<MockState>> {
+    Router::new()
+        .route("/__mock/state", get(snapshot))
+        .route("/__mock/script", get(get_script).put(put_script))
+        .route("/__mock/reset", post(reset))
+        .route("/__mock/merchants", post(add_merchant))
+        .route("/__mock/devices", post(add_device))
+        .route("/__mock/devices/{id}/commands", post(push_command))
+        .route("/__mock/transactions/{id}/status", post(set_status))
+        .route("/__mock/requests", get(requests))
+        .route("/__mock/callbacks", get(callbacks))
+}
+
+#[derive(Debug, Deserialize)]
+struct StatusChange {
+    status: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Command {
+    command: String,
+    #[serde(default)]
+    data: serde_json::Value,
+}
+
+async fn snapshot(State(state): State<Arc<MockState>>) -> Json<serde_json::Value> {
+    Json(state.snapshot())
+}
+
+async fn get_script(State(state): State<Arc<MockState>>) -> Json<Script> {
+    Json(state.script.read().clone())
+}
+
+async fn put_script(State(state): State<Arc<MockState>>, Json(script): Json<Script>) -> StatusCode {
+    info!("Script replaced");
+    *state.script.write() = script;
+    StatusCode::NO_CONTENT
+}
+
+async fn reset(State(state): State<Arc<MockState>>) -> StatusCode {
+    info!("State reset");
+    state.reset();
+    StatusCode::NO_CONTENT
+}
+
+/// Adds a merchant, replacing any existing one with the same id.
+async fn add_merchant(State(state): State<Arc<MockState>>, Json(merchant): Json<MockMerchant>) -> StatusCode {
+    let mut merchants = state.merchants.write();
+    merchants.retain(|m| m.id != merchant.id);
+    merchants.push(merchant);
+    StatusCode::CREATED
+}
+
+/// Adds a device, replacing any existing one with the same id.
+async fn add_device(State(state): State<Arc<MockState>>, Json(device): Json<MockDevice>) -> StatusCode {
+    let mut devices = state.devices.write();
+    devices.retain(|d| d.id != device.id);
+    devices.push(device);
+    StatusCode::CREATED
+}
+
+async fn push_command(State(state): State<Arc<MockState>>, Path(id): Path<String>, Json(command): Json<Command>) -> Response {
+    let command = json!({ "command": command.command, "data": command.data });
+    
+    if state.push_command(&id, command) {
+        StatusCode::ACCEPTED.into_response()
+    } else {
+        (StatusCode::NOT_FOUND, Json(json!({ "error": "Device not found" }))).into_response()
+    }
+}
+
+/// Forces a deposit status by transaction id or order id, sending the callback.
+async fn set_status(State(state): State<Arc<MockState>>, Path(id): Path<String>, Json(change): Json<StatusChange>) -> Response {
+    match state.set_transaction_status(&id, &change.status) {
+        Some(transaction) => Json(json!(transaction)).into_response(),
+        None => (StatusCode::NOT_FOUND, Json(json!({ "error": "Transaction not found" }))).into_response(),
+    }
+}
+
+async fn requests(State(state): State<Arc<MockState>>) -> Json<serde_json::Value> {
+    Json(json!(*state.requests.read()))
+}
+
+async fn callbacks(State(state): State<Arc<MockState>>) -> Json<serde_json::Value> {
+    Json(json!(*state.callbacks.read()))
+}
@@ -0,0 +1,182 @@
+use crate::script::Endpoint;
+use crate::state::{MockDevice, MockState};
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Json, Response};
+use axum::routing::{get, post};
+use axum::Router;
+use chrono::Utc;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+
+const DEVICE_TOKEN_HEADER: &str = "x-device-token";
+
+pub fn routes() -> Router<Arc<MockState>> {
+    Router::new()
+        .route("/api/device/connect", post(connect))
+        .route("/api/device/notification", post(notification))
+        .route("/api/device/info/update", post(info_update))
+        .route("/api/device/ping", get(ping))
+        .route("/api/device/health-check", post(health_check))
+        .route("/api/device/long-poll", post(long_poll))
+}
+
+fn device_error(status: StatusCode, message: &str) -> Response {
+    (status, Json(json!({ "status": "error", "message": message }))).into_response()
+}
+
+fn success(message: &str) -> Response {
+    Json(json!({ "status": "success", "message": message })).into_response()
+}
+
+/// Marks the device as seen and applies `update` to it. Returns None for unknown tokens.
+fn touch(state: &MockState, token: &str, update: impl FnOnce(&mut MockDevice)) -> Option<MockDevice> {
+    let mut devices = state.devices.write();
+    let device = devices.iter_mut().find(|d| d.code == token)?;
+    
+    device.online = true;
+    device.last_seen = Some(Utc::now());
+    update(device);
+    
+    Some(device.clone())
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+}
+
+fn device_token(headers: &HeaderMap) -> Option<&str> {
+    headers.get(DEVICE_TOKEN_HEADER).and_then(|v| v.to_str().ok())
+}
+
+fn battery_level(body: &Value) -> Option<u8> {
+    body.get("batteryLevel").and_then(Value::as_u64).map(|level| level.min(100) as u8)
+}
+
+async fn connect(State(state): State<Arc<MockState>>, Json(body): Json<Value>) -> Response {
+    if let Some(response) = state.intercept(Endpoint::DeviceConnect).await {
+        return response;
+    }
+    
+    let code = body.get("deviceCode").and_then(Value::as_str).unwrap_or_default().trim();
+    if code.is_empty() {
+        return device_error(StatusCode::BAD_REQUEST, "Device code is required and must be a non-empty string");
+    }
+    
+    match touch(&state, code, |device| device.battery_level = battery_level(&body).or(device.battery_level)) {
+        Some(device) => Json(json!({
+            "status": "success",
+            "token": device.code,
+            "message": "Device connected successfully",
+        }))
+        .into_response(),
+        None => device_error(StatusCode::BAD_REQUEST, "Invalid device code"),
+    }
+}
+
+async fn notification(State(state): State<Arc<MockState>>, headers: HeaderMap, Json(body): Json<Value>) -> Response {
+    if let Some(response) = state.intercept(Endpoint::DeviceNotification).await {
+        return response;
+    }
+    
+    let Some(token) = bearer_token(&headers) else {
+        return device_error(StatusCode::UNAUTHORIZED, "Unauthorized: Missing or invalid token");
+    };
+    
+    match touch(&state, token, |device| device.notifications.push(body)) {
+        Some(_) => success("Notification received"),
+        None => device_error(StatusCode::UNAUTHORIZED, "Device not found or invalid token"),
+    }
+}
+
+async fn info_update(State(state): State<Arc<MockState>>, headers: HeaderMap, Json(body): Json<Value>) -> Response {
+    if let Some(response) = state.intercept(Endpoint::DeviceInfoUpdate).await {
+        return response;
+    }
+    
+    let Some(token) = bearer_token(&headers) else {
+        return device_error(StatusCode::UNAUTHORIZED, "Unauthorized: Missing or invalid token");
+    };
+    
+    match touch(&state, token, |device| device.battery_level = battery_level(&body).or(device.battery_level)) {
+        Some(_) => success("Device info updated"),
+        None => device_error(StatusCode::UNAUTHORIZED, "Device not found or invalid token"),
+    }
+}
+
+/// The token is optional here; unknown tokens still get a success, as in the backend.
+async fn ping(State(state): State<Arc<MockState>>, headers: HeaderMap) -> Response {
+    if let Some(response) = state.intercept(Endpoint::DevicePing).await {
+        return response;
+    }
+    
+    if let Some(token) = device_token(&headers) {
+        touch(&state, token, |_| {});
+    }
+    
+    success("Device API is working")
+}
+
+async fn health_check(State(state): State<Arc<MockState>>, headers: HeaderMap, body: Option<Json<Value>>) -> Response {
+    if let Some(response) = state.intercept(Endpoint::DeviceHealthCheck).await {
+        return response;
+    }
+    
+    let Some(token) = device_token(&headers) else {
+        return device_error(StatusCode::UNAUTHORIZED, "Missing device token");
+    };
+    
+    let body = body.map(|Json(body)| body).unwrap_or_default();
+    match touch(&state, token, |device| device.battery_level = battery_level(&body).or(device.battery_level)) {
+        Some(_) => success("Health check received"),
+        None => device_error(StatusCode::UNAUTHORIZED, "Invalid device token"),
+    }
+}
+
+/// Holds the request until a command is queued through `/__mock` or the
+/// scripted timeout passes.
+async fn long_poll(State(state): State<Arc<MockState>>, headers: HeaderMap, Json(body): Json<Value>) -> Response {
+    if let Some(response) = state.intercept(Endpoint::DeviceLongPoll).await {
+        return response;
+    }
+    
+    let Some(token) = device_token(&headers) else {
+        return (StatusCode::BAD_REQUEST, Json(json!({ "error": "Device token required" }))).into_response();
+    };
+    
+    let Some(device) = touch(&state, token, |device| device.battery_level = battery_level(&body).or(device.battery_level)) else {
+        return (StatusCode::UNAUTHORIZED, Json(json!({ "error": "Invalid device token" }))).into_response();
+    };
+    
+    let deadline = Instant::now() + Duration::from_millis(state.script.read().long_poll_timeout_ms);
+    
+    loop {
+        // Register for wakeups before checking, so a command queued in between is not missed
+        let notified = state.commands_changed.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+        
+        if let Some(command) = state.pop_command(&device.id) {
+            return Json(json!({
+                "status": "command",
+                "command": command.get("command").cloned().unwrap_or(Value::Null),
+                "data": command.get("data").cloned().unwrap_or(Value::Null),
+            }))
+            .into_response();
+        }
+        
+        if tokio::time::timeout_at(deadline, notified).await.is_err() {
+            return Json(json!({
+                "status": "timeout",
+                "keepAlive": true,
+                "deviceStatus": { "id": device.id, "isOnline": true, "isWorking": true },
+            }))
+            .into_response();
+        }
+    }
+}
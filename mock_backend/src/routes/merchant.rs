@@ -0,0 +1,471 @@
+use crate::routes::signed_json;
+use crate::script::Endpoint;
+use crate::state::{MockMerchant, MockMethod, MockReceipt, MockRequisites, MockState, MockTransaction};
+use axum::body::Bytes;
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Json, Response};
+use axum::routing::{get, patch, post};
+use axum::Router;
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+const API_KEY_HEADER: &str = "x-merchant-api-key";
+
+const STATUSES: [&str; 9] = [
+    "CREATED", "IN_PROGRESS", "DISPUTE", "EXPIRED", "READY", "MILK", "CANCELED", "PAUSED", "FUNDS_RETURNED",
+];
+const BANK_TYPES: [&str; 10] = [
+    "SBERBANK", "TBANK", "VTB", "ALFABANK", "RAIFFEISEN", "GAZPROMBANK", "OZONBANK", "POCHTABANK", "ROSBANK", "SOVCOMBANK",
+];
+
+pub fn routes() -> Router<Arc<MockState>> {
+    Router::new()
+        .route("/api/merchant/connect", get(connect))
+        .route("/api/merchant/balance", get(balance))
+        .route("/api/merchant/enums", get(enums))
+        .route("/api/merchant/methods", get(methods))
+        .route("/api/merchant/transactions/create", post(create_transaction))
+        .route("/api/merchant/transactions", get(list_transactions))
+        .route("/api/merchant/transactions/list", get(list_transactions))
+        .route("/api/merchant/transactions/status/{id}", get(transaction_status))
+        .route("/api/merchant/transactions/by-order-id/{order_id}/cancel", patch(cancel_transaction))
+        .route("/api/merchant/transactions/{id}/receipt", post(upload_receipt))
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateTransactionRequest {
+    amount: f64,
+    #[serde(rename = "orderId", alias = "order_id")]
+    order_id: String,
+    #[serde(rename = "methodId", alias = "method_id")]
+    method_id: String,
+    #[serde(default)]
+    rate: Option<f64>,
+    #[serde(default, alias = "expiredAt")]
+    expired_at: Option<String>,
+    #[serde(default, alias = "callbackUri")]
+    callback_uri: Option<String>,
+    #[serde(default, alias = "isMock")]
+    is_mock: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReceiptUploadRequest {
+    #[serde(rename = "fileData")]
+    file_data: String,
+    #[serde(rename = "fileName")]
+    file_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListQuery {
+    page: Option<usize>,
+    limit: Option<usize>,
+    status: Option<String>,
+    #[serde(rename = "orderId")]
+    order_id: Option<String>,
+}
+
+fn error(status: StatusCode, message: &str) -> Response {
+    (status, Json(json!({ "error": message }))).into_response()
+}
+
+/// Resolves the merchant from its API key header, or the reason it could not be.
+fn authenticate(state: &MockState, headers: &HeaderMap) -> Result<MockMerchant, &'static str> {
+    let api_key = headers
+        .get(API_KEY_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .ok_or("Missing API key")?;
+    
+    state.merchant_by_key(api_key).ok_or("Invalid API key")
+}
+
+/// Shared prologue: scripted behaviour first, then merchant authentication.
+async fn begin(state: &MockState, endpoint: Endpoint, headers: &HeaderMap) -> Result<MockMerchant, Response> {
+    if let Some(response) = state.intercept(endpoint).await {
+        return Err(response);
+    }
+    authenticate(state, headers).map_err(|message| error(StatusCode::UNAUTHORIZED, message))
+}
+
+fn method_json(method: &MockMethod) -> serde_json::Value {
+    json!({
+        "id": method.id,
+        "code": method.code,
+        "name": method.name,
+        "type": method.method_type,
+        "currency": method.currency,
+    })
+}
+
+fn list_item_json(transaction: &MockTransaction) -> serde_json::Value {
+    json!({
+        "id": transaction.id,
+        "orderId": transaction.order_id,
+        "amount": transaction.amount,
+        "status": transaction.status,
+        "type": "IN",
+        "createdAt": transaction.created_at.to_rfc3339(),
+        "updatedAt": transaction.updated_at.to_rfc3339(),
+        "isMock": transaction.is_mock,
+        "method": method_json(&transaction.method),
+    })
+}
+
+async fn connect(State(state): State<Arc<MockState>>, headers: HeaderMap) -> Response {
+    let merchant = match begin(&state, Endpoint::MerchantConnect, &headers).await {
+        Ok(merchant) => merchant,
+        Err(response) => return response,
+    };
+    
+    let (total, paid) = {
+        let transactions = state.transactions.read();
+        let own = transactions.iter().filter(|t| t.merchant_id == merchant.id);
+        let paid = own.clone().filter(|t| t.status == "READY").count();
+        (own.count(), paid)
+    };
+    
+    let body = json!({
+        "id": merchant.id,
+        "name": merchant.name,
+        "createdAt": Utc::now().to_rfc3339(),
+        "totalTx": total,
+        "paidTx": paid,
+    });
+    Json(body).into_response()
+}
+
+async fn balance(State(state): State<Arc<MockState>>, headers: HeaderMap) -> Response {
+    match begin(&state, Endpoint::MerchantBalance, &headers).await {
+        Ok(merchant) => Json(json!({ "balance": merchant.balance })).into_response(),
+        Err(response) => response,
+    }
+}
+
+async fn enums(State(state): State<Arc<MockState>>, headers: HeaderMap) -> Response {
+    if let Err(response) = begin(&state, Endpoint::MerchantEnums, &headers).await {
+        return response;
+    }
+    
+    let body = json!({
+        "status": STATUSES,
+        "transactionType": ["IN", "OUT"],
+        "methodType": ["upi", "c2ckz", "c2cuz", "c2caz", "c2c", "sbp", "spay", "tpay", "vpay", "apay", "m2ctj", "m2ntj", "m2csber", "m2ctbank", "connectc2c", "connectsbp", "nspk", "ecom", "crypto"],
+        "currency": ["rub", "usdt"],
+        "bankType": BANK_TYPES,
+    });
+    Json(body).into_response()
+}
+
+async fn methods(State(state): State<Arc<MockState>>, headers: HeaderMap) -> Response {
+    let merchant = match begin(&state, Endpoint::MerchantMethods, &headers).await {
+        Ok(merchant) => merchant,
+        Err(response) => return response,
+    };
+    
+    let body: Vec<_> = merchant
+        .methods
+        .iter()
+        .map(|m| {
+            let mut value = method_json(m);
+            value["minPayin"] = json!(m.min_payin);
+            value["maxPayin"] = json!(m.max_payin);
+            value["isEnabled"] = json!(true);
+            value
+        })
+        .collect();
+    Json(json!(body)).into_response()
+}
+
+async fn create_transaction(State(state): State<Arc<MockState>>, headers: HeaderMap, body: Bytes) -> Response {
+    let merchant = match begin(&state, Endpoint::TransactionCreate, &headers).await {
+        Ok(merchant) => merchant,
+        Err(response) => return response,
+    };
+    
+    let request: CreateTransactionRequest = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(e) => return error(StatusCode::BAD_REQUEST, &format!("Invalid request body: {}", e)),
+    };
+    
+    let Some(method) = merchant.methods.iter().find(|m| m.id == request.method_id || m.code == request.method_id) else {
+        return error(StatusCode::NOT_FOUND, "Method not found");
+    };
+    
+    if request.amount < method.min_payin || request.amount > method.max_payin {
+        return error(StatusCode::BAD_REQUEST, "Amount out of allowed range");
+    }
+    
+    let duplicate = state
+        .transactions
+        .read()
+        .iter()
+        .any(|t| t.merchant_id == merchant.id && t.order_id == request.order_id);
+    if duplicate {
+        return error(StatusCode::CONFLICT, "Transaction with this orderId already exists");
+    }
+    
+    let deposits = state.script.read().deposits.clone();
+    let mut rng = rand::thread_rng();
+    
+    if deposits.no_requisite_rate > 0.0 && rng.gen_range(0.0..100.0) < deposits.no_requisite_rate {
+        return error(StatusCode::CONFLICT, "NO_REQUISITE");
+    }
+    
+    let now = Utc::now();
+    let expired_at = request
+        .expired_at
+        .as_deref()
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+        .map(|d| d.with_timezone(&Utc))
+        .unwrap_or_else(|| now + chrono::Duration::hours(24));
+    let card_number: String = std::iter::once("2200".to_string())
+        .chain((0..12).map(|_| rng.gen_range(0..10).to_string()))
+        .collect();
+    
+    let transaction = MockTransaction {
+        id: Uuid::new_v4().to_string(),
+        numeric_id: state.next_numeric_id(),
+        order_id: request.order_id,
+        merchant_id: merchant.id.clone(),
+        amount: request.amount,
+        rate: request.rate,
+        status: deposits.initial_status.clone(),
+        method: method.clone(),
+        trader_id: "mock-trader".to_string(),
+        requisites: MockRequisites {
+            id: Uuid::new_v4().to_string(),
+            bank_type: deposits.pick_bank_type(&mut rng),
+            card_number,
+            recipient_name: "Mock Recipient".to_string(),
+            trader_name: "Mock Trader".to_string(),
+        },
+        callback_uri: request.callback_uri,
+        is_mock: request.is_mock.unwrap_or(false),
+        created_at: now,
+        updated_at: now,
+        expired_at,
+    };
+    
+    state.transactions.write().push(transaction.clone());
+    
+    if let Some(delay) = deposits.settle_after_ms {
+        let outcome = deposits.pick_outcome(&mut rng);
+        let state = state.clone();
+        let id = transaction.id.clone();
+        
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            
+            let open = state.transactions.read().iter().any(|t| t.id == id && !t.is_final());
+            if open {
+                state.set_transaction_status(&id, &outcome);
+            }
+        });
+    }
+    
+    if merchant.name.eq_ignore_ascii_case("wellbit") {
+        return signed_json(StatusCode::CREATED, wellbit_json(&transaction), merchant.private_key.as_deref());
+    }
+    
+    let body = json!({
+        "id": transaction.id,
+        "numericId": transaction.numeric_id,
+        "amount": transaction.amount,
+        "crypto": transaction.rate.filter(|r| *r > 0.0).map(|r| transaction.amount / r),
+        "status": transaction.status,
+        "traderId": transaction.trader_id,
+        "requisites": transaction.requisites,
+        "createdAt": transaction.created_at.to_rfc3339(),
+        "updatedAt": transaction.updated_at.to_rfc3339(),
+        "expired_at": transaction.expired_at.to_rfc3339(),
+        "method": method_json(&transaction.method),
+        "is_mock": transaction.is_mock,
+    });
+    (StatusCode::CREATED, Json(body)).into_response()
+}
+
+/// The create response the backend gives a merchant named "wellbit", the
+/// only native response it signs.
+fn wellbit_json(transaction: &MockTransaction) -> serde_json::Value {
+    let usdt = transaction.rate.filter(|r| *r > 0.0).map(|r| transaction.amount / r);
+    
+    json!({
+        "payment_id": transaction.id,
+        "payment_amount": transaction.amount,
+        "payment_amount_usdt": usdt,
+        "payment_amount_profit": transaction.amount * 0.935,
+        "payment_amount_profit_usdt": usdt.map(|u| u * 0.935),
+        "payment_fee_percent_profit": 6.5,
+        "payment_type": if transaction.method.method_type == "sbp" { "sbp" } else { "card" },
+        "payment_bank": transaction.requisites.bank_type,
+        "payment_course": transaction.rate,
+        "payment_lifetime": (transaction.expired_at - Utc::now()).num_seconds().max(0),
+        "payment_status": "new",
+        "payment_credential": transaction.requisites.card_number,
+    })
+}
+
+async fn list_transactions(
+    State(state): State<Arc<MockState>>,
+    headers: HeaderMap,
+    Query(query): Query<ListQuery>,
+) -> Response {
+    let merchant = match begin(&state, Endpoint::TransactionList, &headers).await {
+        Ok(merchant) => merchant,
+        Err(response) => return response,
+    };
+    
+    state.expire_overdue();
+    
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query.limit.unwrap_or(10).max(1);
+    
+    let mut matching: Vec<MockTransaction> = state
+        .transactions
+        .read()
+        .iter()
+        .filter(|t| t.merchant_id == merchant.id)
+        .filter(|t| query.status.as_ref().is_none_or(|s| &t.status == s))
+        .filter(|t| query.order_id.as_ref().is_none_or(|o| &t.order_id == o))
+        .cloned()
+        .collect();
+    matching.sort_by_key(|t| std::cmp::Reverse(t.created_at));
+    
+    let total = matching.len();
+    let data: Vec<_> = matching.iter().skip((page - 1) * limit).take(limit).map(list_item_json).collect();
+    
+    let body = json!({
+        "data": data,
+        "pagination": {
+            "total": total,
+            "page": page,
+            "limit": limit,
+            "pages": total.div_ceil(limit),
+        },
+    });
+    Json(body).into_response()
+}
+
+async fn transaction_status(
+    State(state): State<Arc<MockState>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Response {
+    let merchant = match begin(&state, Endpoint::TransactionStatus, &headers).await {
+        Ok(merchant) => merchant,
+        Err(response) => return response,
+    };
+    
+    state.expire_overdue();
+    
+    let transaction = state
+        .transactions
+        .read()
+        .iter()
+        .find(|t| t.id == id && t.merchant_id == merchant.id)
+        .cloned();
+    
+    match transaction {
+        Some(transaction) => {
+            let mut body = list_item_json(&transaction);
+            if let Some(object) = body.as_object_mut() {
+                object.remove("isMock");
+            }
+            Json(body).into_response()
+        }
+        None => error(StatusCode::NOT_FOUND, "Transaction not found"),
+    }
+}
+
+async fn cancel_transaction(
+    State(state): State<Arc<MockState>>,
+    headers: HeaderMap,
+    Path(order_id): Path<String>,
+) -> Response {
+    let merchant = match begin(&state, Endpoint::TransactionCancel, &headers).await {
+        Ok(merchant) => merchant,
+        Err(response) => return response,
+    };
+    
+    state.expire_overdue();
+    
+    let existing = state
+        .transactions
+        .read()
+        .iter()
+        .find(|t| t.order_id == order_id && t.merchant_id == merchant.id)
+        .cloned();
+    
+    let Some(existing) = existing else {
+        return error(StatusCode::NOT_FOUND, "Transaction not found");
+    };
+    
+    if existing.status == "EXPIRED" || existing.status == "CANCELED" {
+        return error(StatusCode::BAD_REQUEST, "Transaction is already canceled or expired");
+    }
+    
+    let Some(canceled) = state.set_transaction_status(&existing.id, "CANCELED") else {
+        return error(StatusCode::NOT_FOUND, "Transaction not found");
+    };
+    
+    let body = json!({
+        "success": true,
+        "transaction": {
+            "id": canceled.id,
+            "numericId": canceled.numeric_id,
+            "orderId": canceled.order_id,
+            "amount": canceled.amount,
+            "status": canceled.status,
+            "traderId": canceled.trader_id,
+            "updatedAt": canceled.updated_at.to_rfc3339(),
+        },
+    });
+    Json(body).into_response()
+}
+
+async fn upload_receipt(
+    State(state): State<Arc<MockState>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    body: Bytes,
+) -> Response {
+    let merchant = match begin(&state, Endpoint::ReceiptUpload, &headers).await {
+        Ok(merchant) => merchant,
+        Err(response) => return response,
+    };
+    
+    let request: ReceiptUploadRequest = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(e) => return error(StatusCode::BAD_REQUEST, &format!("Invalid request body: {}", e)),
+    };
+    
+    if !state.transactions.read().iter().any(|t| t.id == id && t.merchant_id == merchant.id) {
+        return error(StatusCode::NOT_FOUND, "Transaction not found");
+    }
+    
+    let receipt = MockReceipt {
+        id: Uuid::new_v4().to_string(),
+        transaction_id: id,
+        file_name: request.file_name,
+        file_data: request.file_data,
+        created_at: Utc::now(),
+    };
+    state.receipts.write().push(receipt.clone());
+    
+    let body = json!({
+        "id": receipt.id,
+        "fileName": receipt.file_name,
+        "isChecked": false,
+        "isFake": false,
+        "isAuto": false,
+        "createdAt": receipt.created_at.to_rfc3339(),
+    });
+    (StatusCode::CREATED, Json(body)).into_response()
+}
@@ -0,0 +1,33 @@
+pub mod control;
+pub mod device;
+pub mod merchant;
+
+use api_signature::{self as signature, SIGNATURE_HEADER};
+use crate::state::MockState;
+use axum::http::{header, HeaderName, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use std::sync::Arc;
+
+pub fn router(state: Arc<MockState>) -> Router {
+    Router::new()
+        .merge(merchant::routes())
+        .merge(device::routes())
+        .merge(control::routes())
+        .with_state(state)
+}
+
+/// JSON response in the backend's canonical form, signed in `x-api-token`
+/// when the merchant has a private key.
+pub(crate) fn signed_json(status: StatusCode, body: serde_json::Value, private_key: Option<&str>) -> Response {
+    let body = signature::canonical_json(&body);
+    let token = private_key.map(|key| signature::sign(key, &body));
+    
+    let mut response = (status, [(header::CONTENT_TYPE, "application/json")], body).into_response();
+    if let Some(token) = token {
+        let value = HeaderValue::from_str(&token).expect("hex digest is a valid header value");
+        response.headers_mut().insert(HeaderName::from_static(SIGNATURE_HEADER), value);
+    }
+    
+    response
+}
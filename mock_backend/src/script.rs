@@ -0,0 +1,149 @@
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Json, Response};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Endpoints whose behaviour can be scripted.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Endpoint {
+    MerchantConnect,
+    MerchantBalance,
+    MerchantEnums,
+    MerchantMethods,
+    TransactionCreate,
+    TransactionList,
+    TransactionStatus,
+    TransactionCancel,
+    ReceiptUpload,
+    DeviceConnect,
+    DeviceNotification,
+    DevicePing,
+    DeviceHealthCheck,
+    DeviceLongPoll,
+    DeviceInfoUpdate,
+}
+
+/// Behaviour of the mock, replaceable at runtime through `PUT /__mock/script`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Script {
+    pub endpoints: HashMap<Endpoint, EndpointBehavior>,
+    pub deposits: DepositScript,
+    pub long_poll_timeout_ms: u64,
+}
+
+impl Default for Script {
+    fn default() -> Self {
+        Self {
+            endpoints: HashMap::new(),
+            deposits: DepositScript::default(),
+            long_poll_timeout_ms: 25_000,
+        }
+    }
+}
+
+/// Injected latency and failures for a single endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EndpointBehavior {
+    pub latency_ms: u64,
+    pub failure_rate: f64, // 0-100% of requests answered with failure_status
+    pub failure_status: u16,
+    pub failure_body: Option<serde_json::Value>, // Defaults to { "error": "Mock failure" }
+    pub fail_next: u32, // The next N requests fail regardless of failure_rate
+    pub retry_after_secs: Option<u64>, // Sent as Retry-After with failures
+}
+
+impl Default for EndpointBehavior {
+    fn default() -> Self {
+        Self {
+            latency_ms: 0,
+            failure_rate: 0.0,
+            failure_status: 500,
+            failure_body: None,
+            fail_next: 0,
+            retry_after_secs: None,
+        }
+    }
+}
+
+impl EndpointBehavior {
+    /// Decides whether this request fails, consuming one of `fail_next`.
+    pub fn roll_failure(&mut self, rng: &mut impl Rng) -> bool {
+        if self.fail_next > 0 {
+            self.fail_next -= 1;
+            return true;
+        }
+        
+        self.failure_rate > 0.0 && rng.gen_range(0.0..100.0) < self.failure_rate
+    }
+    
+    pub fn failure_response(&self) -> Response {
+        let status = StatusCode::from_u16(self.failure_status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let body = self
+            .failure_body
+            .clone()
+            .unwrap_or_else(|| serde_json::json!({ "error": "Mock failure" }));
+        
+        match self.retry_after_secs {
+            Some(secs) => (status, [(header::RETRY_AFTER, secs.to_string())], Json(body)).into_response(),
+            None => (status, Json(body)).into_response(),
+        }
+    }
+}
+
+/// How created deposits are answered and how they settle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DepositScript {
+    pub no_requisite_rate: f64, // 0-100% of creations rejected with 409 NO_REQUISITE
+    pub initial_status: String,
+    pub settle_after_ms: Option<u64>, // None leaves deposits open until changed through /__mock
+    pub outcomes: HashMap<String, f64>, // Final status weights used when settling
+    pub send_callbacks: bool, // POST status changes to the deposit's callback_uri
+    pub bank_types: Vec<String>, // Bank types handed out with requisites
+}
+
+impl Default for DepositScript {
+    fn default() -> Self {
+        Self {
+            no_requisite_rate: 0.0,
+            initial_status: "IN_PROGRESS".to_string(),
+            settle_after_ms: None,
+            outcomes: HashMap::from([("READY".to_string(), 100.0)]),
+            send_callbacks: true,
+            bank_types: vec!["SBERBANK".to_string(), "TBANK".to_string()],
+        }
+    }
+}
+
+impl DepositScript {
+    pub fn pick_outcome(&self, rng: &mut impl Rng) -> String {
+        let total: f64 = self.outcomes.values().filter(|w| **w > 0.0).sum();
+        if total <= 0.0 {
+            return "READY".to_string();
+        }
+        
+        let mut roll = rng.gen_range(0.0..total);
+        for (status, weight) in &self.outcomes {
+            if *weight <= 0.0 {
+                continue;
+            }
+            if roll < *weight {
+                return status.clone();
+            }
+            roll -= weight;
+        }
+        
+        "READY".to_string()
+    }
+    
+    pub fn pick_bank_type(&self, rng: &mut impl Rng) -> String {
+        if self.bank_types.is_empty() {
+            return "SBERBANK".to_string();
+        }
+        self.bank_types[rng.gen_range(0..self.bank_types.len())].clone()
+    }
+}
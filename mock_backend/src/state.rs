@@ -0,0 +1,369 @@
+use crate::script::{Endpoint, Script};
+use api_signature as signature;
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tracing::{info, warn};
+
+const FINAL_STATUSES: [&str; 5] = ["READY", "CANCELED", "EXPIRED", "FUNDS_RETURNED", "MILK"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MockMethod {
+    pub id: String,
+    pub code: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub method_type: String,
+    pub currency: String,
+    pub min_payin: f64,
+    pub max_payin: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MockMerchant {
+    pub id: String,
+    pub name: String,
+    pub api_key: String,
+    #[serde(default)]
+    pub private_key: Option<String>, // Responses and callbacks are signed when set
+    #[serde(default)]
+    pub balance: f64,
+    pub methods: Vec<MockMethod>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MockDevice {
+    pub id: String,
+    pub code: String, // Device code used to connect; also the token, as in the backend
+    pub name: String,
+    #[serde(default)]
+    pub online: bool,
+    #[serde(default)]
+    pub battery_level: Option<u8>,
+    #[serde(default)]
+    pub last_seen: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub notifications: Vec<serde_json::Value>,
+    #[serde(default)]
+    pub pending_commands: VecDeque<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MockRequisites {
+    pub id: String,
+    #[serde(rename = "bankType")]
+    pub bank_type: String,
+    #[serde(rename = "cardNumber")]
+    pub card_number: String,
+    #[serde(rename = "recipientName")]
+    pub recipient_name: String,
+    #[serde(rename = "traderName")]
+    pub trader_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MockTransaction {
+    pub id: String,
+    pub numeric_id: u64,
+    pub order_id: String,
+    pub merchant_id: String,
+    pub amount: f64,
+    pub rate: Option<f64>,
+    pub status: String,
+    pub method: MockMethod,
+    pub trader_id: String,
+    pub requisites: MockRequisites,
+    pub callback_uri: Option<String>,
+    pub is_mock: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub expired_at: DateTime<Utc>,
+}
+
+impl MockTransaction {
+    pub fn is_final(&self) -> bool {
+        FINAL_STATUSES.contains(&self.status.as_str())
+    }
+}
+
+/// A receipt uploaded for a deal, kept as sent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MockReceipt {
+    pub id: String,
+    pub transaction_id: String,
+    pub file_name: String,
+    pub file_data: String, // Base64, optionally a data URI
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub endpoint: Endpoint,
+    pub received_at: DateTime<Utc>,
+    pub injected_failure: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SentCallback {
+    pub transaction_id: String,
+    pub url: String,
+    pub status: String,
+    pub response_status: Option<u16>,
+    pub error: Option<String>,
+    pub sent_at: DateTime<Utc>,
+}
+
+/// Merchants and devices the mock starts with.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Seed {
+    pub merchants: Vec<MockMerchant>,
+    pub devices: Vec<MockDevice>,
+}
+
+impl Default for Seed {
+    fn default() -> Self {
+        let method = |code: &str, name: &str, method_type: &str| MockMethod {
+            id: format!("method-{}", code),
+            code: code.to_string(),
+            name: name.to_string(),
+            method_type: method_type.to_string(),
+            currency: "rub".to_string(),
+            min_payin: 100.0,
+            max_payin: 300_000.0,
+        };
+        
+        Self {
+            merchants: vec![MockMerchant {
+                id: "mock-merchant".to_string(),
+                name: "Mock Merchant".to_string(),
+                api_key: "mock-merchant-key".to_string(),
+                private_key: None,
+                balance: 1000.0,
+                methods: vec![
+                    method("c2c", "Card to card", "c2c"),
+                    method("sbp", "SBP", "sbp"),
+                ],
+            }],
+            devices: vec![MockDevice {
+                id: "mock-device".to_string(),
+                code: "mock-device-code".to_string(),
+                name: "Mock Device".to_string(),
+                online: false,
+                battery_level: None,
+                last_seen: None,
+                notifications: Vec::new(),
+                pending_commands: VecDeque::new(),
+            }],
+        }
+    }
+}
+
+/// In-memory backend state shared by all handlers.
+pub struct MockState {
+    pub merchants: RwLock<Vec<MockMerchant>>,
+    pub devices: RwLock<Vec<MockDevice>>,
+    pub transactions: RwLock<Vec<MockTransaction>>,
+    pub script: RwLock<Script>,
+    pub requests: RwLock<Vec<RecordedRequest>>,
+    pub callbacks: RwLock<Vec<SentCallback>>,
+    pub receipts: RwLock<Vec<MockReceipt>>,
+    pub commands_changed: Notify,
+    next_numeric_id: AtomicU64,
+    http: reqwest::Client,
+}
+
+impl MockState {
+    pub fn new(seed: Seed, script: Script) -> Self {
+        Self {
+            merchants: RwLock::new(seed.merchants),
+            devices: RwLock::new(seed.devices),
+            transactions: RwLock::new(Vec::new()),
+            script: RwLock::new(script),
+            requests: RwLock::new(Vec::new()),
+            callbacks: RwLock::new(Vec::new()),
+            receipts: RwLock::new(Vec::new()),
+            commands_changed: Notify::new(),
+            next_numeric_id: AtomicU64::new(1),
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .expect("default reqwest client"),
+        }
+    }
+    
+    /// Records the request, then applies the endpoint's scripted latency and
+    /// failures. Returns the response to send instead of the real one, if any.
+    pub async fn intercept(&self, endpoint: Endpoint) -> Option<axum::response::Response> {
+        let (latency, failure) = {
+            let mut script = self.script.write();
+            match script.endpoints.get_mut(&endpoint) {
+                Some(behavior) => {
+                    let failed = behavior.roll_failure(&mut rand::thread_rng());
+                    (behavior.latency_ms, failed.then(|| behavior.failure_response()))
+                }
+                None => (0, None),
+            }
+        };
+        
+        self.requests.write().push(RecordedRequest {
+            endpoint,
+            received_at: Utc::now(),
+            injected_failure: failure.is_some(),
+        });
+        
+        if latency > 0 {
+            tokio::time::sleep(Duration::from_millis(latency)).await;
+        }
+        
+        failure
+    }
+    
+    pub fn request_count(&self, endpoint: Endpoint) -> usize {
+        self.requests.read().iter().filter(|r| r.endpoint == endpoint).count()
+    }
+    
+    pub fn merchant_by_key(&self, api_key: &str) -> Option<MockMerchant> {
+        self.merchants.read().iter().find(|m| m.api_key == api_key).cloned()
+    }
+    
+    pub fn next_numeric_id(&self) -> u64 {
+        self.next_numeric_id.fetch_add(1, Ordering::Relaxed)
+    }
+    
+    /// Moves open deposits past their expiry to EXPIRED, like the backend's expiry job.
+    pub fn expire_overdue(self: &Arc<Self>) {
+        let now = Utc::now();
+        let expired: Vec<String> = self
+            .transactions
+            .read()
+            .iter()
+            .filter(|t| !t.is_final() && t.expired_at < now)
+            .map(|t| t.id.clone())
+            .collect();
+        
+        for id in expired {
+            self.set_transaction_status(&id, "EXPIRED");
+        }
+    }
+    
+    /// Changes a deposit's status and sends the callback if the script asks for it.
+    /// Returns the updated deposit, or None if it does not exist.
+    pub fn set_transaction_status(self: &Arc<Self>, id: &str, status: &str) -> Option<MockTransaction> {
+        let updated = {
+            let mut transactions = self.transactions.write();
+            let transaction = transactions.iter_mut().find(|t| t.id == id || t.order_id == id)?;
+            if transaction.status == status {
+                return Some(transaction.clone());
+            }
+            transaction.status = status.to_string();
+            transaction.updated_at = Utc::now();
+            transaction.clone()
+        };
+        
+        info!("Transaction {} ({}) is now {}", updated.id, updated.order_id, updated.status);
+        
+        if self.script.read().deposits.send_callbacks {
+            if let Some(url) = updated.callback_uri.clone() {
+                let state = self.clone();
+                let transaction = updated.clone();
+                tokio::spawn(async move { state.send_callback(&transaction, url).await });
+            }
+        }
+        
+        Some(updated)
+    }
+    
+    async fn send_callback(&self, transaction: &MockTransaction, url: String) {
+        let body = signature::canonical_json(&serde_json::json!({
+            "id": transaction.order_id,
+            "amount": transaction.amount,
+            "status": transaction.status,
+        }));
+        
+        let private_key = self
+            .merchants
+            .read()
+            .iter()
+            .find(|m| m.id == transaction.merchant_id)
+            .and_then(|m| m.private_key.clone());
+        
+        let mut request = self
+            .http
+            .post(&url)
+            .header("Content-Type", "application/json")
+            .body(body.clone());
+        if let Some(private_key) = private_key {
+            request = request.header(signature::SIGNATURE_HEADER, signature::sign(&private_key, &body));
+        }
+        
+        let result = request.send().await;
+        if let Err(e) = &result {
+            warn!("Callback to {} failed: {}", url, e);
+        }
+        
+        self.callbacks.write().push(SentCallback {
+            transaction_id: transaction.id.clone(),
+            url,
+            status: transaction.status.clone(),
+            response_status: result.as_ref().ok().map(|r| r.status().as_u16()),
+            error: result.err().map(|e| e.to_string()),
+            sent_at: Utc::now(),
+        });
+    }
+    
+    /// Queues a command for the device's next long-poll.
+    pub fn push_command(&self, device_id: &str, command: serde_json::Value) -> bool {
+        let queued = match self.devices.write().iter_mut().find(|d| d.id == device_id) {
+            Some(device) => {
+                device.pending_commands.push_back(command);
+                true
+            }
+            None => false,
+        };
+        
+        if queued {
+            self.commands_changed.notify_waiters();
+        }
+        queued
+    }
+    
+    pub fn pop_command(&self, device_id: &str) -> Option<serde_json::Value> {
+        self.devices
+            .write()
+            .iter_mut()
+            .find(|d| d.id == device_id)
+            .and_then(|d| d.pending_commands.pop_front())
+    }
+    
+    /// Clears deposits, logs and device activity, keeping merchants, devices and the script.
+    pub fn reset(&self) {
+        self.transactions.write().clear();
+        self.requests.write().clear();
+        self.callbacks.write().clear();
+        for device in self.devices.write().iter_mut() {
+            device.online = false;
+            device.notifications.clear();
+            device.pending_commands.clear();
+        }
+    }
+    
+    pub fn snapshot(&self) -> serde_json::Value {
+        let mut per_endpoint: HashMap<Endpoint, usize> = HashMap::new();
+        for request in self.requests.read().iter() {
+            *per_endpoint.entry(request.endpoint).or_default() += 1;
+        }
+        
+        serde_json::json!({
+            "merchants": *self.merchants.read(),
+            "devices": *self.devices.read(),
+            "transactions": *self.transactions.read(),
+            "callbacks": *self.callbacks.read(),
+            "requests": per_endpoint,
+        })
+    }
+}
@@ -0,0 +1,75 @@
+use api_signature::{verify, SIGNATURE_HEADER};
+use mock_backend::{MockBackend, Script, Seed};
+use serde_json::json;
+
+const PRIVATE_KEY: &str = "mock-private-key";
+
+/// The seeded merchant with a private key, named as the backend names its Wellbit merchant or not.
+async fn keyed_backend(name: &str) -> MockBackend {
+    let mut seed = Seed::default();
+    seed.merchants[0].name = name.to_string();
+    seed.merchants[0].private_key = Some(PRIVATE_KEY.to_string());
+    MockBackend::start("127.0.0.1:0", seed, Script::default()).await.unwrap()
+}
+
+fn request(backend: &MockBackend, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+    reqwest::Client::new()
+        .request(method, format!("{}{}", backend.base_url(), path))
+        .header("x-merchant-api-key", "mock-merchant-key")
+}
+
+/// Raw body and signature header of a deposit created for 1500.
+async fn create(backend: &MockBackend) -> (String, Option<String>) {
+    let response = request(backend, reqwest::Method::POST, "/api/merchant/transactions/create")
+        .json(&json!({ "amount": 1500, "orderId": "signed-order", "methodId": "method-c2c", "rate": 100 }))
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success(), "create returned {}", response.status());
+    
+    let signature = response.headers().get(SIGNATURE_HEADER).map(|v| v.to_str().unwrap().to_string());
+    (response.text().await.unwrap(), signature)
+}
+
+#[tokio::test]
+async fn wellbit_create_responses_verify() {
+    let backend = keyed_backend("Wellbit").await;
+    
+    let (body, signature) = create(&backend).await;
+    let signature = signature.expect("wellbit create response signed");
+    
+    assert!(verify(PRIVATE_KEY, &body, &signature), "failed verification: {}", body);
+    assert!(!verify("another-key", &body, &signature));
+}
+
+#[tokio::test]
+async fn wellbit_create_responses_are_sent_in_canonical_form() {
+    let backend = keyed_backend("wellbit").await;
+    
+    let (body, _) = create(&backend).await;
+    
+    // Keys are built in the backend's order but go out sorted
+    let keys: Vec<_> = ["\"payment_amount\"", "\"payment_bank\"", "\"payment_credential\"", "\"payment_id\"", "\"payment_status\""]
+        .iter()
+        .map(|key| body.find(key).unwrap())
+        .collect();
+    assert!(keys.windows(2).all(|pair| pair[0] < pair[1]), "{}", body);
+    
+    // A whole amount is printed as JavaScript prints it
+    assert!(body.contains(r#""payment_amount":1500,"#), "{}", body);
+}
+
+#[tokio::test]
+async fn native_responses_are_unsigned() {
+    let backend = keyed_backend("Mock Merchant").await;
+    
+    for path in ["/api/merchant/connect", "/api/merchant/balance", "/api/merchant/enums", "/api/merchant/methods"] {
+        let response = request(&backend, reqwest::Method::GET, path).send().await.unwrap();
+        assert!(response.status().is_success(), "{} returned {}", path, response.status());
+        assert!(response.headers().get(SIGNATURE_HEADER).is_none(), "{} was signed", path);
+    }
+    
+    let (body, signature) = create(&backend).await;
+    assert!(signature.is_none(), "native create was signed");
+    assert!(body.contains("\"requisites\""), "{}", body);
+}